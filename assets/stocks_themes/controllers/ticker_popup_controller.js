@@ -2,6 +2,7 @@ import { Controller } from "https://unpkg.com/@hotwired/stimulus@3.2.2/dist/stim
 
 import { popupApi } from "../popup_api.js";
 import { FundamentalsChart } from "../fundamentals_chart.js";
+import { PeersList } from "../peers_list.js";
 import { PopupCharts } from "../popup_charts.js";
 import { RrgChart } from "../rrg_chart.js";
 
@@ -13,10 +14,13 @@ export default class extends Controller {
         "tagsTab",
         "fundamentalsTab",
         "rrgTab",
+        "peersTab",
         "chartsPanel",
         "tagsPanel",
         "fundamentalsPanel",
         "rrgPanel",
+        "peersPanel",
+        "peersBody",
         "tagsFrame",
         "rrgTickerToggle",
         "fundamentalsStatus",
@@ -82,6 +86,13 @@ export default class extends Controller {
                 this.activeTab() === "fundamentals" &&
                 this.info?.ticker === info.ticker,
         });
+        this.peersList = new PeersList({
+            body: this.peersBodyTarget,
+            isCurrent: info =>
+                this.isOpen() &&
+                this.activeTab() === "peers" &&
+                this.info?.ticker === info.ticker,
+        });
         popupApi.register(this);
     }
 
@@ -108,25 +119,29 @@ export default class extends Controller {
     }
 
     switchTo(tab) {
-        const nextTab = ["charts", "rrg", "tags", "fundamentals"].includes(tab) ? tab : "fundamentals";
+        const nextTab = ["charts", "rrg", "tags", "peers", "fundamentals"].includes(tab) ? tab : "fundamentals";
         const isCharts = nextTab === "charts";
         const isTags = nextTab === "tags";
         const isFundamentals = nextTab === "fundamentals";
         const isRrg = nextTab === "rrg";
+        const isPeers = nextTab === "peers";
         this.activeTabName = nextTab;
 
         this.chartsTabTarget.classList.toggle("active", isCharts);
         this.tagsTabTarget.classList.toggle("active", isTags);
         this.fundamentalsTabTarget.classList.toggle("active", isFundamentals);
         this.rrgTabTarget.classList.toggle("active", isRrg);
+        this.peersTabTarget.classList.toggle("active", isPeers);
         this.chartsPanelTarget.classList.toggle("active", isCharts);
         this.tagsPanelTarget.classList.toggle("active", isTags);
         this.fundamentalsPanelTarget.classList.toggle("active", isFundamentals);
         this.rrgPanelTarget.classList.toggle("active", isRrg);
+        this.peersPanelTarget.classList.toggle("active", isPeers);
         this.rrgTickerToggleTarget.style.display = isRrg ? "" : "none";
 
         if (!this.info) return;
 
+        this.peersList.cancel();
         if (isRrg) {
             this.popupCharts.destroy();
             this.fundamentalsChart.cancel();
@@ -139,6 +154,11 @@ export default class extends Controller {
             this.popupCharts.destroy();
             this.rrgChart.cancel();
             this.fundamentalsChart.render(this.info);
+        } else if (isPeers) {
+            this.popupCharts.destroy();
+            this.rrgChart.cancel();
+            this.fundamentalsChart.cancel();
+            this.peersList.render(this.info);
         } else {
             this.popupCharts.destroy();
             this.rrgChart.cancel();
@@ -148,7 +168,7 @@ export default class extends Controller {
     }
 
     move(direction) {
-        const tabs = ["charts", "rrg", "tags", "fundamentals"];
+        const tabs = ["charts", "rrg", "tags", "peers", "fundamentals"];
         const index = tabs.indexOf(this.activeTabName);
         this.switchTo(tabs[Math.max(0, Math.min(tabs.length - 1, index + direction))]);
     }
@@ -186,6 +206,7 @@ export default class extends Controller {
         this.popupCharts.destroy();
         this.rrgChart.cancel();
         this.fundamentalsChart.cancel();
+        this.peersList.cancel();
         this.info = null;
         this.tagsOpened = false;
         if (tagsOpened) this.dispatch("tags-dismissed", { prefix: "stock-themes" });
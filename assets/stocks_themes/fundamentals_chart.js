@@ -81,7 +81,10 @@ export class FundamentalsChart {
             const response = await fetch(refresh ? `${path}/refresh` : path, {
                 method: refresh ? "POST" : "GET",
             });
-            if (!response.ok) throw new Error(await response.text());
+            if (!response.ok) {
+                const body = await response.json().catch(() => ({}));
+                throw new Error(body.message || `HTTP ${response.status}`);
+            }
             const [data] = await Promise.all([response.json(), loadChartJs()]);
             if (!this.isCurrentRequest(requestId, info)) return;
             this.cache.set(this.key(info), data);
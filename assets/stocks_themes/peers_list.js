@@ -0,0 +1,74 @@
+export class PeersList {
+    constructor({ body, isCurrent }) {
+        this.body = body;
+        this.isCurrent = isCurrent;
+        this.cache = new Map();
+        this.requestId = 0;
+    }
+
+    render(info) {
+        const requestId = ++this.requestId;
+        if (this.cache.has(info.ticker)) {
+            this.draw(this.cache.get(info.ticker));
+            return;
+        }
+        this.body.innerHTML = '<div class="peers-status">Loading peers…</div>';
+        this.fetchAndDraw(info, requestId);
+    }
+
+    cancel() {
+        this.requestId++;
+    }
+
+    async fetchAndDraw(info, requestId) {
+        try {
+            const response = await fetch(`/api/peers/${encodeURIComponent(info.ticker)}`);
+            const body = await response.json();
+            if (!response.ok) throw new Error(body.message || `HTTP ${response.status}`);
+            this.cache.set(info.ticker, body);
+            if (requestId === this.requestId && this.isCurrent(info)) this.draw(body);
+        } catch (err) {
+            if (requestId === this.requestId && this.isCurrent(info)) {
+                this.body.innerHTML = `<div class="peers-status">${this.escapeHtml(err.message)}</div>`;
+            }
+        }
+    }
+
+    draw(data) {
+        if (!data.peers.length) {
+            this.body.innerHTML = '<div class="peers-status">No peers found</div>';
+            return;
+        }
+        const fmt = (value, digits = 2) => value == null ? "—" : value.toFixed(digits);
+        this.body.innerHTML = `
+            <table class="overview-table peers-table">
+                <thead>
+                    <tr>
+                        <th>Ticker</th><th>Score</th><th>Corr</th><th>RS</th><th>ADR%</th><th>Avg Vol</th><th>Shared</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    ${data.peers.map(peer => `
+                        <tr>
+                            <td>${peer.exchange
+                                ? `<a href="https://www.tradingview.com/chart/?symbol=${encodeURIComponent(`${peer.exchange}:${peer.ticker}`)}" target="_blank" rel="noopener noreferrer">${this.escapeHtml(peer.ticker)}</a>`
+                                : this.escapeHtml(peer.ticker)}</td>
+                            <td>${fmt(peer.score)}</td>
+                            <td>${fmt(peer.correlation)}</td>
+                            <td>${fmt(peer.rs)}</td>
+                            <td>${fmt(peer.metrics?.adr_pct, 1)}</td>
+                            <td>${peer.metrics ? peer.metrics.avg_volume.toLocaleString() : "—"}</td>
+                            <td>${[peer.same_industry ? "Industry" : "", ...peer.shared_tags].filter(Boolean).map(name => this.escapeHtml(name)).join(", ")}</td>
+                        </tr>
+                    `).join("")}
+                </tbody>
+            </table>
+        `;
+    }
+
+    escapeHtml(value) {
+        const node = document.createElement("div");
+        node.textContent = String(value ?? "");
+        return node.innerHTML;
+    }
+}
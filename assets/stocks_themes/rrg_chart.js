@@ -1,4 +1,4 @@
-const RRG_PARAMS = "timeframe=daily&tail=20&history=200&period_weeks=10";
+const RRG_PARAMS = { timeframe: "daily", tail: 20, history: 200, period_weeks: 10 };
 
 export class RrgChart {
     constructor({ body, canvas, tickerToggle, isCurrent }) {
@@ -78,18 +78,32 @@ export class RrgChart {
 
         let datasets;
         try {
-            datasets = await Promise.all(etfs.map(async entry => {
-                const response = await fetch(`/api/rrg/${entry.etf}?${RRG_PARAMS}`);
-                if (!response.ok) throw new Error(response.status);
-                return {
-                    ...(await response.json()),
+            // One batch so the benchmark is fetched and resampled once.
+            const response = await fetch("/api/rrg/batch", {
+                method: "POST",
+                headers: { "Content-Type": "application/json" },
+                body: JSON.stringify({ tickers: etfs.map(entry => entry.etf), ...RRG_PARAMS }),
+            });
+            if (!response.ok) {
+                const body = await response.json().catch(() => ({}));
+                throw new Error(body.message || `HTTP ${response.status}`);
+            }
+            const rows = new Map((await response.json()).map(row => [row.ticker, row]));
+            datasets = etfs
+                .map(entry => ({ entry, row: rows.get(entry.etf.toUpperCase()) }))
+                .filter(({ entry, row }) => {
+                    if (!row?.rrg) console.warn(`Failed to load RRG for ${entry.etf}:`, row?.error);
+                    return row?.rrg;
+                })
+                .map(({ entry, row }) => ({
+                    ...row.rrg,
                     ...entry,
                     isTicker: entry.isTicker ?? false,
-                };
-            }));
-        } catch (_) {
+                }));
+            if (datasets.length === 0) throw new Error("No RRG data");
+        } catch (err) {
             if (this.isCurrentRequest(requestId, info)) {
-                this.renderMessage("Failed to load RRG data", "#e74c3c");
+                this.renderMessage(`Failed to load RRG data: ${err.message}`, "#e74c3c");
             }
             return;
         }
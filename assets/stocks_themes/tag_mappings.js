@@ -45,13 +45,16 @@ class TagMappings {
     }
 
     async fetchJson(url) {
+        const offline = window.OFFLINE_DATA?.[url];
+        if (offline) return offline;
+
         const response = await fetch(url);
         if (response.ok) return response.json();
 
         let message = `HTTP ${response.status}`;
         try {
             const body = await response.json();
-            if (body?.error) message = body.error;
+            if (body?.message) message = body.message;
         } catch (_) {
             // Keep the HTTP status when the response body is not JSON.
         }
@@ -10,7 +10,7 @@ export async function api(path, options = {}) {
             let message = `HTTP ${response.status}`;
             try {
                 const body = await response.json();
-                message = body.error || message;
+                message = body.message || message;
             } catch (_) {}
             throw new Error(message);
         }
@@ -20,6 +20,10 @@ export default class extends Controller {
         "newTagForm",
         "newTagCategory",
         "newTagName",
+        "exportForm",
+        "exportBy",
+        "exportOrder",
+        "exportSelected",
     ];
 
     connect() {
@@ -155,6 +159,28 @@ export default class extends Controller {
         }
     }
 
+    toggleExportForm() {
+        if (!this.hasExportFormTarget) return;
+        this.exportFormTarget.style.display = this.exportFormTarget.style.display === "none" ? "grid" : "none";
+    }
+
+    // The server answers with an attachment, so navigating downloads the file.
+    exportTradingView() {
+        const params = new URLSearchParams({
+            by: this.exportByTarget.value,
+            order: this.exportOrderTarget.value,
+        });
+        if (this.exportSelectedTarget.checked) {
+            const names = state.tags.filter(tag => state.selectedTagIds.has(tag.id)).map(tag => tag.name);
+            if (!names.length) {
+                showStatus("Select the tags to export first", "error");
+                return;
+            }
+            params.set("tags", names.join(","));
+        }
+        window.location.href = `/api/stock-tags/export/tradingview?${params}`;
+    }
+
     maybeCreateTag(event) {
         if (event.key === "Enter") this.createTag();
     }
@@ -61,16 +61,20 @@ export default class extends Controller {
     }
 
     renderWorkflowIntro() {
-        if (!state.tagSuggestionEnabled) state.homeTab = "manual";
+        if (!state.tagSuggestionEnabled && state.homeTab === "batch") state.homeTab = "manual";
         this.bodyTarget.innerHTML = `
             <div class="home-tabs">
                 <button class="${state.homeTab === "manual" ? "active" : ""}" data-tab="manual" data-action="click->workspace#switchHomeTab">Manual</button>
                 ${state.tagSuggestionEnabled ? `<button class="${state.homeTab === "batch" ? "active" : ""}" data-tab="batch" data-action="click->workspace#switchHomeTab">Batch</button>` : ""}
+                <button class="${state.homeTab === "discover" ? "active" : ""}" data-tab="discover" data-action="click->workspace#switchHomeTab">Discover</button>
             </div>
             <div id="home-tab-body"></div>
         `;
         if (state.homeTab === "batch") {
             this.renderBatchWorkflow();
+        } else if (state.homeTab === "discover") {
+            this.renderDiscoverWorkflow();
+            this.stopBatchPolling();
         } else {
             this.renderManualWorkflow();
             this.stopBatchPolling();
@@ -109,6 +113,102 @@ export default class extends Controller {
         this.renderWorkflowIntro();
     }
 
+    renderDiscoverWorkflow() {
+        const discover = state.discover;
+        document.getElementById("home-tab-body").innerHTML = `
+            <div class="section">
+                <div class="selected-title">Correlation Clusters</div>
+                <div class="muted">Group stocks whose daily returns move together, then compare each group with its current tags.</div>
+            </div>
+            <div class="batch-filters">
+                <input id="discover-window" type="number" min="10" step="1" title="Daily returns" value="${discover.window}">
+                <input id="discover-threshold" type="number" min="-1" max="1" step="0.05" title="Correlation threshold" value="${discover.threshold}">
+                <input id="discover-min-size" type="number" min="2" step="1" title="Minimum cluster size" value="${discover.minSize}">
+                <label class="prompt-options">
+                    <input id="discover-untagged-only" type="checkbox" ${discover.untaggedOnly ? "checked" : ""}>
+                    No shared tag only
+                </label>
+            </div>
+            <div class="section row-actions">
+                <button class="btn primary" ${discover.loading ? "disabled" : ""} data-action="click->workspace#runDiscovery">${discover.loading ? "Clustering" : "Find clusters"}</button>
+            </div>
+            <div id="discover-results">${this.discoverResultsHtml()}</div>
+        `;
+    }
+
+    discoverResultsHtml() {
+        const result = state.discover.result;
+        if (!result) return "";
+        if (!result.clusters.length) {
+            return `<div class="section muted">No clusters among ${result.tickers_considered} tickers at this threshold.</div>`;
+        }
+        return `
+            <div class="section stats">
+                <span class="stat"><b>${result.clusters.length}</b> clusters</span>
+                <span class="stat"><b>${result.tickers_considered}</b> tickers with ${result.window}d history</span>
+            </div>
+            ${result.clusters.map(cluster => `
+                <div class="section">
+                    <div class="section-title-row">
+                        <div class="section-title">${cluster.members.length} stocks · ρ ${cluster.avg_correlation.toFixed(2)}</div>
+                        <span class="muted">${cluster.shared_tags.length
+                            ? `Shared: ${escapeHtml(cluster.shared_tags.join(", "))}`
+                            : cluster.dominant_tag
+                                ? `No shared tag · ${escapeHtml(cluster.dominant_tag)} ${Math.round(cluster.dominant_tag_coverage * 100)}%`
+                                : "No shared tag"}</span>
+                    </div>
+                    <table class="batch-table">
+                        <tbody>
+                            ${cluster.members.map(member => `
+                                <tr>
+                                    <td class="ticker"><a class="ticker-link" href="/tags_mgmt.html?selectedTicker=${encodeURIComponent(member.ticker)}" data-ticker="${escapeAttr(member.ticker)}" data-action="click->workspace#selectLinkedTicker">${escapeHtml(member.ticker)}</a></td>
+                                    <td><div class="stock-tags">${member.tags.length
+                                        ? member.tags.map(tag => `<span class="chip"><span>${escapeHtml(tag)}</span></span>`).join("")
+                                        : '<span class="no-tags">No tags</span>'}</div></td>
+                                </tr>
+                            `).join("")}
+                        </tbody>
+                    </table>
+                </div>
+            `).join("")}
+        `;
+    }
+
+    async runDiscovery() {
+        const discover = state.discover;
+        discover.window = Number(document.getElementById("discover-window").value) || discover.window;
+        discover.threshold = Number(document.getElementById("discover-threshold").value);
+        discover.minSize = Number(document.getElementById("discover-min-size").value) || discover.minSize;
+        discover.untaggedOnly = document.getElementById("discover-untagged-only").checked;
+
+        const params = new URLSearchParams({
+            window: discover.window,
+            threshold: discover.threshold,
+            min_size: discover.minSize,
+            untagged_only: discover.untaggedOnly,
+        });
+        discover.loading = true;
+        this.renderDiscoverWorkflow();
+        try {
+            discover.result = await api(`/api/tag-discovery/clusters?${params}`);
+        } catch (err) {
+            showStatus(err.message, "error");
+        } finally {
+            discover.loading = false;
+            if (state.homeTab === "discover" && document.getElementById("discover-results")) {
+                this.renderDiscoverWorkflow();
+            }
+        }
+    }
+
+    selectLinkedTicker(event) {
+        event.preventDefault();
+        state.selectedTicker = event.currentTarget.dataset.ticker;
+        state.untaggedSelected = false;
+        state.selectedTagIds.clear();
+        requestRender();
+    }
+
     renderBatchWorkflow() {
         const body = document.getElementById("home-tab-body");
         body.innerHTML = `
@@ -581,9 +681,14 @@ export default class extends Controller {
                 <div class="section-title">Available Tags</div>
                 <div class="stock-tags available-tags" id="available-tags"></div>
             </div>
+            <div class="section">
+                <div class="section-title">Peers</div>
+                <div id="peers-body"><div class="profile-status">Loading peers</div></div>
+            </div>
         `;
 
         this.loadCompanyProfile(stock.ticker);
+        this.loadPeers(stock.ticker);
         this.loadCachedTagSuggestion(stock.ticker);
         this.renderTagPicker();
         document.getElementById("stock-tag-input")?.focus();
@@ -920,6 +1025,36 @@ export default class extends Controller {
         }
     }
 
+    async loadPeers(ticker) {
+        let body;
+        try {
+            const result = await api(`/api/peers/${encodeURIComponent(ticker)}?limit=12`);
+            body = result.peers.length ? `
+                <table class="batch-table">
+                    <thead><tr><th>Ticker</th><th>Score</th><th>Corr</th><th>RS</th><th>Why</th></tr></thead>
+                    <tbody>
+                        ${result.peers.map(peer => `
+                            <tr>
+                                <td class="ticker"><a class="ticker-link" href="/tags_mgmt.html?selectedTicker=${encodeURIComponent(peer.ticker)}" data-ticker="${escapeAttr(peer.ticker)}" data-action="click->workspace#selectLinkedTicker">${escapeHtml(peer.ticker)}</a></td>
+                                <td>${peer.score.toFixed(2)}</td>
+                                <td>${peer.correlation == null ? "—" : peer.correlation.toFixed(2)}</td>
+                                <td>${peer.rs == null ? "—" : peer.rs.toFixed(2)}</td>
+                                <td><div class="stock-tags">
+                                    ${peer.same_industry ? '<span class="chip"><span>Same industry</span></span>' : ""}
+                                    ${peer.shared_tags.map(tag => `<span class="chip"><span>${escapeHtml(tag)}</span></span>`).join("")}
+                                </div></td>
+                            </tr>
+                        `).join("")}
+                    </tbody>
+                </table>
+            ` : '<div class="profile-status">No peers found</div>';
+        } catch (err) {
+            body = `<div class="profile-status profile-error">${escapeHtml(err.message)}</div>`;
+        }
+        const node = document.getElementById("peers-body");
+        if (node && state.selectedTicker === ticker) node.innerHTML = body;
+    }
+
     async refreshCompanyProfile() {
         if (!state.selectedTicker) return;
         await this.loadCompanyProfile(state.selectedTicker, true);
@@ -32,6 +32,14 @@ export const state = {
         activeSuggestionTickers: new Set(),
         pollTimer: null,
     },
+    discover: {
+        window: 63,
+        threshold: 0.6,
+        minSize: 3,
+        untaggedOnly: false,
+        loading: false,
+        result: null,
+    },
 };
 
 export function setData({ tags, categories, stocks, untagged }) {
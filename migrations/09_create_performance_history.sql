@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS performance_history
+(
+    ticker      TEXT NOT NULL,
+    ticker_type TEXT NOT NULL,
+    as_of       DATE NOT NULL,
+    perf_1m     REAL NOT NULL,
+    perf_3m     REAL NOT NULL,
+    perf_6m     REAL NOT NULL,
+    perf_1y     REAL NOT NULL,
+    rs          REAL NOT NULL,
+    rank        INTEGER NOT NULL,
+    PRIMARY KEY (ticker, ticker_type, as_of)
+);
+
+CREATE INDEX IF NOT EXISTS idx_performance_history_type_date
+    ON performance_history (ticker_type, as_of);
@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS sector_etfs
+(
+    sector     TEXT     NOT NULL PRIMARY KEY COLLATE NOCASE,
+    etf        TEXT     NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS industry_etfs
+(
+    industry   TEXT     NOT NULL PRIMARY KEY COLLATE NOCASE,
+    sector     TEXT     NOT NULL COLLATE NOCASE,
+    etf        TEXT     NOT NULL,
+    updated_at DATETIME NOT NULL,
+    FOREIGN KEY (sector) REFERENCES sector_etfs (sector) ON DELETE CASCADE ON UPDATE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_industry_etfs_sector ON industry_etfs (sector);
+
+-- Records that the bundled ETF mapping was seeded, so deleting every mapping
+-- through the API doesn't bring the defaults back.
+CREATE TABLE IF NOT EXISTS etf_mapping_seed
+(
+    id        INTEGER  NOT NULL PRIMARY KEY CHECK (id = 1),
+    seeded_at DATETIME NOT NULL
+);
+
+INSERT OR IGNORE INTO etf_mapping_seed (id, seeded_at)
+SELECT 1, CURRENT_TIMESTAMP
+WHERE EXISTS (SELECT 1 FROM sector_etfs);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS etf_holdings
+(
+    etf         TEXT     NOT NULL COLLATE NOCASE,
+    ticker      TEXT     NOT NULL,
+    name        TEXT     NOT NULL DEFAULT '',
+    weight      REAL     NOT NULL,
+    as_of       DATE     NOT NULL,
+    source_file TEXT     NOT NULL,
+    imported_at DATETIME NOT NULL,
+    PRIMARY KEY (etf, ticker)
+);
+
+CREATE INDEX IF NOT EXISTS idx_etf_holdings_ticker ON etf_holdings (ticker);
@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS watchlist_runs
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    run_date   DATE     NOT NULL,
+    created_at DATETIME NOT NULL,
+    source     TEXT     NOT NULL DEFAULT ''
+);
+
+CREATE INDEX IF NOT EXISTS idx_watchlist_runs_date ON watchlist_runs (run_date);
+
+CREATE TABLE IF NOT EXISTS watchlist_run_stocks
+(
+    run_id   INTEGER NOT NULL REFERENCES watchlist_runs (id) ON DELETE CASCADE,
+    ticker   TEXT    NOT NULL,
+    exchange TEXT    NOT NULL DEFAULT '',
+    sector   TEXT    NOT NULL,
+    industry TEXT    NOT NULL,
+    PRIMARY KEY (run_id, ticker)
+);
+
+CREATE TABLE IF NOT EXISTS watchlist_run_tags
+(
+    run_id INTEGER NOT NULL REFERENCES watchlist_runs (id) ON DELETE CASCADE,
+    ticker TEXT    NOT NULL,
+    tag    TEXT    NOT NULL,
+    PRIMARY KEY (run_id, ticker, tag)
+);
@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS watchlists
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    name       TEXT     NOT NULL UNIQUE COLLATE NOCASE,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS watchlist_items
+(
+    watchlist_id INTEGER NOT NULL REFERENCES watchlists (id) ON DELETE CASCADE,
+    position     INTEGER NOT NULL,
+    ticker       TEXT    NOT NULL,
+    exchange     TEXT    NOT NULL DEFAULT '',
+    section      TEXT    NOT NULL DEFAULT '',
+    PRIMARY KEY (watchlist_id, ticker)
+);
@@ -0,0 +1,75 @@
+-- Class shares were stored in whatever notation the input used (BRK-B,
+-- BRK/B). Rewrite them to the canonical BRK.B; rows that would duplicate an
+-- existing canonical row are dropped.
+
+UPDATE OR IGNORE stocks
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM stocks
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE company_profiles
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM company_profiles
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE stock_tags
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM stock_tags
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE tag_suggestions
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM tag_suggestions
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE daily_candles
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM daily_candles
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE hourly_candles
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM hourly_candles
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE performance
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM performance
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE performance_history
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM performance_history
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE etf_holdings
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM etf_holdings
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE watchlist_run_stocks
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM watchlist_run_stocks
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE watchlist_run_tags
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM watchlist_run_tags
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+
+UPDATE OR IGNORE watchlist_items
+SET ticker = substr(ticker, 1, length(ticker) - 2) || '.' || substr(ticker, -1)
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
+DELETE FROM watchlist_items
+WHERE ticker GLOB '[A-Z0-9]*[-/][A-Z]' AND substr(ticker, 1, length(ticker) - 2) NOT GLOB '*[^A-Z0-9]*';
@@ -0,0 +1,8 @@
+-- Bare tickers TradingView resolved to a listing with another ID (SHOP ->
+-- SHOP.TO when only the TSX listing matches), so the next run finds the
+-- stored stock without resolving it again.
+CREATE TABLE IF NOT EXISTS ticker_aliases
+(
+    alias  TEXT NOT NULL PRIMARY KEY,
+    ticker TEXT NOT NULL
+);
@@ -0,0 +1,283 @@
+use axum::{
+    Json,
+    body::to_bytes,
+    extract::Request,
+    http::{
+        HeaderValue, StatusCode,
+        header::{CONTENT_TYPE, RETRY_AFTER},
+    },
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+
+use crate::yf::YfError;
+
+/// Yahoo Finance does not say how long a rate limit lasts, so clients are
+/// told to back off for a minute.
+const RATE_LIMIT_RETRY_SECS: u64 = 60;
+
+/// Extractor rejections are a line of text; anything longer is cut off.
+const MAX_REJECTION_BYTES: usize = 16 * 1024;
+
+/// Invalid input detected below the handler; maps to HTTP 400.
+#[derive(Debug)]
+pub struct ValidationError(pub String);
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A named resource (watchlist, run, ...) doesn't exist; maps to HTTP 404.
+#[derive(Debug)]
+pub struct NotFoundError(pub String);
+
+impl fmt::Display for NotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFoundError {}
+
+/// Error returned by `/api/*` handlers, rendered as `{code, message, details}`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    details: Option<Value>,
+    retry_after: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    code: &'a str,
+    message: &'a str,
+    details: &'a Option<Value>,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+            retry_after: None,
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl<E> From<E> for ApiError
+where
+    E: Into<anyhow::Error>,
+{
+    fn from(error: E) -> Self {
+        let error = error.into();
+        let message = format!("{error:#}");
+        for cause in error.chain() {
+            if let Some(yf_error) = cause.downcast_ref::<YfError>() {
+                return match yf_error {
+                    YfError::NotFound { .. } => Self::not_found(message),
+                    YfError::RateLimited => Self {
+                        retry_after: Some(RATE_LIMIT_RETRY_SECS),
+                        ..Self::new(StatusCode::TOO_MANY_REQUESTS, "rate_limited", message)
+                    },
+                };
+            }
+            if cause.is::<ValidationError>() {
+                return Self::bad_request(message);
+            }
+            if cause.is::<NotFoundError>() {
+                return Self::not_found(message);
+            }
+            match cause.downcast_ref::<sqlx::Error>() {
+                Some(sqlx::Error::RowNotFound) => return Self::not_found(message),
+                Some(sqlx::Error::Database(db_error))
+                    if db_error.is_unique_violation() || db_error.is_foreign_key_violation() =>
+                {
+                    return Self::conflict(message);
+                }
+                _ => {}
+            }
+        }
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal", message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            code: self.code,
+            message: &self.message,
+            details: &self.details,
+        };
+        let mut response = (self.status, Json(body)).into_response();
+        if let Some(secs) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
+    }
+}
+
+/// Middleware giving the plain-text client errors under `/api/*` (axum's
+/// `Json`, `Query` and `Path` rejections, unknown routes) the same
+/// `{code, message, details}` body as [`ApiError`].
+pub async fn json_rejections(request: Request, next: Next) -> Response {
+    let is_api = request.uri().path().starts_with("/api/");
+    let response = next.run(request).await;
+    if is_api {
+        as_json_error(response).await
+    } else {
+        response
+    }
+}
+
+async fn as_json_error(response: Response) -> Response {
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if !status.is_client_error() || is_json {
+        return response;
+    }
+
+    let body = to_bytes(response.into_body(), MAX_REJECTION_BYTES)
+        .await
+        .unwrap_or_default();
+    let message = match String::from_utf8_lossy(&body).trim() {
+        "" => status
+            .canonical_reason()
+            .unwrap_or("Bad request")
+            .to_string(),
+        text => text.to_string(),
+    };
+    let code = match status {
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        _ => "bad_request",
+    };
+    ApiError::new(status, code, message).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_wrapped_errors() {
+        let not_found = anyhow::Error::new(YfError::NotFound {
+            url: "https://example.com".to_string(),
+        })
+        .context("Failed to fetch candles for ZZZZ");
+        assert_eq!(ApiError::from(not_found).status(), StatusCode::NOT_FOUND);
+
+        let rate_limited = ApiError::from(YfError::RateLimited).into_response();
+        assert_eq!(rate_limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(rate_limited.headers()[RETRY_AFTER], "60");
+
+        let invalid = anyhow::Error::new(ValidationError("top must be at least 1".to_string()));
+        assert_eq!(ApiError::from(invalid).status(), StatusCode::BAD_REQUEST);
+
+        let unknown_preset = crate::rs_model::RsModel::parse_presets("ibd,bogus").unwrap_err();
+        assert_eq!(
+            ApiError::from(unknown_preset).status(),
+            StatusCode::BAD_REQUEST
+        );
+
+        let missing = anyhow::Error::new(NotFoundError("No watchlist named tech".to_string()))
+            .context("Failed to load watchlist");
+        assert_eq!(ApiError::from(missing).status(), StatusCode::NOT_FOUND);
+
+        assert_eq!(
+            ApiError::from(sqlx::Error::RowNotFound).status(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::from(anyhow::anyhow!("boom")).status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[tokio::test]
+    async fn rewrites_extractor_rejections_as_json() {
+        use axum::extract::FromRequest;
+
+        let request = Request::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from("{\"tickers\":"))
+            .unwrap();
+        let rejection = Json::<Value>::from_request(request, &())
+            .await
+            .unwrap_err()
+            .into_response();
+        let status = rejection.status();
+        assert!(status.is_client_error());
+
+        let response = as_json_error(rejection).await;
+        assert_eq!(response.status(), status);
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["code"], "bad_request");
+        assert!(body["message"].as_str().unwrap().contains("JSON"));
+        assert_eq!(body["details"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn renders_json_body() {
+        let response = ApiError::bad_request("Tag name is required")
+            .with_details(serde_json::json!({ "field": "name" }))
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "code": "bad_request",
+                "message": "Tag name is required",
+                "details": { "field": "name" },
+            })
+        );
+    }
+}
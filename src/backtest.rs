@@ -0,0 +1,534 @@
+use askama::Template;
+use axum::Json;
+use axum::extract::{Extension, Query};
+use axum::response::Html;
+use chrono::{Datelike, NaiveDate, TimeDelta};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::api_error::{ApiError, ValidationError};
+use crate::config::APP_CONFIG;
+use crate::html_error::HtmlError;
+use crate::rrg_util::{Normalization, rrg_series};
+use crate::rs_model::{RS_MODEL, RsModel};
+use crate::store::Store;
+use crate::yf::Candle;
+
+/// JdK standard smoothing used when ranking by RRG position.
+const RRG_PERIOD_WEEKS: usize = 10;
+/// A member whose last candle is older than this on a rebalance date is skipped.
+const MAX_STALE_DAYS: i64 = 7;
+const START_EQUITY: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Rebalance {
+    Weekly,
+    Monthly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RankBy {
+    /// Median RS of the tag's members under the configured RS model.
+    Rs,
+    /// Mean distance of the members from the RRG centre, RS-Ratio plus RS-Momentum.
+    Rrg,
+}
+
+/// A rotation rule: every `rebalance` period hold the `top` strongest tags,
+/// equal weight per tag and equal weight across each tag's members.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BacktestParams {
+    pub top: usize,
+    pub rebalance: Rebalance,
+    pub rank_by: RankBy,
+    /// Tags with fewer members trading on the rebalance date are not ranked.
+    pub min_members: usize,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+impl Default for BacktestParams {
+    fn default() -> Self {
+        Self {
+            top: 3,
+            rebalance: Rebalance::Weekly,
+            rank_by: RankBy::Rs,
+            min_members: 3,
+            start: None,
+            end: None,
+        }
+    }
+}
+
+/// Everything a backtest replays: stored daily candles and the current tag
+/// membership (so results carry survivorship bias towards today's tags).
+pub struct Universe {
+    pub benchmark_ticker: String,
+    pub benchmark: Vec<Candle>,
+    pub candles: HashMap<String, Vec<Candle>>,
+    pub tags: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EquityPoint {
+    pub date: NaiveDate,
+    pub equity: f64,
+    pub benchmark: f64,
+    /// Percent below the running peak, ≤ 0.
+    pub drawdown: f64,
+    pub benchmark_drawdown: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TagPick {
+    pub tag: String,
+    pub score: f64,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RebalanceRecord {
+    pub date: NaiveDate,
+    pub picks: Vec<TagPick>,
+    /// One-sided turnover as a fraction of equity (1.0 = everything traded).
+    pub turnover: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestStats {
+    pub total_return: f64,
+    pub cagr: f64,
+    pub max_drawdown: f64,
+    pub benchmark_total_return: f64,
+    pub benchmark_cagr: f64,
+    pub benchmark_max_drawdown: f64,
+    pub excess_return: f64,
+    pub rebalances: usize,
+    /// Average turnover per rebalance, excluding the initial purchase.
+    pub avg_turnover: f64,
+    pub annual_turnover: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub params: BacktestParams,
+    pub rs_model: String,
+    pub benchmark: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub stats: BacktestStats,
+    pub equity: Vec<EquityPoint>,
+    pub rebalances: Vec<RebalanceRecord>,
+}
+
+/// Loads stored candles for every tagged stock and the benchmark. Nothing is
+/// fetched, so refresh candles (e.g. by running stock_themes) beforehand.
+pub async fn load_universe(store: &Store) -> anyhow::Result<Universe> {
+    let benchmark = store.get_candles(&APP_CONFIG.base_ticker).await?;
+    if benchmark.is_empty() {
+        anyhow::bail!("No stored candles for benchmark {}", APP_CONFIG.base_ticker);
+    }
+
+    let mut tags = BTreeMap::<String, Vec<String>>::new();
+    let mut candles = HashMap::new();
+    for stock in store.list_stock_tags().await? {
+        let stock_candles = store.get_candles(&stock.ticker).await?;
+        if stock_candles.is_empty() {
+            warn!("No stored candles for {}, leaving it out", stock.ticker);
+            continue;
+        }
+        for tag in stock.tags {
+            tags.entry(tag.name).or_default().push(stock.ticker.clone());
+        }
+        candles.insert(stock.ticker, stock_candles);
+    }
+    info!(
+        "Loaded {} tags over {} stocks for backtesting",
+        tags.len(),
+        candles.len()
+    );
+
+    Ok(Universe {
+        benchmark_ticker: APP_CONFIG.base_ticker.to_uppercase(),
+        benchmark,
+        candles,
+        tags,
+    })
+}
+
+/// Loads the universe and replays it on a blocking thread; ranking by RRG
+/// computes a series per member at every rebalance.
+pub async fn run(store: &Store, params: BacktestParams) -> anyhow::Result<BacktestReport> {
+    let universe = load_universe(store).await?;
+    tokio::task::spawn_blocking(move || run_backtest(&universe, &RS_MODEL, params)).await?
+}
+
+/// Replays `params` day by day over the benchmark's trading days. Tags are
+/// ranked on the close of the first trading day of each period and the
+/// portfolio is rebalanced at that same close.
+pub fn run_backtest(
+    universe: &Universe,
+    model: &RsModel,
+    params: BacktestParams,
+) -> anyhow::Result<BacktestReport> {
+    if params.top == 0 {
+        return Err(ValidationError("top must be at least 1".to_string()).into());
+    }
+
+    let warmup = model
+        .periods
+        .iter()
+        .map(|p| p.months as usize * 21)
+        .max()
+        .unwrap_or(0);
+    let days = universe
+        .benchmark
+        .iter()
+        .skip(warmup)
+        .map(|c| c.timestamp.date_naive())
+        .filter(|d| params.start.is_none_or(|start| *d >= start))
+        .filter(|d| params.end.is_none_or(|end| *d <= end))
+        .collect_vec();
+    let (Some(&first_day), Some(&last_day)) = (days.first(), days.last()) else {
+        anyhow::bail!(
+            "No trading days to replay, the benchmark has {} candles and RS needs {warmup} for warm-up",
+            universe.benchmark.len()
+        );
+    };
+
+    let bench_start = close_on(&universe.benchmark, first_day).unwrap_or(1.0);
+    let mut holdings = HashMap::<String, f64>::new();
+    let mut cash = START_EQUITY;
+    let mut peak = START_EQUITY;
+    let mut bench_peak = START_EQUITY;
+    let mut equity_curve = Vec::with_capacity(days.len());
+    let mut rebalances = Vec::new();
+
+    for (idx, &day) in days.iter().enumerate() {
+        if idx > 0 {
+            let prev = days[idx - 1];
+            for (ticker, value) in holdings.iter_mut() {
+                let candles = &universe.candles[ticker];
+                if let (Some(now), Some(before)) = (close_on(candles, day), close_on(candles, prev))
+                    && before > 0.0
+                {
+                    *value *= now / before;
+                }
+            }
+        }
+        let equity = cash + holdings.values().sum::<f64>();
+
+        if idx == 0 || is_new_period(params.rebalance, days[idx - 1], day) {
+            let picks = pick_tags(universe, model, &params, day);
+            let targets = target_weights(&picks);
+            let turnover = targets
+                .keys()
+                .chain(holdings.keys())
+                .unique()
+                .map(|ticker| {
+                    let current = holdings.get(ticker).copied().unwrap_or(0.0) / equity;
+                    (targets.get(ticker).copied().unwrap_or(0.0) - current).abs()
+                })
+                .sum::<f64>()
+                + (1.0 - targets.values().sum::<f64>() - cash / equity).abs();
+
+            holdings = targets
+                .into_iter()
+                .map(|(ticker, weight)| (ticker, weight * equity))
+                .collect();
+            cash = equity - holdings.values().sum::<f64>();
+            rebalances.push(RebalanceRecord {
+                date: day,
+                picks,
+                turnover: round(turnover / 2.0, 4),
+            });
+        }
+
+        let bench =
+            START_EQUITY * close_on(&universe.benchmark, day).unwrap_or(bench_start) / bench_start;
+        peak = peak.max(equity);
+        bench_peak = bench_peak.max(bench);
+        equity_curve.push(EquityPoint {
+            date: day,
+            equity: round(equity, 4),
+            benchmark: round(bench, 4),
+            drawdown: round((equity / peak - 1.0) * 100.0, 2),
+            benchmark_drawdown: round((bench / bench_peak - 1.0) * 100.0, 2),
+        });
+    }
+
+    let stats = summarize(&equity_curve, &rebalances, first_day, last_day);
+    Ok(BacktestReport {
+        params,
+        rs_model: model.to_string(),
+        benchmark: universe.benchmark_ticker.clone(),
+        start: first_day,
+        end: last_day,
+        stats,
+        equity: equity_curve,
+        rebalances,
+    })
+}
+
+fn pick_tags(
+    universe: &Universe,
+    model: &RsModel,
+    params: &BacktestParams,
+    day: NaiveDate,
+) -> Vec<TagPick> {
+    let bench = upto(&universe.benchmark, day);
+    let mut scored = universe
+        .tags
+        .iter()
+        .filter_map(|(tag, tickers)| {
+            let members = tickers
+                .iter()
+                .filter_map(|ticker| {
+                    let candles = upto(&universe.candles[ticker], day);
+                    let last = candles.last()?.timestamp.date_naive();
+                    if day - last > TimeDelta::days(MAX_STALE_DAYS) {
+                        return None;
+                    }
+                    let score = match params.rank_by {
+                        RankBy::Rs => model.rs_candles(candles, bench),
+                        RankBy::Rrg => {
+                            let series = rrg_series(
+                                candles,
+                                bench,
+                                "weekly",
+                                RRG_PERIOD_WEEKS,
+                                Normalization::Sma,
+                            )?;
+                            series.rs_ratio.last()? + series.rs_momentum.last()? - 200.0
+                        }
+                    };
+                    Some((ticker.clone(), score))
+                })
+                .collect_vec();
+            if members.len() < params.min_members.max(1) {
+                return None;
+            }
+
+            let scores = members.iter().map(|(_, score)| *score).collect_vec();
+            let score = match params.rank_by {
+                RankBy::Rs => median(scores),
+                RankBy::Rrg => scores.iter().sum::<f64>() / scores.len() as f64,
+            };
+            Some(TagPick {
+                tag: tag.clone(),
+                score: round(score, 3),
+                members: members.into_iter().map(|(ticker, _)| ticker).collect(),
+            })
+        })
+        .collect_vec();
+    scored.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tag.cmp(&b.tag)));
+    scored.truncate(params.top);
+    scored
+}
+
+/// Equal weight per picked tag, split equally across its members. A stock in
+/// several picked tags gets the sum of its shares.
+fn target_weights(picks: &[TagPick]) -> HashMap<String, f64> {
+    let mut weights = HashMap::new();
+    for pick in picks {
+        let weight = 1.0 / picks.len() as f64 / pick.members.len() as f64;
+        for ticker in &pick.members {
+            *weights.entry(ticker.clone()).or_insert(0.0) += weight;
+        }
+    }
+    weights
+}
+
+fn summarize(
+    curve: &[EquityPoint],
+    rebalances: &[RebalanceRecord],
+    first_day: NaiveDate,
+    last_day: NaiveDate,
+) -> BacktestStats {
+    let years = (last_day - first_day).num_days() as f64 / 365.25;
+    let total = |end: f64| (end / START_EQUITY - 1.0) * 100.0;
+    let cagr = |end: f64| {
+        if years > 0.0 {
+            ((end / START_EQUITY).powf(1.0 / years) - 1.0) * 100.0
+        } else {
+            0.0
+        }
+    };
+    let last = curve.last();
+    let equity = last.map_or(START_EQUITY, |p| p.equity);
+    let bench = last.map_or(START_EQUITY, |p| p.benchmark);
+    let traded = rebalances.iter().skip(1).map(|r| r.turnover).collect_vec();
+    let traded_total = traded.iter().sum::<f64>();
+
+    BacktestStats {
+        total_return: round(total(equity), 2),
+        cagr: round(cagr(equity), 2),
+        max_drawdown: curve.iter().map(|p| p.drawdown).fold(0.0, f64::min),
+        benchmark_total_return: round(total(bench), 2),
+        benchmark_cagr: round(cagr(bench), 2),
+        benchmark_max_drawdown: curve
+            .iter()
+            .map(|p| p.benchmark_drawdown)
+            .fold(0.0, f64::min),
+        excess_return: round(total(equity) - total(bench), 2),
+        rebalances: rebalances.len(),
+        avg_turnover: if traded.is_empty() {
+            0.0
+        } else {
+            round(traded_total / traded.len() as f64, 4)
+        },
+        annual_turnover: if years > 0.0 {
+            round(traded_total / years, 2)
+        } else {
+            0.0
+        },
+    }
+}
+
+fn is_new_period(rebalance: Rebalance, prev: NaiveDate, day: NaiveDate) -> bool {
+    match rebalance {
+        Rebalance::Weekly => prev.iso_week() != day.iso_week(),
+        Rebalance::Monthly => (prev.year(), prev.month()) != (day.year(), day.month()),
+    }
+}
+
+/// Candles up to and including `day`.
+fn upto(candles: &[Candle], day: NaiveDate) -> &[Candle] {
+    let end = candles.partition_point(|c| c.timestamp.date_naive() <= day);
+    &candles[..end]
+}
+
+/// Last close on or before `day`.
+fn close_on(candles: &[Candle], day: NaiveDate) -> Option<f64> {
+    upto(candles, day).last().map(|c| c.close)
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+fn round(value: f64, places: i32) -> f64 {
+    let factor = 10f64.powi(places);
+    (value * factor).round() / factor
+}
+
+#[derive(Template)]
+#[template(path = "backtest.html")]
+struct BacktestTemplate {
+    benchmark: String,
+    report_json: String,
+}
+
+/// GET /backtest.html?top=3&rebalance=weekly&rank_by=rs
+pub async fn backtest_home(
+    Extension(store): Extension<Arc<Store>>,
+    Query(params): Query<BacktestParams>,
+) -> Result<Html<String>, HtmlError> {
+    let report = run(&store, params).await?;
+    let html = BacktestTemplate {
+        benchmark: APP_CONFIG.base_ticker.to_uppercase(),
+        report_json: serde_json::to_string(&report)?,
+    }
+    .render()?;
+    Ok(Html(html))
+}
+
+/// GET /api/backtest?top=3&rebalance=weekly&rank_by=rs
+pub async fn backtest_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Query(params): Query<BacktestParams>,
+) -> Result<Json<BacktestReport>, ApiError> {
+    Ok(Json(run(&store, params).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rs_model::RsPeriod;
+    use chrono::{Local, TimeZone, Utc};
+
+    fn candles(closes: impl IntoIterator<Item = f64>) -> Vec<Candle> {
+        closes
+            .into_iter()
+            .enumerate()
+            .map(|(i, close)| Candle {
+                // 2025-01-06 is a Monday.
+                timestamp: Utc.with_ymd_and_hms(2025, 1, 6, 21, 0, 0).unwrap()
+                    + TimeDelta::days(i as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0,
+                adj_close: None,
+                last_updated: Local::now(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rotates_into_the_strongest_tag() {
+        let days = 60;
+        let rising = |step: f64| candles((0..days).map(move |i| 100.0 + step * i as f64));
+        let universe = Universe {
+            benchmark_ticker: "SPY".to_string(),
+            benchmark: candles(vec![100.0; days]),
+            candles: HashMap::from([
+                ("UP1".to_string(), rising(1.0)),
+                ("UP2".to_string(), rising(2.0)),
+                ("DOWN".to_string(), rising(-0.5)),
+            ]),
+            tags: BTreeMap::from([
+                (
+                    "Leaders".to_string(),
+                    vec!["UP1".to_string(), "UP2".to_string()],
+                ),
+                ("Laggards".to_string(), vec!["DOWN".to_string()]),
+            ]),
+        };
+        let model = RsModel {
+            name: "one-month".to_string(),
+            periods: vec![RsPeriod {
+                months: 1,
+                weight: 1.0,
+            }],
+        };
+        let params = BacktestParams {
+            top: 1,
+            min_members: 1,
+            ..BacktestParams::default()
+        };
+
+        let report = run_backtest(&universe, &model, params).unwrap();
+
+        assert_eq!(report.equity.len(), days - 21);
+        assert!(
+            report
+                .rebalances
+                .iter()
+                .all(|r| r.picks[0].tag == "Leaders")
+        );
+        assert_eq!(report.rebalances[0].turnover, 1.0);
+        // Holdings drift between rebalances, so later turnover is small but not zero.
+        assert!(report.stats.avg_turnover < 0.1);
+        assert!(report.stats.total_return > 0.0);
+        assert_eq!(report.stats.benchmark_total_return, 0.0);
+        assert_eq!(report.stats.max_drawdown, 0.0);
+        assert!(report.stats.excess_return > 0.0);
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::Context;
+use axum::response::Redirect;
+use axum::{Extension, Router, middleware, routing};
+use chrono::NaiveDate;
+use clap::Parser;
+use std::path::PathBuf;
+use tracing::info;
+
+use stock_themes::backtest::{self, BacktestParams, RankBy, Rebalance};
+use stock_themes::store::Store;
+use stock_themes::{backtest_router, init_logger, no_cache, serve, static_asset};
+
+#[derive(Parser, Debug)]
+#[command(name = "backtest")]
+#[command(
+    about = "Replays stored daily candles to backtest a tag rotation rule, \
+             e.g. hold the top 3 tags by RS rating, rebalanced weekly"
+)]
+struct BacktestArgs {
+    /// Number of tags to hold
+    #[arg(short = 't', long, default_value_t = 3)]
+    pub top: usize,
+
+    #[arg(short = 'r', long, value_enum, default_value_t = Rebalance::Weekly)]
+    pub rebalance: Rebalance,
+
+    #[arg(long, value_enum, default_value_t = RankBy::Rs)]
+    pub rank_by: RankBy,
+
+    /// Minimum members with candles for a tag to be ranked
+    #[arg(short = 'm', long, default_value_t = 3)]
+    pub min_members: usize,
+
+    /// First day to replay (defaults to the first day after the RS warm-up)
+    #[arg(long)]
+    pub start: Option<NaiveDate>,
+
+    /// Last day to replay (defaults to the latest stored candle)
+    #[arg(long)]
+    pub end: Option<NaiveDate>,
+
+    /// Output JSON report
+    #[arg(short = 'o', long, default_value = "backtest.json")]
+    pub output_file: PathBuf,
+
+    /// Serve the report page at /backtest.html after writing the JSON
+    #[arg(long, default_value_t = false)]
+    pub serve: bool,
+}
+
+#[tokio::main(flavor = "multi_thread", worker_threads = 2)]
+async fn main() -> anyhow::Result<()> {
+    init_logger();
+
+    let args = BacktestArgs::parse();
+    let params = BacktestParams {
+        top: args.top,
+        rebalance: args.rebalance,
+        rank_by: args.rank_by,
+        min_members: args.min_members,
+        start: args.start,
+        end: args.end,
+    };
+
+    let store = Store::load_store().await?;
+    let report = backtest::run(&store, params.clone()).await?;
+    tokio::fs::write(&args.output_file, serde_json::to_string_pretty(&report)?)
+        .await
+        .with_context(|| format!("Couldn't write {:?}", args.output_file))?;
+    let s = &report.stats;
+    info!(
+        "{} → {}: {:+.2}% vs {} {:+.2}%, max drawdown {:.2}%, {} rebalances, written to {:?}",
+        report.start,
+        report.end,
+        s.total_return,
+        report.benchmark,
+        s.benchmark_total_return,
+        s.max_drawdown,
+        s.rebalances,
+        args.output_file
+    );
+
+    if !args.serve {
+        return Ok(());
+    }
+
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    if let serde_json::Value::Object(fields) = serde_json::to_value(&params)? {
+        for (key, value) in fields {
+            match value {
+                serde_json::Value::Null => {}
+                serde_json::Value::String(value) => {
+                    query.append_pair(&key, &value);
+                }
+                value => {
+                    query.append_pair(&key, &value.to_string());
+                }
+            }
+        }
+    }
+    let home = format!("/backtest.html?{}", query.finish());
+    let app = Router::new()
+        .route("/", routing::get(async move || Redirect::to(&home)))
+        .route("/assets/{*path}", routing::get(static_asset))
+        .merge(backtest_router())
+        .layer(Extension(store))
+        .layer(middleware::from_fn(no_cache));
+    serve(app).await
+}
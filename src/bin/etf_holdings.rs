@@ -0,0 +1,39 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tracing::info;
+
+use stock_themes::etf_map::holdings;
+use stock_themes::init_logger;
+use stock_themes::store::Store;
+
+#[derive(Parser, Debug)]
+#[command(name = "etf_holdings")]
+#[command(about = "Loads ETF constituent CSV exports (one file per ETF) into the store")]
+struct EtfHoldingsArgs {
+    /// Directory with the issuer CSV files, named after the ETF (e.g. SMH.csv)
+    #[arg(default_value = "etf_holdings")]
+    pub dir: PathBuf,
+}
+
+#[tokio::main(flavor = "multi_thread", worker_threads = 2)]
+async fn main() -> anyhow::Result<()> {
+    init_logger();
+
+    let args = EtfHoldingsArgs::parse();
+    let store = Store::load_store().await?;
+    let ingested = holdings::ingest_holdings_dir(&store, &args.dir).await?;
+
+    let total = ingested.iter().map(|file| file.holdings).sum::<usize>();
+    info!(
+        "Loaded {} ETFs with {total} holdings from {:?}",
+        ingested.len(),
+        args.dir
+    );
+    for file in ingested {
+        println!(
+            "{:<8} {:>5} holdings  as of {}  ({})",
+            file.etf, file.holdings, file.as_of, file.file
+        );
+    }
+    Ok(())
+}
@@ -1,14 +1,15 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use anyhow::Context;
 use axum::{Extension, Router, middleware, routing};
 use clap::Parser;
-use stock_themes::config::APP_CONFIG;
-use stock_themes::rrg_util::RrgMode;
+use stock_themes::rrg_util::RrgTickers;
 use stock_themes::store::Store;
-use stock_themes::{etf_map, init_logger, no_cache, rrg_util, static_asset, tags, util};
-use tokio::net::TcpListener;
+use stock_themes::yf::YFinance;
+use stock_themes::{
+    backtest_router, etf_map, init_logger, no_cache, rrg_router, rrg_util, serve, static_asset,
+    tags, util, watchlist,
+};
 use tracing::info;
 
 #[derive(Parser, Debug)]
@@ -21,13 +22,17 @@ pub struct RrgArgs {
     /// When omitted, the sector/industry ETF rotation is shown.
     pub files: Vec<PathBuf>,
 
-    /// Number of header lines to skip in each CSV
-    #[arg(short = 'n', long, default_value_t = 4)]
-    pub skip_lines: usize,
+    /// Number of header lines to skip in each file; detected when omitted
+    #[arg(short = 'n', long)]
+    pub skip_lines: Option<usize>,
 
     /// Comma separated list of stocks to skip
     #[arg(short = 's', long, default_value = "")]
     pub skip_stocks: String,
+
+    /// Plot the tickers of this stored watchlist instead of CSV files
+    #[arg(short = 'w', long, conflicts_with = "files")]
+    pub watchlist: Option<String>,
 }
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 2)]
@@ -35,9 +40,16 @@ async fn main() -> anyhow::Result<()> {
     init_logger();
 
     let args = RrgArgs::parse();
+    let store = Store::load_store().await?;
 
-    let mode = if args.files.is_empty() {
-        RrgMode::Sectors(etf_map::tv_mapping())
+    let tickers = if let Some(name) = &args.watchlist {
+        let tickers = watchlist::watchlist_tickers(&store, name, &args.skip_stocks).await?;
+        if tickers.is_empty() {
+            anyhow::bail!("Watchlist {name} has no tickers to plot");
+        }
+        Some(tickers)
+    } else if args.files.is_empty() {
+        None
     } else {
         let tickers = util::read_stocks(&args.files, args.skip_lines, &args.skip_stocks).await?;
         if tickers.is_empty() {
@@ -46,30 +58,26 @@ async fn main() -> anyhow::Result<()> {
                 args.files
             );
         }
-        RrgMode::Tickers(tickers)
+        Some(tickers)
     };
-    match &mode {
-        RrgMode::Sectors(_) => info!("No ticker files — serving sector/industry rotation"),
-        RrgMode::Tickers(t) => info!("Serving ticker rotation for {} tickers", t.len()),
+    match &tickers {
+        None => info!("No ticker files — serving sector/industry rotation"),
+        Some(t) => info!("Serving ticker rotation for {} tickers", t.len()),
     }
-    let mode = Arc::new(mode);
+    let rrg = Arc::new(RrgTickers::new(tickers));
+    let yf = Arc::new(YFinance::new());
 
-    let addr = format!("127.0.0.1:{}", APP_CONFIG.http_port);
-    let listener = TcpListener::bind(&addr)
-        .await
-        .with_context(|| format!("Failed to bind at {addr}"))?;
-
-    info!("Running http server at: {addr}");
-    let store = Store::load_store().await?;
     let app = Router::new()
         .route("/", routing::get(rrg_util::rrg_home))
-        .route("/rrg.html", routing::get(rrg_util::rrg_home))
         .route("/assets/{*path}", routing::get(static_asset))
-        .route("/api/rrg/{ticker}", routing::get(rrg_util::rrg_handler))
-        .merge(tags::router(store))
-        .layer(Extension(mode))
+        .merge(rrg_router())
+        .merge(backtest_router())
+        .merge(tags::router(store.clone(), yf.clone()))
+        .merge(etf_map::router(store.clone(), yf.clone()))
+        .merge(watchlist::routes::router(store.clone()))
+        .layer(Extension(rrg))
+        .layer(Extension(yf))
+        .layer(Extension(store))
         .layer(middleware::from_fn(no_cache));
-    axum::serve(listener, app).await?;
-
-    Ok(())
+    serve(app).await
 }
@@ -0,0 +1,104 @@
+use clap::Parser;
+use itertools::Itertools;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::info;
+
+use stock_themes::screener::{self, Expr, FIELDS};
+use stock_themes::store::Store;
+use stock_themes::summary::SummaryState;
+use stock_themes::watchlist::store::WatchlistItem;
+use stock_themes::yf::YFinance;
+use stock_themes::{init_logger, start_http_server, util};
+
+#[derive(Parser, Debug)]
+#[command(name = "screener")]
+#[command(about = "Screens the stored universe with a filter expression, e.g. \
+             'rs_rating > 80 and adr_pct > 4 and tag in (\"Semiconductors\")'")]
+struct ScreenerArgs {
+    /// Filter expression over stored candles, metrics, RS, fundamentals and tags
+    pub expression: String,
+
+    /// Output CSV file (readable by stock_themes and rrg)
+    #[arg(short = 'o', long, default_value = "screen.csv")]
+    pub output_file: PathBuf,
+
+    /// Save the matches to this stored watchlist instead of the output CSV
+    #[arg(short = 'w', long)]
+    pub watchlist: Option<String>,
+
+    /// Serve the matches in the stock themes web UI instead of only writing the CSV
+    #[arg(long, default_value_t = false)]
+    pub serve: bool,
+
+    /// List the fields usable in expressions and exit
+    #[arg(long, default_value_t = false)]
+    pub list_fields: bool,
+}
+
+#[tokio::main(flavor = "multi_thread", worker_threads = 2)]
+async fn main() -> anyhow::Result<()> {
+    init_logger();
+
+    let args = ScreenerArgs::parse();
+    if args.list_fields {
+        for (name, kind) in FIELDS {
+            println!("{name:<18} {kind:?}");
+        }
+        return Ok(());
+    }
+
+    let expr = Expr::parse(&args.expression)?;
+    info!("Screening with: {expr:?}");
+
+    let store = Store::load_store().await?;
+    let rows = screener::screen(&store, &expr).await?;
+    info!("{} stocks matched", rows.len());
+    for row in &rows {
+        info!(
+            "{:<8} RS={:<3} {} / {}",
+            row.stock.ticker,
+            row.rs_rating.map(|r| r.to_string()).unwrap_or_default(),
+            row.stock.sector.name,
+            row.stock.industry.name,
+        );
+    }
+
+    let tickers = rows
+        .iter()
+        .map(|row| row.stock.ticker.clone())
+        .collect_vec();
+    match &args.watchlist {
+        Some(name) => {
+            let items = rows
+                .iter()
+                .map(|row| WatchlistItem {
+                    ticker: row.stock.ticker.clone(),
+                    exchange: row.stock.exchange.clone(),
+                    section: String::new(),
+                })
+                .collect_vec();
+            store.save_watchlist(name, &items).await?;
+            info!("Saved {} matches to watchlist {name}", items.len());
+        }
+        None => {
+            util::save_stocks_csv(
+                &args.output_file,
+                "Screener Results",
+                &args.expression,
+                &tickers,
+            )
+            .await?
+        }
+    }
+
+    if !args.serve {
+        return Ok(());
+    }
+
+    let stocks = rows.into_iter().map(|row| row.stock).collect_vec();
+    let yf = Arc::new(YFinance::new());
+    let summary = Arc::new(SummaryState::build(&store, yf.clone(), stocks).await?);
+
+    start_http_server(store, yf, summary).await
+}
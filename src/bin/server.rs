@@ -0,0 +1,34 @@
+use clap::Parser;
+use std::sync::Arc;
+
+use stock_themes::rrg_util::RrgTickers;
+use stock_themes::store::Store;
+use stock_themes::summary::SummaryState;
+use stock_themes::yf::YFinance;
+use stock_themes::{app_router, init_logger, serve, trades};
+
+#[derive(Parser, Debug)]
+#[command(name = "server")]
+#[command(
+    about = "Serves stock themes, RRG, tags, stock tags and trade analysis together. Starts empty; load inputs through the APIs:
+  POST /api/watchlist             upload a watchlist file
+  POST /api/watchlist/load/{name} serve a stored watchlist
+  PUT  /api/rrg/tickers           plot tickers or a watchlist on the RRG
+  POST /api/trades                upload a ThinkorSwim statement for /trades.html"
+)]
+struct ServerArgs {}
+
+#[tokio::main(flavor = "multi_thread", worker_threads = 2)]
+async fn main() -> anyhow::Result<()> {
+    init_logger();
+    ServerArgs::parse();
+
+    let store = Store::load_store().await?;
+    let yf = Arc::new(YFinance::new());
+    let summary = Arc::new(SummaryState::build(&store, yf.clone(), Vec::new()).await?);
+    let trades = trades::routes::AppState::new(store.clone(), yf.clone(), Vec::new());
+    let rrg = Arc::new(RrgTickers::default());
+
+    let app = app_router(store, yf, summary, rrg).merge(trades::routes::router(trades));
+    serve(app).await
+}
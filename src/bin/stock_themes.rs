@@ -1,16 +1,13 @@
-use chrono::Utc;
 use clap::Parser;
+use tracing::info;
 
-use stock_themes::Group;
-use tracing::{info, warn};
-
-use std::collections::HashMap;
 use std::path::PathBuf;
-use std::time::Instant;
-use stock_themes::{Stock, init_logger, metrics, rs, start_http_server, store::Store, util};
+use std::sync::Arc;
+use stock_themes::{init_logger, start_http_server, store::Store, util, watchlist};
 
-use stock_themes::summary::Summary;
-use stock_themes::tv::screener_api::ScreenerApi;
+use stock_themes::export::{self, ExportFormat};
+use stock_themes::summary::SummaryState;
+use stock_themes::watchlist::{WatchlistFiles, runs};
 use stock_themes::yf::YFinance;
 
 #[derive(Parser, Debug)]
@@ -18,111 +15,70 @@ use stock_themes::yf::YFinance;
 #[command(about = "Process csv files with stocks to find the common themes among them")]
 pub struct StockThemesArgs {
     /// Input files to process
-    #[arg(required = true)]
+    #[arg(required_unless_present = "watchlist")]
     pub files: Vec<PathBuf>,
 
-    /// Number of items to skip
-    #[arg(short = 'n', long, default_value_t = 4)]
-    pub skip_lines: usize,
+    /// Use this stored watchlist instead of input files
+    #[arg(short = 'w', long, conflicts_with = "files")]
+    pub watchlist: Option<String>,
+
+    /// Number of header lines to skip in each file; detected when omitted
+    #[arg(short = 'n', long)]
+    pub skip_lines: Option<usize>,
 
     /// Comma seperated list of Stocks to skip
     #[arg(short = 's', long, default_value = "")]
     pub skip_stocks: String,
+
+    /// Write the summary in this format to --output and exit instead of
+    /// starting the server
+    #[arg(long, value_enum, value_name = "FORMAT", requires = "output")]
+    pub export: Option<ExportFormat>,
+
+    /// File the --export is written to
+    #[arg(short = 'o', long, value_name = "PATH", requires = "export")]
+    pub output: Option<PathBuf>,
 }
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 2)]
 async fn main() -> anyhow::Result<()> {
-    init_logger();
-
     let args = StockThemesArgs::parse();
+    init_logger();
     info!("args: {args:#?}");
 
-    let yf = YFinance::new();
     let store = Store::load_store().await?;
 
-    let tickers = util::read_stocks(&args.files, args.skip_lines, &args.skip_stocks).await?;
+    let tickers = match &args.watchlist {
+        Some(name) => watchlist::watchlist_tickers(&store, name, &args.skip_stocks).await?,
+        None => util::read_stocks(&args.files, args.skip_lines, &args.skip_stocks).await?,
+    };
     info!("Total unique stocks: {}", tickers.len());
 
-    let stocks = fetch_stock_info(&store, tickers).await?;
-    let rs_maps = rs::build_rs_maps(&store, &yf, &stocks).await?;
-
-    let stock_metrics = metrics::build_stock_metrics(&store, &yf, &stocks).await?;
-    info!("Computed metrics for {} stocks", stock_metrics.len());
-    let summary = Summary::summarize(stocks);
-    let html = summary.render(
-        rs_maps.sectors,
-        rs_maps.industries,
-        rs_maps.stocks,
-        stock_metrics,
-    );
-
-    start_http_server(store, html).await
-}
-
-async fn fetch_stock_info(store: &Store, tickers: Vec<String>) -> anyhow::Result<Vec<Stock>> {
-    let start = Instant::now();
-    let mut cached_stocks = HashMap::new();
-    let mut missing_tickers = Vec::new();
-    for ticker in &tickers {
-        match store.get_stock(ticker).await? {
-            Some(stock) => {
-                cached_stocks.insert(ticker.clone(), stock);
-            }
-            None => missing_tickers.push(ticker.clone()),
-        }
+    let stocks = watchlist::fetch_stock_info(&store, tickers).await?.stocks;
+    let yf = Arc::new(YFinance::new());
+    let summary = Arc::new(SummaryState::build(&store, yf.clone(), stocks).await?);
+    if let (Some(format), Some(output)) = (args.export, &args.output) {
+        // An export is a one-off view and leaves the run history alone.
+        let report = summary.report().await;
+        return export::export(&store, &report, format, output).await;
     }
 
-    if !missing_tickers.is_empty() {
-        info!(
-            "Fetching {} stocks info from TradingView API",
-            missing_tickers.len(),
-        );
-        let stock_info_fetcher = ScreenerApi::default();
-        let mut fetched_stocks = stock_info_fetcher.fetch_stocks(&missing_tickers).await?;
-        fetched_stocks
-            .retain(|_, stock| !(stock.sector.name.is_empty() || stock.industry.name.is_empty()));
-        if !fetched_stocks.is_empty() {
-            let fetched = fetched_stocks.values().cloned().collect::<Vec<_>>();
-            store.add_stocks(&fetched).await?;
-            cached_stocks.extend(fetched_stocks);
+    let files = WatchlistFiles {
+        files: args.files,
+        skip_lines: args.skip_lines,
+        skip_stocks: args.skip_stocks,
+    };
+    let source = match &args.watchlist {
+        Some(name) => format!("watchlist {name}"),
+        None => files.source(),
+    };
+    runs::record_run(&store, &summary.stocks().await, &source).await;
+    match args.watchlist {
+        Some(name) => {
+            watchlist::watch_watchlist(name, files.skip_stocks, store.clone(), summary.clone())
         }
+        None => watchlist::watch_files(files, store.clone(), summary.clone()),
     }
 
-    let missing_tickers = tickers
-        .into_iter()
-        .filter(|t| !cached_stocks.contains_key(t))
-        .collect::<Vec<_>>();
-    if !missing_tickers.is_empty() {
-        warn!(
-            "Failed to fetch stock info for {} tickers: [{}]",
-            missing_tickers.len(),
-            missing_tickers.join(","),
-        );
-        for ticker in missing_tickers {
-            cached_stocks.insert(
-                ticker.clone(),
-                Stock {
-                    ticker,
-                    exchange: "".into(),
-                    sector: unknown_group(),
-                    industry: unknown_group(),
-                    last_update: Utc::now().date_naive(),
-                },
-            );
-        }
-    }
-    info!(
-        "Finished processing {} tickers in {:.2?}",
-        cached_stocks.len(),
-        start.elapsed(),
-    );
-
-    Ok(cached_stocks.into_values().collect())
-}
-
-fn unknown_group() -> Group {
-    Group {
-        name: "Unknown".into(),
-        url: "".into(),
-    }
+    start_http_server(store, yf, summary).await
 }
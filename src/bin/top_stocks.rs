@@ -1,15 +1,17 @@
-use anyhow::Context;
 use clap::Parser;
+use itertools::Itertools;
 
 use tracing::info;
 
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 use stock_themes::store::Store;
 
+use stock_themes::watchlist::store::WatchlistItem;
+
 use stock_themes::tv::tv_manager::TvManager;
 
-use stock_themes::init_logger;
+use stock_themes::{init_logger, symbol, util};
 
 #[derive(Parser, Debug)]
 #[command(name = "top_stocks")]
@@ -34,6 +36,10 @@ struct TopStocksArgs {
     /// Output CSV File
     #[arg(short = 'o', long, default_value = "watchlist.csv")]
     pub output_file: PathBuf,
+
+    /// Save the stocks to this stored watchlist instead of the output CSV
+    #[arg(short = 'w', long)]
+    pub watchlist: Option<String>,
 }
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 2)]
@@ -43,7 +49,8 @@ async fn main() -> anyhow::Result<()> {
     let args = TopStocksArgs::parse();
     info!("Using args: {args:#?}");
 
-    let mut tv_manager = TvManager::new(Store::load_store().await?);
+    let store = Store::load_store().await?;
+    let mut tv_manager = TvManager::new(store.clone());
     let stocks = tv_manager
         .fetch_top_stocks(
             &args.tv_screen_url,
@@ -54,25 +61,27 @@ async fn main() -> anyhow::Result<()> {
         .await?;
     info!("Total {} unique stocks fetched", stocks.len());
 
-    save_csv(&args.output_file, &args.tv_screen_url, &stocks).await
-}
-
-async fn save_csv(file: &Path, source: &str, stocks: &[String]) -> anyhow::Result<()> {
-    use std::fmt::Write;
-
-    let mut content = String::new();
-    writeln!(content, "======= Top Performing Stocks ======")?;
-    writeln!(content, "Source: {source}")?;
-    writeln!(content, "Count: {}", stocks.len())?;
-    writeln!(content)?;
-    for stock in stocks {
-        writeln!(content, "{stock}")?;
+    if let Some(name) = &args.watchlist {
+        let items = stocks
+            .iter()
+            .map(|ticker| WatchlistItem {
+                ticker: symbol::canonical(ticker),
+                exchange: String::new(),
+                section: String::new(),
+            })
+            .collect_vec();
+        store.save_watchlist(name, &items).await?;
+        info!("Saved {} stocks to watchlist {name}", items.len());
+        return Ok(());
     }
-    tokio::fs::write(file, content)
-        .await
-        .with_context(|| format!("Error writing output to {file:?}"))?;
-    info!("Saved the output to {:?}\n", file.canonicalize()?);
-    Ok(())
+
+    util::save_stocks_csv(
+        &args.output_file,
+        "Top Performing Stocks",
+        &args.tv_screen_url,
+        &stocks,
+    )
+    .await
 }
 
 pub fn time_frames(input: &str) -> impl Iterator<Item = String> {
@@ -54,12 +54,11 @@ async fn main() -> anyhow::Result<()> {
     );
 
     // Build views
-    let benchmark = APP_CONFIG.base_ticker.to_uppercase();
     let views = build_views(&trades, &APP_CONFIG.trade_analysis);
 
     let store = Store::load_store().await?;
     let yf = Arc::new(YFinance::new());
 
     // Start web server
-    start_server(store, yf, views.trade_views, &benchmark).await
+    start_server(store, yf, views.trade_views).await
 }
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     sync::LazyLock,
 };
@@ -7,6 +8,8 @@ use anyhow::Context;
 use chrono::NaiveTime;
 use serde::{Deserialize, Serialize};
 
+use crate::rs_model::RsModel;
+
 const CONFIG_FILE: &str = "config.toml";
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -17,6 +20,10 @@ pub struct Config {
     pub chrome_args: Vec<String>,
     pub launch_chrome_if_needed: bool,
     pub base_ticker: String,
+    /// Benchmark per non-US market code (`TSX = "XIU.TO"`), replacing the
+    /// built-in ones. US listings use `base_ticker`.
+    #[serde(default)]
+    pub benchmarks: HashMap<String, String>,
     pub market_hours: (NaiveTime, NaiveTime),
     #[serde(default)]
     pub ignored_stocks: Vec<String>,
@@ -30,6 +37,9 @@ pub struct Config {
 
     #[serde(default)]
     pub tag_suggestion: Option<TagSuggestionConfig>,
+
+    #[serde(default)]
+    pub rs: RsConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -46,6 +56,22 @@ pub struct MetricsConfig {
     pub avg_volume_days: usize,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RsConfig {
+    /// Preset name (ibd, momentum, equal, tv) or a custom `{ name, periods }` table.
+    pub model: RsModelSpec,
+    /// Models shown next to the primary one in the stock metrics stream.
+    pub compare: Vec<RsModelSpec>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum RsModelSpec {
+    Preset(String),
+    Custom(RsModel),
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TagSuggestionProvider {
@@ -97,6 +123,15 @@ impl Default for TradeAnalysisConfig {
     }
 }
 
+impl Default for RsConfig {
+    fn default() -> Self {
+        Self {
+            model: RsModelSpec::Preset("ibd".to_string()),
+            compare: Vec::new(),
+        }
+    }
+}
+
 impl Default for MetricsConfig {
     fn default() -> Self {
         Self {
@@ -0,0 +1,186 @@
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+use crate::yf::Candle;
+
+/// Daily close-to-close returns keyed by trading date.
+pub type ReturnSeries = BTreeMap<NaiveDate, f64>;
+
+/// Builds the last `window` daily returns from `candles` (oldest → newest).
+pub fn daily_returns(candles: &[Candle], window: usize) -> ReturnSeries {
+    let start = candles.len().saturating_sub(window + 1);
+    candles[start..]
+        .windows(2)
+        .filter(|pair| pair[0].adj_close() > 0.0)
+        .map(|pair| {
+            let ret = pair[1].adj_close() / pair[0].adj_close() - 1.0;
+            (pair[1].timestamp.date_naive(), ret)
+        })
+        .collect()
+}
+
+/// Pearson correlation over the dates present in both series. Returns `None`
+/// when fewer than `min_overlap` dates line up or either side is flat.
+pub fn correlation(a: &ReturnSeries, b: &ReturnSeries, min_overlap: usize) -> Option<f64> {
+    let pairs = a
+        .iter()
+        .filter_map(|(date, x)| b.get(date).map(|y| (*x, *y)))
+        .collect::<Vec<_>>();
+    if pairs.len() < min_overlap.max(2) {
+        return None;
+    }
+
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in &pairs {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x * var_y).sqrt())
+}
+
+/// Symmetric pairwise correlation matrix. Pairs without enough overlap are
+/// treated as uncorrelated (0.0).
+pub fn correlation_matrix(series: &[ReturnSeries], min_overlap: usize) -> Vec<Vec<f64>> {
+    let n = series.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        matrix[i][i] = 1.0;
+        for j in i + 1..n {
+            let corr = correlation(&series[i], &series[j], min_overlap).unwrap_or(0.0);
+            matrix[i][j] = corr;
+            matrix[j][i] = corr;
+        }
+    }
+    matrix
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    /// Indices into the matrix the cluster was built from.
+    pub members: Vec<usize>,
+    /// Mean pairwise correlation between members.
+    pub avg_correlation: f64,
+}
+
+/// Agglomerative (average-linkage) clustering of a correlation matrix. Clusters
+/// keep merging while the average correlation between them is at least
+/// `threshold`; singletons are dropped from the result.
+pub fn cluster(matrix: &[Vec<f64>], threshold: f64) -> Vec<Cluster> {
+    let mut clusters = (0..matrix.len()).map(|i| vec![i]).collect::<Vec<_>>();
+    let mut linkage = matrix.to_vec();
+    // Merged-away clusters are left empty rather than removed, so merging
+    // doesn't shift every row of the linkage matrix.
+    let mut active = vec![true; matrix.len()];
+
+    loop {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for i in (0..linkage.len()).filter(|&i| active[i]) {
+            for j in (i + 1..linkage.len()).filter(|&j| active[j]) {
+                let sim = linkage[i][j];
+                if sim >= threshold && best.is_none_or(|(_, _, best_sim)| sim > best_sim) {
+                    best = Some((i, j, sim));
+                }
+            }
+        }
+        let Some((i, j, _)) = best else {
+            break;
+        };
+
+        // Lance–Williams update for average linkage, then retire `j`.
+        let (size_i, size_j) = (clusters[i].len() as f64, clusters[j].len() as f64);
+        for k in 0..linkage.len() {
+            if k != i && k != j && active[k] {
+                let sim = (size_i * linkage[i][k] + size_j * linkage[j][k]) / (size_i + size_j);
+                linkage[i][k] = sim;
+                linkage[k][i] = sim;
+            }
+        }
+        let absorbed = std::mem::take(&mut clusters[j]);
+        clusters[i].extend(absorbed);
+        active[j] = false;
+    }
+
+    let mut result = clusters
+        .into_iter()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            members.sort_unstable();
+            let avg_correlation = mean_pairwise(matrix, &members);
+            Cluster {
+                members,
+                avg_correlation,
+            }
+        })
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| {
+        b.members
+            .len()
+            .cmp(&a.members.len())
+            .then_with(|| b.avg_correlation.total_cmp(&a.avg_correlation))
+    });
+    result
+}
+
+fn mean_pairwise(matrix: &[Vec<f64>], members: &[usize]) -> f64 {
+    let mut sum = 0.0;
+    let mut count = 0;
+    for (idx, &i) in members.iter().enumerate() {
+        for &j in &members[idx + 1..] {
+            sum += matrix[i][j];
+            count += 1;
+        }
+    }
+    if count == 0 { 1.0 } else { sum / count as f64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(values: &[f64]) -> ReturnSeries {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (start + chrono::Days::new(i as u64), *v))
+            .collect()
+    }
+
+    #[test]
+    fn correlation_of_scaled_and_inverted_series() {
+        let a = series(&[0.01, -0.02, 0.03, 0.00, -0.01]);
+        let b = series(&[0.02, -0.04, 0.06, 0.00, -0.02]);
+        let c = series(&[-0.01, 0.02, -0.03, 0.00, 0.01]);
+
+        assert!((correlation(&a, &b, 3).unwrap() - 1.0).abs() < 1e-12);
+        assert!((correlation(&a, &c, 3).unwrap() + 1.0).abs() < 1e-12);
+        assert_eq!(correlation(&a, &b, 10), None);
+        assert_eq!(correlation(&a, &series(&[0.0; 5]), 3), None);
+    }
+
+    #[test]
+    fn average_linkage_groups_correlated_blocks() {
+        let matrix = vec![
+            vec![1.0, 0.9, 0.8, 0.1, 0.0],
+            vec![0.9, 1.0, 0.85, 0.2, 0.1],
+            vec![0.8, 0.85, 1.0, 0.0, 0.1],
+            vec![0.1, 0.2, 0.0, 1.0, 0.75],
+            vec![0.0, 0.1, 0.1, 0.75, 1.0],
+        ];
+
+        let clusters = cluster(&matrix, 0.7);
+
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].members, vec![0, 1, 2]);
+        assert!((clusters[0].avg_correlation - 0.85).abs() < 1e-12);
+        assert_eq!(clusters[1].members, vec![3, 4]);
+        assert!(cluster(&matrix, 0.95).is_empty());
+    }
+}
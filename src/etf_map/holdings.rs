@@ -0,0 +1,341 @@
+use anyhow::Context;
+use chrono::{DateTime, Local, NaiveDate};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::store::Store;
+use crate::symbol;
+use crate::util::split_csv_line;
+
+const TICKER_COLUMNS: &[&str] = &["ticker", "symbol", "holding ticker", "stock ticker"];
+const WEIGHT_COLUMNS: &[&str] = &[
+    "weight",
+    "weight (%)",
+    "weight %",
+    "% weight",
+    "weighting",
+    "% of net assets",
+    "% of fund",
+    "portfolio weight",
+    "market value weight",
+];
+const NAME_COLUMNS: &[&str] = &[
+    "name",
+    "security name",
+    "holding name",
+    "company",
+    "description",
+];
+const AS_OF_FORMATS: &[&str] = &["%b %d, %Y", "%m/%d/%Y", "%Y-%m-%d", "%d-%b-%Y", "%B %d, %Y"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Holding {
+    pub ticker: String,
+    pub name: String,
+    /// Percent of the fund, 0–100.
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct HoldingsFile {
+    pub etf: String,
+    pub as_of: Option<NaiveDate>,
+    pub holdings: Vec<Holding>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestedFile {
+    pub file: String,
+    pub etf: String,
+    pub as_of: NaiveDate,
+    pub holdings: usize,
+}
+
+/// Parses an issuer's constituent export. Layouts differ between issuers, so
+/// the header row is found by looking for a ticker and a weight column, and
+/// any preamble above it is scanned for an "as of" date. `default_etf` (the
+/// file name) is used unless the file carries a fund ticker column.
+pub fn parse_holdings(default_etf: &str, content: &str) -> anyhow::Result<HoldingsFile> {
+    let rows = content.lines().map(split_csv_line).collect_vec();
+
+    let (header_idx, header) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, row)| {
+            column(row, TICKER_COLUMNS).is_some() && column(row, WEIGHT_COLUMNS).is_some()
+        })
+        .context("No header row with ticker and weight columns")?;
+    let ticker_col = column(header, TICKER_COLUMNS).unwrap();
+    let weight_col = column(header, WEIGHT_COLUMNS).unwrap();
+    let name_col = column(header, NAME_COLUMNS);
+    let fund_col = column(header, &["fund ticker"]);
+    let asset_class_col = column(header, &["asset class"]);
+
+    let as_of = rows[..header_idx].iter().find_map(|row| parse_as_of(row));
+
+    let mut etf = None;
+    let mut merged = HashMap::<String, Holding>::new();
+    for row in &rows[header_idx + 1..] {
+        let Some(ticker) = row.get(ticker_col).and_then(|t| normalize_ticker(t)) else {
+            continue;
+        };
+        if let Some(class) = asset_class_col.and_then(|c| row.get(c))
+            && !class.is_empty()
+            && !class.eq_ignore_ascii_case("equity")
+        {
+            continue;
+        }
+        let Some(weight) = row.get(weight_col).and_then(|w| parse_weight(w)) else {
+            continue;
+        };
+        if etf.is_none() {
+            etf = fund_col
+                .and_then(|c| row.get(c))
+                .filter(|f| !f.is_empty())
+                .map(|f| f.to_uppercase());
+        }
+
+        let name = name_col
+            .and_then(|c| row.get(c))
+            .cloned()
+            .unwrap_or_default();
+        merged
+            .entry(ticker.clone())
+            .and_modify(|h| h.weight += weight)
+            .or_insert(Holding {
+                ticker,
+                name,
+                weight,
+            });
+    }
+
+    let mut holdings = merged.into_values().collect_vec();
+    // Some issuers export weights as fractions of 1 rather than percentages.
+    let total = holdings.iter().map(|h| h.weight).sum::<f64>();
+    if total > 0.0 && total <= 1.5 {
+        holdings.iter_mut().for_each(|h| h.weight *= 100.0);
+    }
+    holdings.sort_by(|a, b| {
+        b.weight
+            .total_cmp(&a.weight)
+            .then_with(|| a.ticker.cmp(&b.ticker))
+    });
+    if holdings.is_empty() {
+        anyhow::bail!("No holdings found below the header row");
+    }
+
+    Ok(HoldingsFile {
+        etf: etf.unwrap_or_else(|| default_etf.to_uppercase()),
+        as_of,
+        holdings,
+    })
+}
+
+/// Loads every `*.csv` in `dir` into `etf_holdings`, replacing the previous
+/// holdings of each ETF. Files are expected to be named after the ETF, e.g.
+/// `SMH.csv` or `SMH_holdings.csv`.
+pub async fn ingest_holdings_dir(store: &Store, dir: &Path) -> anyhow::Result<Vec<IngestedFile>> {
+    let mut entries = fs::read_dir(dir)
+        .await
+        .with_context(|| format!("Couldn't read {dir:?}"))?;
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    let mut ingested = Vec::new();
+    for path in files {
+        let file = path
+            .file_name()
+            .map(|f| f.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let default_etf = etf_from_file_name(&file);
+        let content = fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Couldn't read {path:?}"))?;
+        let parsed = match parse_holdings(&default_etf, &content) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                warn!("Skipping {file}: {err:#}");
+                continue;
+            }
+        };
+        let as_of = match parsed.as_of {
+            Some(as_of) => as_of,
+            None => {
+                let modified = fs::metadata(&path).await?.modified()?;
+                DateTime::<Local>::from(modified).date_naive()
+            }
+        };
+
+        store
+            .replace_etf_holdings(&parsed.etf, as_of, &file, &parsed.holdings)
+            .await?;
+        info!(
+            "Loaded {} holdings of {} as of {as_of} from {file}",
+            parsed.holdings.len(),
+            parsed.etf
+        );
+        ingested.push(IngestedFile {
+            file,
+            etf: parsed.etf,
+            as_of,
+            holdings: parsed.holdings.len(),
+        });
+    }
+    Ok(ingested)
+}
+
+fn etf_from_file_name(file: &str) -> String {
+    file.chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_uppercase()
+}
+
+fn column(row: &[String], names: &[&str]) -> Option<usize> {
+    row.iter()
+        .position(|cell| names.iter().any(|n| cell.eq_ignore_ascii_case(n)))
+}
+
+fn parse_as_of(row: &[String]) -> Option<NaiveDate> {
+    let (idx, pos) = row
+        .iter()
+        .enumerate()
+        .find_map(|(idx, cell)| Some((idx, cell.to_ascii_lowercase().find("as of")?)))?;
+    // The date is either after "as of" in the same cell or in the next one.
+    let same_cell = row[idx][pos + "as of".len()..].to_string();
+    std::iter::once(same_cell)
+        .chain(row.get(idx + 1).cloned())
+        .find_map(|text| {
+            let text = text.trim_start_matches(':').trim();
+            AS_OF_FORMATS
+                .iter()
+                .find_map(|fmt| NaiveDate::parse_from_str(text, fmt).ok())
+        })
+}
+
+fn parse_weight(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .trim_end_matches('%')
+        .replace(',', "")
+        .parse::<f64>()
+        .ok()
+        .filter(|w| w.is_finite())
+}
+
+/// Bloomberg exchange codes issuers append to non-US listings (`SHOP CN`),
+/// mapped to the TradingView prefix of the market.
+const ISSUER_EXCHANGES: &[(&str, &str)] = &[
+    ("CN", "TSX"),
+    ("CT", "TSX"),
+    ("CV", "TSXV"),
+    ("LN", "LSE"),
+    ("AU", "ASX"),
+    ("AT", "ASX"),
+    ("GR", "XETR"),
+    ("GY", "XETR"),
+    ("HK", "HKEX"),
+];
+
+/// Issuer files separate share classes and exchange codes with spaces
+/// (`BRK B`, `SHOP CN`); rewrite those into a notation [`symbol::canonical`]
+/// understands before converting.
+fn normalize_ticker(ticker: &str) -> Option<String> {
+    let mut parts = ticker
+        .split_whitespace()
+        .map(str::to_uppercase)
+        .collect_vec();
+    let exchange = parts.last().filter(|_| parts.len() > 1).and_then(|code| {
+        ISSUER_EXCHANGES
+            .iter()
+            .find(|(issuer_code, _)| issuer_code == code)
+            .map(|(_, exchange)| *exchange)
+    });
+    if exchange.is_some() {
+        parts.pop();
+    }
+    let root = parts.join("/");
+    if root.is_empty() || root == "-" {
+        return None;
+    }
+    Some(match exchange {
+        Some(exchange) => symbol::canonical(&format!("{exchange}:{root}")),
+        None => symbol::canonical(&root),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ishares_style_export() {
+        let content = "\u{feff}iShares Semiconductor ETF\n\
+            Fund Holdings as of,\"Oct 17, 2025\"\n\
+            Inception Date,\"Jul 10, 2001\"\n\
+            \u{a0}\n\
+            Ticker,Name,Sector,Asset Class,Market Value,Weight (%),Notional Value\n\
+            NVDA,NVIDIA CORP,Information Technology,Equity,\"1,234,567.00\",8.51,\"1,234,567.00\"\n\
+            BRK/B,BERKSHIRE HATHAWAY INC CLASS B,Financials,Equity,\"100.00\",1.20,\"100.00\"\n\
+            USD,USD CASH,Cash and/or Derivatives,Cash,\"50.00\",0.10,\"50.00\"\n\
+            \u{a0}\n\
+            \"The content contained herein is owned or licensed by BlackRock\"\n";
+
+        let parsed = parse_holdings("SOXX", content).unwrap();
+
+        assert_eq!(parsed.etf, "SOXX");
+        assert_eq!(parsed.as_of, NaiveDate::from_ymd_opt(2025, 10, 17));
+        assert_eq!(parsed.holdings.len(), 2);
+        assert_eq!(parsed.holdings[0].ticker, "NVDA");
+        assert_eq!(parsed.holdings[0].weight, 8.51);
+        assert_eq!(parsed.holdings[1].ticker, "BRK.B");
+    }
+
+    #[test]
+    fn parses_fund_ticker_column_and_fractional_weights() {
+        let content = "Fund Ticker,Holding Ticker,Weight,Name\n\
+            PBW,ENPH,0.40,Enphase\n\
+            PBW,FSLR,0.35,First Solar\n\
+            PBW,FSLR,0.05,First Solar\n\
+            PBW,PLUG,0.20,Plug Power\n";
+
+        let parsed = parse_holdings("holdings", content).unwrap();
+
+        assert_eq!(parsed.etf, "PBW");
+        assert_eq!(parsed.as_of, None);
+        let weights = parsed
+            .holdings
+            .iter()
+            .map(|h| (h.ticker.as_str(), h.weight.round()))
+            .collect_vec();
+        assert_eq!(
+            weights,
+            vec![("ENPH", 40.0), ("FSLR", 40.0), ("PLUG", 20.0)]
+        );
+    }
+
+    #[test]
+    fn normalizes_issuer_ticker_spellings() {
+        assert_eq!(normalize_ticker("BRK B").as_deref(), Some("BRK.B"));
+        assert_eq!(normalize_ticker("brk/b").as_deref(), Some("BRK.B"));
+        assert_eq!(normalize_ticker("SHOP CN").as_deref(), Some("SHOP.TO"));
+        assert_eq!(normalize_ticker("RCI/B CN").as_deref(), Some("RCI.B.TO"));
+        assert_eq!(normalize_ticker("AZN LN").as_deref(), Some("AZN.L"));
+        assert_eq!(normalize_ticker("CN").as_deref(), Some("CN"));
+        assert_eq!(normalize_ticker(" - "), None);
+        assert_eq!(normalize_ticker(""), None);
+    }
+}
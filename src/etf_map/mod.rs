@@ -0,0 +1,122 @@
+pub mod holdings;
+pub mod routes;
+pub mod store;
+
+pub use routes::router;
+
+use chrono::{Local, NaiveDate, TimeDelta};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::fetch_candles;
+use crate::store::Store;
+use crate::yf::YFinance;
+
+/// Bars needed for the full IBD RS lookback (four 63-bar quarters).
+pub const MIN_HISTORY_BARS: usize = 252;
+/// Latest candle must be at most this old for the ETF to count as trading.
+const MAX_STALENESS_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Deserialize)]
+struct TradingViewMapping {
+    #[serde(rename = "TradingView_Master_Mapping")]
+    sectors: Vec<Sector>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Sector {
+    pub sector: String,
+    pub sector_etf: String,
+    pub industries: Vec<Industry>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Industry {
+    pub name: String,
+    pub etf: String,
+}
+
+/// The bundled mapping the store tables are seeded from.
+pub fn seed_mapping() -> Vec<Sector> {
+    let json = include_str!("../../templates/sectors_industries_etf_map.json");
+    let mapping = serde_json::from_str::<TradingViewMapping>(json).expect("Invalid JSON");
+    mapping.sectors
+}
+
+/// Current sector/industry → ETF mapping from the store, seeding it from the
+/// bundled JSON once. Mappings deleted later stay deleted.
+pub async fn load_mapping(store: &Store) -> anyhow::Result<Vec<Sector>> {
+    if !store.etf_mappings_seeded().await? {
+        let seed = seed_mapping();
+        store.seed_etf_mappings(&seed).await?;
+        info!(
+            "Seeded {} sector ETF mappings from bundled JSON",
+            seed.len()
+        );
+    }
+    Ok(store.list_etf_mappings().await?)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EtfValidation {
+    pub etf: String,
+    pub valid: bool,
+    pub candles: usize,
+    pub first_day: Option<NaiveDate>,
+    pub last_day: Option<NaiveDate>,
+    pub problems: Vec<String>,
+}
+
+/// Checks that `etf` has daily candles, enough history for RS, and is still
+/// trading.
+pub async fn validate_etf(store: &Store, yf: &YFinance, etf: &str) -> EtfValidation {
+    let mut validation = EtfValidation {
+        etf: etf.to_string(),
+        valid: false,
+        candles: 0,
+        first_day: None,
+        last_day: None,
+        problems: Vec::new(),
+    };
+
+    let candles = match fetch_candles(store, yf, etf).await {
+        Ok(candles) => candles,
+        Err(err) => {
+            validation
+                .problems
+                .push(format!("Failed to fetch candles: {err}"));
+            return validation;
+        }
+    };
+    validation.candles = candles.len();
+    validation.first_day = candles.first().map(|c| c.timestamp.date_naive());
+    validation.last_day = candles.last().map(|c| c.timestamp.date_naive());
+
+    if candles.is_empty() {
+        validation.problems.push("No candles found".to_string());
+    } else if candles.len() < MIN_HISTORY_BARS {
+        validation.problems.push(format!(
+            "Only {} daily candles, RS needs at least {MIN_HISTORY_BARS}",
+            candles.len()
+        ));
+    }
+    if let Some(last_day) = validation.last_day
+        && Local::now().date_naive() - last_day > TimeDelta::days(MAX_STALENESS_DAYS)
+    {
+        validation.problems.push(format!(
+            "Last candle is from {last_day}, the ETF may be delisted"
+        ));
+    }
+    validation.valid = validation.problems.is_empty();
+    validation
+}
+
+#[cfg(test)]
+mod test {
+    use crate::etf_map::seed_mapping;
+
+    #[test]
+    fn print_mapping() {
+        eprintln!("{:#?}", seed_mapping());
+    }
+}
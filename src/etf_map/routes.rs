@@ -0,0 +1,208 @@
+use askama::Template;
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse},
+    routing,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::api_error::ApiError;
+use crate::config::APP_CONFIG;
+use crate::etf_map::{load_mapping, validate_etf};
+use crate::html_error::HtmlError;
+use crate::store::Store;
+use crate::symbol;
+use crate::yf::YFinance;
+
+#[derive(Clone)]
+struct EtfMapState {
+    store: Arc<Store>,
+    yf: Arc<YFinance>,
+}
+
+#[derive(Template)]
+#[template(path = "etf_mappings.html")]
+struct EtfMappingsTemplate {
+    benchmark: String,
+    sectors_json: String,
+    unmapped_json: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SectorMappingRequest {
+    etf: String,
+    /// Save even when the ETF fails validation.
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndustryMappingRequest {
+    sector: String,
+    etf: String,
+    #[serde(default)]
+    force: bool,
+}
+
+pub fn router(store: Arc<Store>, yf: Arc<YFinance>) -> Router {
+    Router::new()
+        .route("/etf_mappings.html", routing::get(etf_mappings_home))
+        .route("/api/etf-mappings", routing::get(list_mappings))
+        .route(
+            "/api/etf-mappings/sectors/{sector}",
+            routing::put(put_sector).delete(delete_sector),
+        )
+        .route(
+            "/api/etf-mappings/industries/{industry}",
+            routing::put(put_industry).delete(delete_industry),
+        )
+        .route("/api/etf-mappings/validate/{etf}", routing::get(validate))
+        .route("/api/etf-mappings/unmapped", routing::get(list_unmapped))
+        .route(
+            "/api/etf-holdings/stocks/{ticker}",
+            routing::get(stock_memberships),
+        )
+        .route("/api/etf-holdings/tags/{tag}", routing::get(tag_exposure))
+        .with_state(EtfMapState { store, yf })
+}
+
+async fn etf_mappings_home(State(state): State<EtfMapState>) -> Result<Html<String>, HtmlError> {
+    let sectors = load_mapping(&state.store).await?;
+    let unmapped = state.store.list_unmapped_groups().await?;
+
+    let html = EtfMappingsTemplate {
+        benchmark: APP_CONFIG.base_ticker.to_uppercase(),
+        sectors_json: serde_json::to_string(&sectors)?,
+        unmapped_json: serde_json::to_string(&unmapped)?,
+    }
+    .render()?;
+
+    Ok(Html(html))
+}
+
+async fn list_mappings(State(state): State<EtfMapState>) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(load_mapping(&state.store).await?))
+}
+
+async fn list_unmapped(State(state): State<EtfMapState>) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(state.store.list_unmapped_groups().await?))
+}
+
+async fn stock_memberships(
+    State(state): State<EtfMapState>,
+    Path(ticker): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let ticker = symbol::canonical(&ticker);
+    Ok(Json(state.store.etf_memberships(&ticker).await?))
+}
+
+async fn tag_exposure(
+    State(state): State<EtfMapState>,
+    Path(tag): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tag = required(&tag, "Tag")?;
+    Ok(Json(state.store.etf_exposure_for_tag(&tag).await?))
+}
+
+async fn validate(
+    State(state): State<EtfMapState>,
+    Path(etf): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let etf = normalize_etf(&etf)?;
+    Ok(Json(validate_etf(&state.store, &state.yf, &etf).await))
+}
+
+async fn put_sector(
+    State(state): State<EtfMapState>,
+    Path(sector): Path<String>,
+    Json(req): Json<SectorMappingRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let sector = required(&sector, "Sector")?;
+    let etf = normalize_etf(&req.etf)?;
+    let validation = validate_etf(&state.store, &state.yf, &etf).await;
+    if !validation.valid && !req.force {
+        return Err(ApiError::bad_request(format!(
+            "{etf} failed validation: {}",
+            validation.problems.join("; ")
+        )));
+    }
+
+    state.store.upsert_sector_etf(&sector, &etf).await?;
+    info!("Mapped sector {sector} to {etf}");
+    Ok(Json(validation))
+}
+
+async fn delete_sector(
+    State(state): State<EtfMapState>,
+    Path(sector): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state.store.delete_sector_etf(sector.trim()).await? {
+        return Err(ApiError::not_found(format!(
+            "No mapping for sector {sector}"
+        )));
+    }
+    info!("Deleted ETF mapping for sector {sector}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn put_industry(
+    State(state): State<EtfMapState>,
+    Path(industry): Path<String>,
+    Json(req): Json<IndustryMappingRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let industry = required(&industry, "Industry")?;
+    let sector = required(&req.sector, "Sector")?;
+    if !state.store.sector_etf_exists(&sector).await? {
+        return Err(ApiError::bad_request(format!(
+            "Sector {sector} has no mapping yet, add it first"
+        )));
+    }
+    let etf = normalize_etf(&req.etf)?;
+    let validation = validate_etf(&state.store, &state.yf, &etf).await;
+    if !validation.valid && !req.force {
+        return Err(ApiError::bad_request(format!(
+            "{etf} failed validation: {}",
+            validation.problems.join("; ")
+        )));
+    }
+
+    state
+        .store
+        .upsert_industry_etf(&industry, &sector, &etf)
+        .await?;
+    info!("Mapped industry {industry} ({sector}) to {etf}");
+    Ok(Json(validation))
+}
+
+async fn delete_industry(
+    State(state): State<EtfMapState>,
+    Path(industry): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state.store.delete_industry_etf(industry.trim()).await? {
+        return Err(ApiError::not_found(format!(
+            "No mapping for industry {industry}"
+        )));
+    }
+    info!("Deleted ETF mapping for industry {industry}");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn required(value: &str, what: &str) -> Result<String, ApiError> {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    if value.is_empty() {
+        return Err(ApiError::bad_request(format!("{what} is required")));
+    }
+    Ok(value)
+}
+
+fn normalize_etf(etf: &str) -> Result<String, ApiError> {
+    let etf = symbol::canonical(etf);
+    if etf.is_empty() {
+        return Err(ApiError::bad_request("ETF is required"));
+    }
+    Ok(etf)
+}
@@ -0,0 +1,319 @@
+use chrono::{Local, NaiveDate};
+use serde::Serialize;
+
+use crate::etf_map::holdings::Holding;
+use crate::etf_map::{Industry, Sector};
+use crate::store::Store;
+
+/// A TradingView sector or industry seen in `stocks` with no ETF mapped.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UnmappedGroup {
+    pub sector: String,
+    pub industry: Option<String>,
+    pub stock_count: i64,
+    /// Ingested ETF holding the largest combined weight of the group's stocks.
+    pub suggested_etf: Option<String>,
+}
+
+/// One ETF that holds a given stock.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct EtfMembership {
+    pub etf: String,
+    pub weight: f64,
+    pub as_of: NaiveDate,
+}
+
+/// How much of an ETF is made up of stocks carrying a tag.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TagEtfExposure {
+    pub etf: String,
+    /// Combined weight, in percent, of the ETF's holdings that carry the tag.
+    pub tagged_weight: f64,
+    pub tagged_holdings: i64,
+    pub total_holdings: i64,
+    pub as_of: NaiveDate,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UnmappedReport {
+    pub sectors: Vec<UnmappedGroup>,
+    pub industries: Vec<UnmappedGroup>,
+}
+
+impl Store {
+    pub async fn list_etf_mappings(&self) -> sqlx::Result<Vec<Sector>> {
+        let sectors = sqlx::query_as::<_, (String, String)>(
+            "SELECT sector, etf FROM sector_etfs ORDER BY lower(sector)",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let industries = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT sector, industry, etf FROM industry_etfs ORDER BY lower(industry)",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sectors
+            .into_iter()
+            .map(|(sector, sector_etf)| Sector {
+                industries: industries
+                    .iter()
+                    .filter(|(s, _, _)| s.eq_ignore_ascii_case(&sector))
+                    .map(|(_, name, etf)| Industry {
+                        name: name.clone(),
+                        etf: etf.clone(),
+                    })
+                    .collect(),
+                sector,
+                sector_etf,
+            })
+            .collect())
+    }
+
+    /// Inserts `sectors` without touching mappings that already exist.
+    pub async fn seed_etf_mappings(&self, sectors: &[Sector]) -> sqlx::Result<()> {
+        let now = Local::now();
+        let mut tx = self.pool.begin().await?;
+        for sector in sectors {
+            sqlx::query(
+                r#"
+                INSERT INTO sector_etfs (sector, etf, updated_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT(sector) DO NOTHING
+                "#,
+            )
+            .bind(&sector.sector)
+            .bind(&sector.sector_etf)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            for industry in &sector.industries {
+                sqlx::query(
+                    r#"
+                    INSERT INTO industry_etfs (industry, sector, etf, updated_at)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT(industry) DO NOTHING
+                    "#,
+                )
+                .bind(&industry.name)
+                .bind(&sector.sector)
+                .bind(&industry.etf)
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        sqlx::query("INSERT OR IGNORE INTO etf_mapping_seed (id, seeded_at) VALUES (1, $1)")
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+
+    /// Whether the bundled mapping was seeded before, even if every mapping
+    /// has been deleted since.
+    pub async fn etf_mappings_seeded(&self) -> sqlx::Result<bool> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM etf_mapping_seed")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count > 0)
+    }
+
+    pub async fn sector_etf_exists(&self, sector: &str) -> sqlx::Result<bool> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sector_etfs WHERE sector = $1")
+            .bind(sector)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count > 0)
+    }
+
+    pub async fn upsert_sector_etf(&self, sector: &str, etf: &str) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sector_etfs (sector, etf, updated_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(sector) DO UPDATE SET
+                etf        = excluded.etf,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(sector)
+        .bind(etf)
+        .bind(Local::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Deletes a sector mapping together with its industries.
+    pub async fn delete_sector_etf(&self, sector: &str) -> sqlx::Result<bool> {
+        let deleted = sqlx::query("DELETE FROM sector_etfs WHERE sector = $1")
+            .bind(sector)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    pub async fn upsert_industry_etf(
+        &self,
+        industry: &str,
+        sector: &str,
+        etf: &str,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO industry_etfs (industry, sector, etf, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT(industry) DO UPDATE SET
+                sector     = excluded.sector,
+                etf        = excluded.etf,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(industry)
+        .bind(sector)
+        .bind(etf)
+        .bind(Local::now())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_industry_etf(&self, industry: &str) -> sqlx::Result<bool> {
+        let deleted = sqlx::query("DELETE FROM industry_etfs WHERE industry = $1")
+            .bind(industry)
+            .execute(&self.pool)
+            .await?
+            .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    pub async fn list_unmapped_groups(&self) -> sqlx::Result<UnmappedReport> {
+        let sectors = sqlx::query_as::<_, UnmappedGroup>(
+            r#"
+            SELECT
+                s.sector_name as sector,
+                NULL as industry,
+                COUNT(*) as stock_count,
+                (
+                    SELECT h.etf
+                    FROM etf_holdings h
+                    JOIN stocks hs ON hs.ticker = h.ticker
+                    WHERE hs.sector_name = s.sector_name
+                    GROUP BY h.etf
+                    ORDER BY SUM(h.weight) DESC
+                    LIMIT 1
+                ) as suggested_etf
+            FROM stocks s
+            LEFT JOIN sector_etfs m ON m.sector = s.sector_name
+            WHERE m.sector IS NULL AND s.sector_name != ''
+            GROUP BY s.sector_name
+            ORDER BY stock_count DESC, lower(s.sector_name)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let industries = sqlx::query_as::<_, UnmappedGroup>(
+            r#"
+            SELECT
+                s.sector_name as sector,
+                s.industry_name as industry,
+                COUNT(*) as stock_count,
+                (
+                    SELECT h.etf
+                    FROM etf_holdings h
+                    JOIN stocks hs ON hs.ticker = h.ticker
+                    WHERE hs.industry_name = s.industry_name
+                    GROUP BY h.etf
+                    ORDER BY SUM(h.weight) DESC
+                    LIMIT 1
+                ) as suggested_etf
+            FROM stocks s
+            LEFT JOIN industry_etfs m ON m.industry = s.industry_name
+            WHERE m.industry IS NULL AND s.industry_name != ''
+            GROUP BY s.sector_name, s.industry_name
+            ORDER BY stock_count DESC, lower(s.industry_name)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(UnmappedReport {
+            sectors,
+            industries,
+        })
+    }
+
+    /// Replaces every holding of `etf` with `holdings`.
+    pub async fn replace_etf_holdings(
+        &self,
+        etf: &str,
+        as_of: NaiveDate,
+        source_file: &str,
+        holdings: &[Holding],
+    ) -> sqlx::Result<()> {
+        let now = Local::now();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM etf_holdings WHERE etf = $1")
+            .bind(etf)
+            .execute(&mut *tx)
+            .await?;
+        for holding in holdings {
+            sqlx::query(
+                r#"
+                INSERT INTO etf_holdings (etf, ticker, name, weight, as_of, source_file, imported_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+            )
+            .bind(etf)
+            .bind(&holding.ticker)
+            .bind(&holding.name)
+            .bind(holding.weight)
+            .bind(as_of)
+            .bind(source_file)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn etf_memberships(&self, ticker: &str) -> sqlx::Result<Vec<EtfMembership>> {
+        sqlx::query_as::<_, EtfMembership>(
+            r#"
+            SELECT etf, weight, as_of
+            FROM etf_holdings
+            WHERE ticker = $1
+            ORDER BY weight DESC, etf
+            "#,
+        )
+        .bind(ticker)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// ETFs ranked by the combined weight of their holdings tagged `tag`.
+    pub async fn etf_exposure_for_tag(&self, tag: &str) -> sqlx::Result<Vec<TagEtfExposure>> {
+        sqlx::query_as::<_, TagEtfExposure>(
+            r#"
+            SELECT
+                h.etf,
+                SUM(CASE WHEN t.id IS NOT NULL THEN h.weight ELSE 0 END) as tagged_weight,
+                COUNT(t.id) as tagged_holdings,
+                COUNT(*) as total_holdings,
+                MAX(h.as_of) as as_of
+            FROM etf_holdings h
+            LEFT JOIN stock_tags st ON st.ticker = h.ticker
+                AND st.tag_id = (SELECT id FROM tags WHERE name = $1)
+            LEFT JOIN tags t ON t.id = st.tag_id
+            GROUP BY h.etf
+            HAVING tagged_holdings > 0
+            ORDER BY tagged_weight DESC, h.etf
+            "#,
+        )
+        .bind(tag)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
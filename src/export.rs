@@ -0,0 +1,458 @@
+use crate::asset_bytes;
+use crate::store::Store;
+use crate::summary::{OfflineAssets, SummaryPage, SummaryReport};
+use crate::tags::routes::stock_views;
+use anyhow::{Context, anyhow};
+use askama::Template;
+use clap::ValueEnum;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+use tracing::info;
+
+const TAG_MAPPINGS: &str = "stocks_themes/tag_mappings.js";
+const APPLICATION: &str = "stocks_themes/application.js";
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// The stock_themes page with its data and app scripts inlined; Stimulus
+    /// and the TradingView widget still load from their CDNs
+    Html,
+    Json,
+    Markdown,
+    /// One row per ticker
+    Csv,
+}
+
+/// Writes the summary to `path` so it can be shared or archived without the
+/// server. The html page still loads TradingView and Stimulus from their CDNs;
+/// popup tabs that need the API (fundamentals, peers, RRG) show an error.
+pub async fn export(
+    store: &Store,
+    report: &SummaryReport,
+    format: ExportFormat,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let content = match format {
+        ExportFormat::Html => render_html(store, report).await?,
+        ExportFormat::Json => serde_json::to_string_pretty(report)?,
+        ExportFormat::Markdown => render_markdown(report),
+        ExportFormat::Csv => render_csv(report),
+    };
+    tokio::fs::write(path, content)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    info!("Exported {} stocks to {}", report.size, path.display());
+    Ok(())
+}
+
+async fn render_html(store: &Store, report: &SummaryReport) -> anyhow::Result<String> {
+    let data = json!({
+        "/api/summary": report,
+        "/api/stock-tags": stock_views(store).await?,
+        "/api/tags": store.list_tags().await?,
+    });
+    let tag_mappings_js = String::from_utf8(
+        asset_bytes(TAG_MAPPINGS)
+            .await
+            .ok_or_else(|| anyhow!("Missing asset {TAG_MAPPINGS}"))?,
+    )?;
+    let application_js = bundle(APPLICATION, &load_modules(APPLICATION).await?)?;
+
+    let html = SummaryPage {
+        base_ticker: &report.benchmark,
+        rs_model: report.rs_model.clone(),
+        offline: Some(OfflineAssets {
+            data_json: inline_script(&data.to_string()),
+            tag_mappings_js: inline_script(&tag_mappings_js),
+            application_js: inline_script(&application_js),
+        }),
+    }
+    .render()?;
+    Ok(html)
+}
+
+/// Keeps inlined text from closing its `<script>` element early.
+fn inline_script(source: &str) -> String {
+    source.replace("</", "<\\/")
+}
+
+/// Reads `entry` and every asset module it imports, keyed by asset path.
+async fn load_modules(entry: &str) -> anyhow::Result<HashMap<String, String>> {
+    let mut sources = HashMap::new();
+    let mut pending = vec![entry.to_string()];
+    while let Some(path) = pending.pop() {
+        if sources.contains_key(&path) {
+            continue;
+        }
+        let bytes = asset_bytes(&path)
+            .await
+            .ok_or_else(|| anyhow!("Missing asset {path}"))?;
+        let source = String::from_utf8(bytes)?;
+        pending.extend(
+            source
+                .lines()
+                .filter_map(parse_import)
+                .filter_map(|import| resolve(&path, import.source)),
+        );
+        sources.insert(path, source);
+    }
+    Ok(sources)
+}
+
+struct Import<'a> {
+    spec: &'a str,
+    source: &'a str,
+}
+
+/// Parses a single-line `import <spec> from "<source>";`, the only form the
+/// page's modules use.
+fn parse_import(line: &str) -> Option<Import<'_>> {
+    let rest = line.trim().strip_prefix("import ")?;
+    let (spec, source) = rest.split_once(" from ")?;
+    let source = source.trim().trim_end_matches(';').trim();
+    let source = source
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .or_else(|| source.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))?;
+    Some(Import {
+        spec: spec.trim(),
+        source,
+    })
+}
+
+/// Resolves a relative import against the importing asset's path; anything
+/// else (CDN urls) isn't an asset and is left to the browser.
+fn resolve(importer: &str, source: &str) -> Option<String> {
+    if !source.starts_with("./") && !source.starts_with("../") {
+        return None;
+    }
+    let mut segments = importer.split('/').collect::<Vec<_>>();
+    segments.pop();
+    for segment in source.split('/') {
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// Joins an ES module graph into one module for an inline `<script>`. Each
+/// asset module becomes a function returning its exports; remote imports are
+/// hoisted to the top once.
+fn bundle(entry: &str, sources: &HashMap<String, String>) -> anyhow::Result<String> {
+    let mut order = Vec::new();
+    visit(entry, sources, &mut HashSet::new(), &mut order)?;
+
+    let mut remote_imports = Vec::<String>::new();
+    let mut modules = String::new();
+    for path in order {
+        let mut body = String::new();
+        let mut exports = Vec::new();
+        for line in sources[path].lines() {
+            if let Some(import) = parse_import(line) {
+                match resolve(path, import.source) {
+                    Some(dependency) => {
+                        let statement = match import.spec.strip_prefix('{') {
+                            Some(_) => {
+                                format!(
+                                    "const {} = __modules[{dependency:?}];",
+                                    import.spec.replace(" as ", ": ")
+                                )
+                            }
+                            None => format!(
+                                "const {} = __modules[{dependency:?}].default;",
+                                import.spec
+                            ),
+                        };
+                        writeln!(body, "{statement}")?;
+                    }
+                    None => {
+                        let line = line.trim().to_string();
+                        if !remote_imports.contains(&line) {
+                            remote_imports.push(line);
+                        }
+                    }
+                }
+                continue;
+            }
+
+            match export_of(line) {
+                Some(Export::Default(rest)) => {
+                    exports.push("default: __default".to_string());
+                    writeln!(body, "const __default = {rest}")?;
+                }
+                Some(Export::Named(name, declaration)) => {
+                    exports.push(name.to_string());
+                    writeln!(body, "{declaration}")?;
+                }
+                None => writeln!(body, "{line}")?,
+            }
+        }
+        writeln!(
+            modules,
+            "__modules[{path:?}] = (() => {{\n{body}return {{ {} }};\n}})();\n",
+            exports.join(", ")
+        )?;
+    }
+
+    Ok(format!(
+        "{}\n\nconst __modules = {{}};\n\n{modules}",
+        remote_imports.join("\n")
+    ))
+}
+
+fn visit<'a>(
+    path: &str,
+    sources: &'a HashMap<String, String>,
+    seen: &mut HashSet<&'a str>,
+    order: &mut Vec<&'a str>,
+) -> anyhow::Result<()> {
+    let (path, source) = sources
+        .get_key_value(path)
+        .ok_or_else(|| anyhow!("Missing module {path}"))?;
+    if !seen.insert(path) {
+        return Ok(());
+    }
+    for import in source.lines().filter_map(parse_import) {
+        if let Some(dependency) = resolve(path, import.source) {
+            visit(&dependency, sources, seen, order)?;
+        }
+    }
+    order.push(path);
+    Ok(())
+}
+
+enum Export<'a> {
+    /// The expression after `export default`.
+    Default(&'a str),
+    /// The exported name and its declaration without `export`.
+    Named(&'a str, &'a str),
+}
+
+fn export_of(line: &str) -> Option<Export<'_>> {
+    let declaration = line.strip_prefix("export ")?;
+    if let Some(rest) = declaration.strip_prefix("default ") {
+        return Some(Export::Default(rest));
+    }
+    let (_, rest) = declaration.split_once(' ')?;
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .unwrap_or(rest.len());
+    Some(Export::Named(&rest[..end], declaration))
+}
+
+fn render_markdown(report: &SummaryReport) -> String {
+    let mut out = format!(
+        "# Stock themes\n\n{} stocks · benchmark {} · RS model {} · generated {}\n",
+        report.size,
+        report.benchmark,
+        report.rs_model,
+        report.generated_at.format("%Y-%m-%d %H:%M"),
+    );
+    for sector in &report.sectors {
+        let _ = write!(
+            out,
+            "\n## {}{}\n",
+            markdown_cell(&sector.name),
+            group_details(sector.size, sector.etf.as_deref(), sector.rs)
+        );
+        for industry in &sector.industries {
+            let _ = write!(
+                out,
+                "\n### {}{}\n\n| Ticker | Exchange | RS | ADR % | Avg volume |\n|---|---|---:|---:|---:|\n",
+                markdown_cell(&industry.name),
+                group_details(industry.size, industry.etf.as_deref(), industry.rs)
+            );
+            for ticker in &industry.tickers {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} |",
+                    ticker.ticker,
+                    ticker.exchange,
+                    fmt_opt(ticker.rs, 2),
+                    fmt_opt(ticker.metrics.as_ref().map(|m| m.adr_pct), 1),
+                    ticker
+                        .metrics
+                        .as_ref()
+                        .map(|m| m.avg_volume.to_string())
+                        .unwrap_or_default(),
+                );
+            }
+        }
+    }
+    out
+}
+
+fn group_details(size: usize, etf: Option<&str>, rs: Option<f64>) -> String {
+    let mut details = format!(" ({size} stocks");
+    if let Some(etf) = etf {
+        let _ = write!(details, ", ETF {etf}");
+    }
+    if let Some(rs) = rs {
+        let _ = write!(details, ", RS {rs:.2}");
+    }
+    details.push(')');
+    details
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn render_csv(report: &SummaryReport) -> String {
+    let mut out = String::from(
+        "sector,industry,ticker,exchange,rs,sector_rs,industry_rs,sector_etf,industry_etf,adr_pct,avg_volume\n",
+    );
+    for sector in &report.sectors {
+        for industry in &sector.industries {
+            for ticker in &industry.tickers {
+                let row = [
+                    csv_field(&sector.name),
+                    csv_field(&industry.name),
+                    csv_field(&ticker.ticker),
+                    csv_field(&ticker.exchange),
+                    fmt_opt(ticker.rs, 2),
+                    fmt_opt(sector.rs, 2),
+                    fmt_opt(industry.rs, 2),
+                    csv_field(sector.etf.as_deref().unwrap_or_default()),
+                    csv_field(industry.etf.as_deref().unwrap_or_default()),
+                    fmt_opt(ticker.metrics.as_ref().map(|m| m.adr_pct), 2),
+                    ticker
+                        .metrics
+                        .as_ref()
+                        .map(|m| m.avg_volume.to_string())
+                        .unwrap_or_default(),
+                ];
+                out.push_str(&row.join(","));
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn fmt_opt(value: Option<f64>, precision: usize) -> String {
+    value
+        .map(|value| format!("{value:.precision$}"))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::StockMetrics;
+    use crate::summary::{ReportIndustry, ReportSector, ReportTicker};
+    use chrono::Local;
+
+    fn report() -> SummaryReport {
+        SummaryReport {
+            benchmark: "SPY".into(),
+            rs_model: "ibd".into(),
+            generated_at: Local::now(),
+            size: 2,
+            sectors: vec![ReportSector {
+                name: "Technology".into(),
+                url: String::new(),
+                etf: Some("XLK".into()),
+                rs: Some(81.5),
+                size: 2,
+                industries: vec![ReportIndustry {
+                    name: "Software, Infrastructure".into(),
+                    url: String::new(),
+                    etf: None,
+                    rs: None,
+                    size: 2,
+                    tickers: vec![
+                        ReportTicker {
+                            ticker: "MSFT".into(),
+                            exchange: "NASDAQ".into(),
+                            rs: Some(90.0),
+                            metrics: Some(StockMetrics {
+                                adr_pct: 1.75,
+                                avg_volume: 20_000_000,
+                            }),
+                        },
+                        ReportTicker {
+                            ticker: "ORCL".into(),
+                            exchange: "NYSE".into(),
+                            rs: None,
+                            metrics: None,
+                        },
+                    ],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn csv_has_a_quoted_row_per_ticker() {
+        let csv = render_csv(&report());
+        let lines = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            "Technology,\"Software, Infrastructure\",MSFT,NASDAQ,90.00,81.50,,XLK,,1.75,20000000"
+        );
+        assert_eq!(
+            lines[2],
+            "Technology,\"Software, Infrastructure\",ORCL,NYSE,,81.50,,XLK,,,"
+        );
+    }
+
+    #[test]
+    fn markdown_lists_groups_and_tickers() {
+        let markdown = render_markdown(&report());
+
+        assert!(markdown.contains("## Technology (2 stocks, ETF XLK, RS 81.50)"));
+        assert!(markdown.contains("### Software, Infrastructure (2 stocks)"));
+        assert!(markdown.contains("| MSFT | NASDAQ | 90.00 | 1.8 | 20000000 |"));
+        assert!(markdown.contains("| ORCL | NYSE |  |  |  |"));
+    }
+
+    #[test]
+    fn bundle_inlines_relative_modules_once() {
+        let sources = HashMap::from([
+            (
+                "app/main.js".to_string(),
+                "import { Application } from \"https://cdn/stimulus.js\";\nimport Popup from \"./controllers/popup.js\";\nimport { api } from \"./api.js\";\nApplication.start(Popup, api);".to_string(),
+            ),
+            (
+                "app/controllers/popup.js".to_string(),
+                "import { Application } from \"https://cdn/stimulus.js\";\nimport { api as popupApi } from \"../api.js\";\nexport default class extends Application {\n}".to_string(),
+            ),
+            (
+                "app/api.js".to_string(),
+                "export const api = {\n};".to_string(),
+            ),
+        ]);
+
+        let bundled = bundle("app/main.js", &sources).unwrap();
+
+        assert_eq!(bundled.matches("import { Application }").count(), 1);
+        let api = bundled.find("__modules[\"app/api.js\"] =").unwrap();
+        let popup = bundled
+            .find("__modules[\"app/controllers/popup.js\"] =")
+            .unwrap();
+        let main = bundled.find("__modules[\"app/main.js\"] =").unwrap();
+        assert!(api < popup && popup < main);
+        assert!(bundled.contains("return { api };"));
+        assert!(bundled.contains("const { api: popupApi } = __modules[\"app/api.js\"];"));
+        assert!(bundled.contains("const __default = class extends Application {"));
+        assert!(bundled.contains("const Popup = __modules[\"app/controllers/popup.js\"].default;"));
+    }
+}
@@ -0,0 +1,198 @@
+use axum::Json;
+use axum::extract::{Extension, Path};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::api_error::ApiError;
+use crate::screener::{ScreenRow, load_universe};
+use crate::store::Store;
+
+/// RS rating at or above which a member counts as a group leader.
+const LEADER_RS_RATING: u8 = 80;
+
+/// Strength of one TradingView sector or industry, computed from the stored
+/// stocks that belong to it rather than from a proxy ETF.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupStrength {
+    pub name: String,
+    /// 1 = strongest group.
+    pub rank: usize,
+    pub members: usize,
+    /// Median raw RS of the members, on the same scale as ETF RS values.
+    pub median_rs: f64,
+    /// Percentage of members with an RS rating of 80 or better.
+    pub leaders_pct: f64,
+    /// Equal-weighted member returns, in percent.
+    pub perf_1m: f64,
+    pub perf_3m: f64,
+    pub perf_6m: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GroupRanks {
+    pub sectors: Vec<GroupStrength>,
+    pub industries: Vec<GroupStrength>,
+}
+
+impl GroupRanks {
+    pub fn sector(&self, name: &str) -> Option<&GroupStrength> {
+        self.sectors
+            .iter()
+            .find(|g| g.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn industry(&self, name: &str) -> Option<&GroupStrength> {
+        self.industries
+            .iter()
+            .find(|g| g.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Ranks every sector and industry in the stored universe.
+pub async fn load_group_ranks(store: &Store) -> anyhow::Result<GroupRanks> {
+    let rows = load_universe(store).await?;
+    Ok(GroupRanks {
+        sectors: rank_groups(&rows, |row| &row.stock.sector.name),
+        industries: rank_groups(&rows, |row| &row.stock.industry.name),
+    })
+}
+
+/// Groups `rows` by `key` and ranks the groups by median RS, breaking ties by
+/// the share of leaders. Members without candles are ignored.
+pub fn rank_groups(rows: &[ScreenRow], key: impl Fn(&ScreenRow) -> &String) -> Vec<GroupStrength> {
+    let mut groups = HashMap::<&String, Vec<&ScreenRow>>::new();
+    for row in rows.iter().filter(|row| row.rs.is_some()) {
+        groups.entry(key(row)).or_default().push(row);
+    }
+
+    let mut ranked = groups
+        .into_iter()
+        .map(|(name, members)| {
+            let n = members.len() as f64;
+            let leaders = members
+                .iter()
+                .filter(|m| m.rs_rating.is_some_and(|r| r >= LEADER_RS_RATING))
+                .count();
+            let mean_perf = |perf: fn(&crate::Performance) -> f64| {
+                let values = members
+                    .iter()
+                    .filter_map(|m| m.performance.as_ref().map(perf))
+                    .collect_vec();
+                if values.is_empty() {
+                    0.0
+                } else {
+                    round2(values.iter().sum::<f64>() / values.len() as f64)
+                }
+            };
+
+            GroupStrength {
+                name: name.clone(),
+                rank: 0,
+                members: members.len(),
+                median_rs: round2(median(members.iter().filter_map(|m| m.rs).collect())),
+                leaders_pct: round2(leaders as f64 * 100.0 / n),
+                perf_1m: mean_perf(|p| p.perf_1m),
+                perf_3m: mean_perf(|p| p.perf_3m),
+                perf_6m: mean_perf(|p| p.perf_6m),
+            }
+        })
+        .collect_vec();
+    ranked.sort_by(|a, b| {
+        b.median_rs
+            .total_cmp(&a.median_rs)
+            .then_with(|| b.leaders_pct.total_cmp(&a.leaders_pct))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    for (idx, group) in ranked.iter_mut().enumerate() {
+        group.rank = idx + 1;
+    }
+    ranked
+}
+
+pub async fn group_ranks_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Path(kind): Path<String>,
+) -> Result<Json<Vec<GroupStrength>>, ApiError> {
+    let ranks = load_group_ranks(&store).await?;
+    match kind.as_str() {
+        "sectors" => Ok(Json(ranks.sectors)),
+        "industries" => Ok(Json(ranks.industries)),
+        other => Err(ApiError::not_found(format!(
+            "Unknown group kind {other:?}, expected sectors or industries"
+        ))),
+    }
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Group, Stock};
+    use chrono::NaiveDate;
+
+    fn row(ticker: &str, industry: &str, rs: f64, rating: u8) -> ScreenRow {
+        let group = |name: &str| Group {
+            name: name.to_string(),
+            url: String::new(),
+        };
+        ScreenRow {
+            stock: Stock {
+                ticker: ticker.to_string(),
+                exchange: "NASDAQ".to_string(),
+                sector: group("Technology"),
+                industry: group(industry),
+                last_update: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            },
+            tags: Vec::new(),
+            rs: Some(rs),
+            rs_rating: Some(rating),
+            metrics: None,
+            close: None,
+            performance: None,
+            eps_growth_q: None,
+            revenue_growth_q: None,
+            eps_surprise_pct: None,
+        }
+    }
+
+    #[test]
+    fn ranks_groups_by_median_rs() {
+        let rows = vec![
+            row("NVDA", "Semiconductors", 1.6, 99),
+            row("AMD", "Semiconductors", 1.2, 85),
+            row("INTC", "Semiconductors", 0.7, 10),
+            row("MSFT", "Software", 1.1, 70),
+            row("ORCL", "Software", 1.4, 90),
+        ];
+
+        let ranked = rank_groups(&rows, |row| &row.stock.industry.name);
+
+        assert_eq!(ranked[0].name, "Software");
+        assert_eq!(ranked[0].rank, 1);
+        assert_eq!(ranked[0].median_rs, 1.25);
+        assert_eq!(ranked[0].leaders_pct, 50.0);
+        assert_eq!(ranked[1].name, "Semiconductors");
+        assert_eq!(ranked[1].rank, 2);
+        assert_eq!(ranked[1].median_rs, 1.2);
+        assert_eq!(ranked[1].leaders_pct, 66.67);
+        assert_eq!(ranked[1].members, 3);
+    }
+}
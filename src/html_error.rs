@@ -1,25 +1,51 @@
-use axum::{
-    http::StatusCode,
-    response::{IntoResponse, Response},
-};
+use axum::response::{Html, IntoResponse, Response};
 
-pub struct HtmlError(anyhow::Error);
+use crate::api_error::ApiError;
+
+/// Error returned by page handlers: same status mapping as [`ApiError`],
+/// rendered as a small HTML page instead of JSON.
+pub struct HtmlError(ApiError);
 
 impl<E> From<E> for HtmlError
 where
     E: Into<anyhow::Error>,
 {
     fn from(err: E) -> Self {
-        Self(err.into())
+        Self(ApiError::from(err))
+    }
+}
+
+impl From<ApiError> for HtmlError {
+    fn from(err: ApiError) -> Self {
+        Self(err)
     }
 }
 
 impl IntoResponse for HtmlError {
     fn into_response(self) -> Response {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Something went wrong: {}", self.0),
-        )
-            .into_response()
+        let status = self.0.status();
+        let title = status.canonical_reason().unwrap_or("Error");
+        let page = format!(
+            "<!DOCTYPE html><html><head><title>{code} {title}</title></head>\
+             <body><h1>{code} {title}</h1><p>Something went wrong: {message}</p></body></html>",
+            code = status.as_u16(),
+            message = escape_html(self.0.message()),
+        );
+        (status, Html(page)).into_response()
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
     }
+    escaped
 }
@@ -7,7 +7,6 @@ use axum::extract::{Extension, Path};
 use axum::http::StatusCode;
 use axum::http::header::{CACHE_CONTROL, CONTENT_TYPE, HeaderValue};
 use axum::middleware::Next;
-use axum::response::Html;
 use axum::response::Response;
 use axum::{Router, middleware, routing};
 use chrono::{DateTime, Local, Months, NaiveDate, TimeDelta, Utc};
@@ -23,18 +22,28 @@ use tokio::net::TcpListener;
 use tokio::sync::Mutex as AsyncMutex;
 use tracing::{info, trace, warn};
 
+pub mod api_error;
+pub mod backtest;
 pub mod config;
+pub mod correlation;
 pub mod etf_map;
+pub mod export;
+pub mod groups;
 pub mod html_error;
 pub mod metrics;
+pub mod rotation;
 pub mod rrg_util;
 pub mod rs;
+pub mod rs_model;
+pub mod screener;
 pub mod store;
 pub mod summary;
+pub mod symbol;
 pub mod tags;
 pub mod trades;
 pub mod tv;
 pub mod util;
+pub mod watchlist;
 pub mod yf;
 
 #[cfg(not(debug_assertions))]
@@ -97,13 +106,11 @@ pub fn init_logger() {
         .init();
 }
 
-pub async fn start_http_server(store: Arc<Store>, home: String) -> anyhow::Result<()> {
-    let addr = format!("127.0.0.1:{}", APP_CONFIG.http_port);
-    let listener = TcpListener::bind(&addr)
-        .await
-        .with_context(|| format!("Failed to bind at {addr}"))?;
-
-    info!("Running http server at: {addr}");
+pub async fn start_http_server(
+    store: Arc<Store>,
+    yf: Arc<YFinance>,
+    summary: Arc<summary::SummaryState>,
+) -> anyhow::Result<()> {
     let untagged = store.list_untagged_stocks().await?;
     if !untagged.is_empty() {
         warn!(
@@ -112,11 +119,47 @@ pub async fn start_http_server(store: Arc<Store>, home: String) -> anyhow::Resul
             untagged.join(",")
         );
     }
+    let rrg = Arc::new(rrg_util::RrgTickers::default());
+    serve(app_router(store, yf, summary, rrg)).await
+}
+
+/// Binds `APP_CONFIG.http_port` and serves `app` until the process exits.
+pub async fn serve(app: Router) -> anyhow::Result<()> {
+    let addr = format!("127.0.0.1:{}", APP_CONFIG.http_port);
+    let listener = TcpListener::bind(&addr)
+        .await
+        .with_context(|| format!("Failed to bind at {addr}"))?;
+
+    info!("Running http server at: {addr}");
+    let app = app.layer(middleware::from_fn(api_error::json_rejections));
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
 
+/// Stock themes, RRG, backtest, tags, ETF mapping, watchlists and
+/// fundamentals, all sharing `store`, `yf`, `summary`, the plotted RRG
+/// tickers and one fundamentals client.
+pub fn app_router(
+    store: Arc<Store>,
+    yf: Arc<YFinance>,
+    summary: Arc<summary::SummaryState>,
+    rrg: Arc<rrg_util::RrgTickers>,
+) -> Router {
     let fundamentals_client = tv::fundamentals::FundamentalsClient::new();
-    let app = Router::new()
-        .route("/", routing::get(async || Html(home)))
-        .route("/rrg.html", routing::get(rrg_util::rrg_home))
+    Router::new()
+        .route("/", routing::get(summary::summary_home))
+        .route("/api/summary", routing::get(summary::summary_handler))
+        .route(
+            "/api/summary/refresh",
+            routing::post(summary::refresh_handler),
+        )
+        .route("/api/summary/events", routing::get(summary::events_handler))
+        .route("/api/watchlist", routing::post(watchlist::upload_handler))
+        .route(
+            "/api/watchlist/load/{name}",
+            routing::post(watchlist::load_handler),
+        )
         .route(
             "/stock_tags.html",
             routing::get(tags::stock_tags::stock_tags_home),
@@ -125,7 +168,18 @@ pub async fn start_http_server(store: Arc<Store>, home: String) -> anyhow::Resul
             "/api/stock-tags/metrics/stream",
             routing::get(tags::stock_tags::stock_tag_metrics_stream),
         )
-        .route("/api/rrg/{ticker}", routing::get(rrg_util::rrg_handler))
+        .route(
+            "/watchlist_diff.html",
+            routing::get(watchlist::runs::diff_home),
+        )
+        .route(
+            "/api/watchlist/runs",
+            routing::get(watchlist::runs::runs_handler),
+        )
+        .route(
+            "/api/watchlist/diff",
+            routing::get(watchlist::runs::diff_handler),
+        )
         .route(
             "/api/fundamentals/{exchange}/{ticker}",
             routing::get(tv::fundamentals_api::get),
@@ -135,36 +189,65 @@ pub async fn start_http_server(store: Arc<Store>, home: String) -> anyhow::Resul
             routing::post(tv::fundamentals_api::refresh),
         )
         .route("/assets/{*path}", routing::get(static_asset))
-        .merge(tags::router(store.clone()))
+        .merge(rrg_router())
+        .merge(backtest_router())
+        .merge(tags::router(store.clone(), yf.clone()))
+        .merge(etf_map::router(store.clone(), yf.clone()))
+        .merge(watchlist::routes::router(store.clone()))
         .layer(Extension(fundamentals_client))
+        .layer(Extension(rrg))
+        .layer(Extension(yf))
+        .layer(Extension(summary))
         .layer(Extension(store))
-        .layer(middleware::from_fn(no_cache));
-    axum::serve(listener, app).await?;
+        .layer(middleware::from_fn(no_cache))
+}
 
-    Ok(())
+/// RRG page and APIs with the rotation heatmap and group ranks. Handlers take
+/// the store, `YFinance` and the plotted [`rrg_util::RrgTickers`] from
+/// extensions.
+pub fn rrg_router() -> Router {
+    Router::new()
+        .route("/rrg.html", routing::get(rrg_util::rrg_home))
+        .route(
+            "/api/rrg/crossings",
+            routing::get(rrg_util::crossings_handler),
+        )
+        .route("/api/rrg/batch", routing::post(rrg_util::rrg_batch_handler))
+        .route(
+            "/api/rrg/frames",
+            routing::post(rrg_util::rrg_frames_handler),
+        )
+        .route(
+            "/api/rrg/consensus",
+            routing::post(rrg_util::consensus_handler),
+        )
+        .route(
+            "/api/rrg/tickers",
+            routing::put(rrg_util::set_tickers_handler),
+        )
+        .route("/api/rrg/{ticker}", routing::get(rrg_util::rrg_handler))
+        .route(
+            "/api/rotation/heatmap",
+            routing::get(rotation::heatmap_handler),
+        )
+        .route(
+            "/api/groups/{kind}",
+            routing::get(groups::group_ranks_handler),
+        )
+}
+
+/// Backtest page and API; handlers take the store from an extension.
+pub fn backtest_router() -> Router {
+    Router::new()
+        .route("/backtest.html", routing::get(backtest::backtest_home))
+        .route("/api/backtest", routing::get(backtest::backtest_handler))
 }
 
 pub async fn static_asset(Path(path): Path<String>) -> Result<Response, StatusCode> {
     if path.is_empty() || path.starts_with('/') || path.contains("..") || path.contains('\\') {
         return Err(StatusCode::BAD_REQUEST);
     }
-
-    #[cfg(debug_assertions)]
-    let bytes = {
-        let full_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .join("assets")
-            .join(&path);
-        tokio::fs::read(&full_path)
-            .await
-            .map_err(|_| StatusCode::NOT_FOUND)?
-    };
-
-    #[cfg(not(debug_assertions))]
-    let bytes = ASSETS
-        .get_file(&path)
-        .ok_or(StatusCode::NOT_FOUND)?
-        .contents()
-        .to_vec();
+    let bytes = asset_bytes(&path).await.ok_or(StatusCode::NOT_FOUND)?;
 
     let content_type = match PathBuf::from(&path)
         .extension()
@@ -183,6 +266,23 @@ pub async fn static_asset(Path(path): Path<String>) -> Result<Response, StatusCo
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
+/// Reads a file under `assets/`: from disk in debug builds so edits show up
+/// without a rebuild, from the embedded copy in release builds.
+pub async fn asset_bytes(path: &str) -> Option<Vec<u8>> {
+    #[cfg(debug_assertions)]
+    {
+        let full_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("assets")
+            .join(path);
+        tokio::fs::read(&full_path).await.ok()
+    }
+
+    #[cfg(not(debug_assertions))]
+    {
+        ASSETS.get_file(path).map(|file| file.contents().to_vec())
+    }
+}
+
 pub async fn no_cache(request: axum::extract::Request, next: Next) -> Response {
     let mut response = next.run(request).await;
     response
@@ -199,6 +299,7 @@ pub async fn fetch_candles(
     yf: &YFinance,
     ticker: &str,
 ) -> anyhow::Result<Vec<Candle>> {
+    let ticker = &symbol::canonical(ticker);
     let lock = {
         let mut map = FETCH_LOCKS.lock().expect("lock poison");
         Arc::clone(map.entry(ticker.to_string()).or_default())
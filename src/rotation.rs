@@ -0,0 +1,336 @@
+use axum::Json;
+use axum::extract::{Extension, Query};
+use chrono::{Local, NaiveDate, TimeDelta};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::info;
+
+use crate::api_error::ApiError;
+use crate::config::APP_CONFIG;
+use crate::rs_model::{RS_MODEL, RsModel};
+use crate::store::Store;
+use crate::{Performance, TickerType};
+
+/// One day's performance and RS rank of a sector or industry.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PerformanceSnapshot {
+    pub ticker: String,
+    pub as_of: NaiveDate,
+    pub perf_1m: f64,
+    pub perf_3m: f64,
+    pub perf_6m: f64,
+    pub perf_1y: f64,
+    pub rs: f64,
+    pub rank: i64,
+}
+
+impl Store {
+    pub async fn save_performance_history(
+        &self,
+        ticker_type: TickerType,
+        snapshots: &[PerformanceSnapshot],
+    ) -> sqlx::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for snap in snapshots {
+            sqlx::query(
+                r#"
+                INSERT INTO performance_history
+                    (ticker, ticker_type, as_of, perf_1m, perf_3m, perf_6m, perf_1y, rs, rank)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT(ticker, ticker_type, as_of) DO UPDATE SET
+                    perf_1m = excluded.perf_1m,
+                    perf_3m = excluded.perf_3m,
+                    perf_6m = excluded.perf_6m,
+                    perf_1y = excluded.perf_1y,
+                    rs      = excluded.rs,
+                    rank    = excluded.rank
+                "#,
+            )
+            .bind(&snap.ticker)
+            .bind(ticker_type)
+            .bind(snap.as_of)
+            .bind(snap.perf_1m)
+            .bind(snap.perf_3m)
+            .bind(snap.perf_6m)
+            .bind(snap.perf_1y)
+            .bind(snap.rs)
+            .bind(snap.rank)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn get_performance_history(
+        &self,
+        ticker_type: TickerType,
+        since: NaiveDate,
+    ) -> sqlx::Result<Vec<PerformanceSnapshot>> {
+        sqlx::query_as::<_, PerformanceSnapshot>(
+            r#"
+            SELECT ticker, as_of, perf_1m, perf_3m, perf_6m, perf_1y, rs, rank
+            FROM performance_history
+            WHERE ticker_type = $1 AND as_of >= $2
+            ORDER BY as_of, rank
+            "#,
+        )
+        .bind(ticker_type)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await
+    }
+}
+
+/// Ranks freshly fetched sector/industry performances by RS against the
+/// benchmark and appends them to the history table under today's date.
+pub async fn record_snapshot(
+    store: &Store,
+    ticker_type: TickerType,
+    perfs: &[Performance],
+) -> anyhow::Result<()> {
+    let base_candles = store.get_candles(&APP_CONFIG.base_ticker).await?;
+    let base = if base_candles.is_empty() {
+        // RS ranks don't depend on the benchmark, only the absolute RS values do.
+        Performance::new(&APP_CONFIG.base_ticker, TickerType::Stock, HashMap::new())
+    } else {
+        Performance::compute(&APP_CONFIG.base_ticker, TickerType::Stock, &base_candles)
+    };
+
+    let snapshots = rank_snapshots(&RS_MODEL, perfs, &base, Local::now().date_naive());
+    store
+        .save_performance_history(ticker_type, &snapshots)
+        .await?;
+    info!(
+        "Recorded {} {ticker_type:?} performance snapshots",
+        snapshots.len()
+    );
+    Ok(())
+}
+
+fn rank_snapshots(
+    model: &RsModel,
+    perfs: &[Performance],
+    base: &Performance,
+    as_of: NaiveDate,
+) -> Vec<PerformanceSnapshot> {
+    perfs
+        .iter()
+        .map(|perf| (perf, model.rs_performance(perf, base)))
+        .sorted_by(|a, b| b.1.total_cmp(&a.1))
+        .enumerate()
+        .map(|(idx, (perf, rs))| PerformanceSnapshot {
+            ticker: perf.ticker.clone(),
+            as_of,
+            perf_1m: perf.perf_1m,
+            perf_3m: perf.perf_3m,
+            perf_6m: perf.perf_6m,
+            perf_1y: perf.perf_1y,
+            rs: (rs * 100.0).round() / 100.0,
+            rank: idx as i64 + 1,
+        })
+        .collect()
+}
+
+// ── Heatmap API ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupKind {
+    Sector,
+    Industry,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Interval {
+    Day,
+    Week,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HeatmapQuery {
+    #[serde(rename = "type", default = "default_kind")]
+    kind: GroupKind,
+    #[serde(default = "default_interval")]
+    interval: Interval,
+    /// How many weeks of history to include.
+    #[serde(default = "default_weeks")]
+    weeks: u32,
+}
+
+fn default_kind() -> GroupKind {
+    GroupKind::Sector
+}
+
+fn default_interval() -> Interval {
+    Interval::Week
+}
+
+fn default_weeks() -> u32 {
+    12
+}
+
+#[derive(Debug, Serialize)]
+pub struct Heatmap {
+    /// Column labels (oldest → newest): the snapshot date closing each period.
+    pub periods: Vec<NaiveDate>,
+    /// Rows ordered by the latest rank.
+    pub rows: Vec<HeatmapRow>,
+    /// Number of groups ranked in the latest period.
+    pub group_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeatmapRow {
+    pub name: String,
+    /// Rank per period, `None` where the group was missing from that snapshot.
+    pub ranks: Vec<Option<i64>>,
+    pub rs: Vec<Option<f64>>,
+    /// Positive when the group climbed the ranking over the window.
+    pub rank_change: Option<i64>,
+}
+
+pub async fn heatmap_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Query(query): Query<HeatmapQuery>,
+) -> Result<Json<Heatmap>, ApiError> {
+    let ticker_type = match query.kind {
+        GroupKind::Sector => TickerType::Sector,
+        GroupKind::Industry => TickerType::Industry,
+    };
+    let since = Local::now().date_naive() - TimeDelta::weeks(query.weeks.clamp(1, 104).into());
+    let history = store.get_performance_history(ticker_type, since).await?;
+
+    Ok(Json(build_heatmap(history, query.interval)))
+}
+
+/// Pivots snapshots into a rank matrix. Weekly buckets keep the last snapshot
+/// taken in each ISO week.
+fn build_heatmap(history: Vec<PerformanceSnapshot>, interval: Interval) -> Heatmap {
+    let mut by_period = BTreeMap::<NaiveDate, NaiveDate>::new();
+    for date in history.iter().map(|s| s.as_of).unique() {
+        let bucket = match interval {
+            Interval::Day => date,
+            Interval::Week => date.week(chrono::Weekday::Mon).first_day(),
+        };
+        let last = by_period.entry(bucket).or_insert(date);
+        *last = (*last).max(date);
+    }
+    let periods = by_period.into_values().collect_vec();
+    let column = periods
+        .iter()
+        .enumerate()
+        .map(|(idx, date)| (*date, idx))
+        .collect::<HashMap<_, _>>();
+
+    let mut rows = BTreeMap::<String, HeatmapRow>::new();
+    for snap in history {
+        let Some(&idx) = column.get(&snap.as_of) else {
+            continue;
+        };
+        let row = rows
+            .entry(snap.ticker.clone())
+            .or_insert_with(|| HeatmapRow {
+                name: snap.ticker.clone(),
+                ranks: vec![None; periods.len()],
+                rs: vec![None; periods.len()],
+                rank_change: None,
+            });
+        row.ranks[idx] = Some(snap.rank);
+        row.rs[idx] = Some(snap.rs);
+    }
+
+    let mut rows = rows.into_values().collect_vec();
+    for row in &mut rows {
+        let first = row.ranks.iter().flatten().next();
+        let last = row.ranks.last().copied().flatten();
+        row.rank_change = first.zip(last).map(|(first, last)| first - last);
+    }
+    let group_count = rows
+        .iter()
+        .filter(|r| r.ranks.last().is_some_and(Option::is_some))
+        .count();
+    rows.sort_by_key(|row| {
+        (
+            row.ranks.last().copied().flatten().unwrap_or(i64::MAX),
+            row.name.clone(),
+        )
+    });
+
+    Heatmap {
+        periods,
+        rows,
+        group_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(ticker: &str, as_of: &str, rank: i64) -> PerformanceSnapshot {
+        PerformanceSnapshot {
+            ticker: ticker.to_string(),
+            as_of: as_of.parse().unwrap(),
+            perf_1m: 0.0,
+            perf_3m: 0.0,
+            perf_6m: 0.0,
+            perf_1y: 0.0,
+            rs: 1.0,
+            rank,
+        }
+    }
+
+    #[test]
+    fn weekly_heatmap_keeps_last_snapshot_per_week() {
+        let history = vec![
+            snap("Energy", "2025-03-03", 2),
+            snap("Technology", "2025-03-03", 1),
+            snap("Energy", "2025-03-07", 1),
+            snap("Technology", "2025-03-07", 2),
+            snap("Energy", "2025-03-10", 1),
+            snap("Technology", "2025-03-10", 2),
+        ];
+
+        let heatmap = build_heatmap(history, Interval::Week);
+
+        assert_eq!(
+            heatmap.periods,
+            vec!["2025-03-07".parse().unwrap(), "2025-03-10".parse().unwrap()]
+        );
+        assert_eq!(heatmap.rows[0].name, "Energy");
+        assert_eq!(heatmap.rows[0].ranks, vec![Some(1), Some(1)]);
+        assert_eq!(heatmap.rows[1].ranks, vec![Some(2), Some(2)]);
+        assert_eq!(heatmap.group_count, 2);
+    }
+
+    #[test]
+    fn ranks_by_rs_against_benchmark() {
+        let perf = |ticker: &str, p: f64| Performance {
+            ticker: ticker.to_string(),
+            ticker_type: TickerType::Sector,
+            perf_1m: p,
+            perf_3m: p,
+            perf_6m: p,
+            perf_1y: p,
+            last_updated: Local::now(),
+        };
+        let base = perf("SPY", 5.0);
+        let as_of = "2025-03-07".parse().unwrap();
+
+        let snaps = rank_snapshots(
+            &RsModel::preset("tv").unwrap(),
+            &[perf("Utilities", 2.0), perf("Energy", 10.0)],
+            &base,
+            as_of,
+        );
+
+        assert_eq!(snaps[0].ticker, "Energy");
+        assert_eq!(snaps[0].rank, 1);
+        assert_eq!(snaps[1].ticker, "Utilities");
+        assert_eq!(snaps[1].rank, 2);
+        assert!(snaps[0].rs > 1.0 && snaps[1].rs < 1.0);
+    }
+}
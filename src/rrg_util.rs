@@ -1,21 +1,32 @@
+use crate::api_error::ApiError;
 use crate::config::APP_CONFIG;
 use crate::html_error::HtmlError;
+use crate::rs::benchmark_for;
 use crate::store::Store;
+use crate::symbol;
 use crate::yf::{Candle, YFinance};
-use crate::{etf_map, fetch_candles};
+use crate::{etf_map, fetch_candles, watchlist};
 use anyhow::Context;
 use askama::Template;
-use axum::response::{Html, IntoResponse};
+use axum::body::{Body, Bytes};
+use axum::http::header::CONTENT_TYPE;
+use axum::response::{Html, IntoResponse, Response};
 use axum::{
     Extension, Json,
     extract::{Path, Query},
 };
-use chrono::Datelike;
+use chrono::{Datelike, Days, NaiveDate};
+use futures::StreamExt;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, LazyLock};
-use tracing::trace;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::{Arc, RwLock};
+use tracing::{trace, warn};
 
-static YF: LazyLock<YFinance> = LazyLock::new(YFinance::new);
+const RRG_BATCH_CONCURRENCY: usize = 8;
+/// Cap on snapshots per frames request, about two years of daily frames.
+const MAX_RRG_FRAMES: usize = 520;
 
 /// What the RRG page should plot. The two modes are mutually exclusive and each
 /// carries exactly the data it needs, so neither "both" nor "neither" can be
@@ -28,8 +39,34 @@ pub enum RrgMode {
     Tickers(Vec<String>),
 }
 
+/// Tickers the RRG plots instead of sector/industry ETFs, switchable at
+/// runtime through `PUT /api/rrg/tickers`. Servers without one always plot
+/// sectors.
+#[derive(Default)]
+pub struct RrgTickers(RwLock<Option<Vec<String>>>);
+
+impl RrgTickers {
+    pub fn new(tickers: Option<Vec<String>>) -> Self {
+        let rrg = RrgTickers::default();
+        rrg.set(tickers);
+        rrg
+    }
+
+    fn get(&self) -> Option<Vec<String>> {
+        self.0.read().expect("lock poison").clone()
+    }
+
+    /// `None` or no tickers switches back to sector rotation.
+    fn set(&self, tickers: Option<Vec<String>>) {
+        *self.0.write().expect("lock poison") = tickers
+            .map(|tickers| batch_tickers(&tickers))
+            .filter(|tickers| !tickers.is_empty());
+    }
+}
+
 pub async fn rrg_home(
-    mode: Option<Extension<Arc<RrgMode>>>,
+    Extension(store): Extension<Arc<Store>>,
+    mode: Option<Extension<Arc<RrgTickers>>>,
 ) -> Result<impl IntoResponse, HtmlError> {
     #[derive(Template)]
     #[template(path = "rrg.html")]
@@ -38,18 +75,141 @@ pub async fn rrg_home(
         mode: RrgMode,
     }
 
-    let mode = mode
-        .map(|Extension(m)| (*m).clone())
-        .unwrap_or_else(|| RrgMode::Sectors(etf_map::tv_mapping()));
-
     let home = Home {
         benchmark: APP_CONFIG.base_ticker.to_uppercase(),
-        mode,
+        mode: current_mode(&store, mode).await?,
     };
 
     Ok(Html(home.render()?))
 }
 
+async fn current_mode(
+    store: &Store,
+    mode: Option<Extension<Arc<RrgTickers>>>,
+) -> anyhow::Result<RrgMode> {
+    // Sector mappings are editable at runtime, so always read the current ones.
+    Ok(match mode.and_then(|Extension(rrg)| rrg.get()) {
+        Some(tickers) => RrgMode::Tickers(tickers),
+        None => RrgMode::Sectors(etf_map::load_mapping(store).await?),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RrgTickersRequest {
+    #[serde(default)]
+    tickers: Option<Vec<String>>,
+    /// Plot this stored watchlist; takes precedence over `tickers`.
+    #[serde(default)]
+    watchlist: Option<String>,
+}
+
+/// PUT /api/rrg/tickers with `{"tickers": [...]}` or `{"watchlist": "name"}`,
+/// or `{}` to go back to sector rotation. Returns the tickers now plotted.
+pub async fn set_tickers_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(rrg): Extension<Arc<RrgTickers>>,
+    Json(req): Json<RrgTickersRequest>,
+) -> Result<Json<Option<Vec<String>>>, ApiError> {
+    let tickers = match &req.watchlist {
+        Some(name) => Some(watchlist::watchlist_tickers(&store, name, "").await?),
+        None => req.tickers,
+    };
+    rrg.set(tickers);
+    Ok(Json(rrg.get()))
+}
+
+impl RrgMode {
+    /// `(name, ticker)` of everything the page can plot, each ticker once.
+    fn plotted(&self) -> Vec<(String, String)> {
+        match self {
+            RrgMode::Sectors(sectors) => sectors
+                .iter()
+                .flat_map(|sec| {
+                    std::iter::once((sec.sector.clone(), sec.sector_etf.clone())).chain(
+                        sec.industries
+                            .iter()
+                            .map(|ind| (ind.name.clone(), ind.etf.clone())),
+                    )
+                })
+                .unique_by(|(_, ticker)| symbol::canonical(ticker))
+                .collect(),
+            RrgMode::Tickers(tickers) => tickers.iter().map(|t| (t.clone(), t.clone())).collect(),
+        }
+    }
+}
+
+/// How RS-Ratio and RS-Momentum are derived from raw RS.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    /// Ratio to an expanding SMA, the classic JdK approximation.
+    #[default]
+    Sma,
+    /// Ratio to an EMA; reacts faster and is less biased at the start.
+    Ema,
+    /// 100 + rolling z-score, so volatile and quiet tickers share a scale.
+    Zscore,
+}
+
+/// Position on the RRG, clockwise from the top right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Quadrant {
+    Leading,
+    Weakening,
+    Lagging,
+    Improving,
+}
+
+impl Quadrant {
+    pub fn of(rs_ratio: f64, rs_momentum: f64) -> Quadrant {
+        match (rs_ratio >= 100.0, rs_momentum >= 100.0) {
+            (true, true) => Quadrant::Leading,
+            (true, false) => Quadrant::Weakening,
+            (false, false) => Quadrant::Lagging,
+            (false, true) => Quadrant::Improving,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuadrantTransition {
+    pub date: String,
+    pub from: Quadrant,
+    pub to: Quadrant,
+}
+
+/// Direction and length of the move between two RRG points. The heading is
+/// in degrees counter-clockwise from the RS-Ratio axis: 45° is heading into
+/// Leading, 135° into Improving, 225° into Lagging and 315° into Weakening.
+fn heading_and_speed(from: (f64, f64), to: (f64, f64)) -> (f64, f64) {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    (dy.atan2(dx).to_degrees().rem_euclid(360.0), dx.hypot(dy))
+}
+
+/// Every change of quadrant between consecutive points, oldest first.
+fn transitions(
+    dates: &[chrono::NaiveDate],
+    rs_ratio: &[f64],
+    rs_momentum: &[f64],
+) -> Vec<QuadrantTransition> {
+    let quadrants = rs_ratio
+        .iter()
+        .zip(rs_momentum)
+        .map(|(r, m)| Quadrant::of(*r, *m))
+        .collect_vec();
+    quadrants
+        .iter()
+        .tuple_windows()
+        .zip(dates.iter().skip(1))
+        .filter(|((from, to), _)| from != to)
+        .map(|((from, to), date)| QuadrantTransition {
+            date: date.format("%Y-%m-%d").to_string(),
+            from: *from,
+            to: *to,
+        })
+        .collect()
+}
+
 // ── Query params & response types ───────────────────────────────────────────
 
 #[derive(Debug, Deserialize)]
@@ -68,6 +228,14 @@ pub struct RrgQuery {
     /// Defaults to 10 weeks (the JdK standard).
     #[serde(default = "default_period_weeks")]
     period_weeks: usize,
+
+    /// End the RRG at the last period closing on or before this date instead
+    /// of the latest one. The tail and history end there too.
+    as_of: Option<NaiveDate>,
+
+    /// "sma" (default), "ema" or "zscore".
+    #[serde(default)]
+    normalization: Normalization,
 }
 
 fn default_period_weeks() -> usize {
@@ -78,8 +246,10 @@ fn default_period_weeks() -> usize {
 
 #[derive(Serialize)]
 struct TailPoint {
+    date: String,
     rs_ratio: f64,
     rs_momentum: f64,
+    quadrant: Quadrant,
 }
 
 #[derive(Serialize)]
@@ -93,42 +263,571 @@ pub struct RrgResponse {
     ticker: String,
     rs_ratio: f64,
     rs_momentum: f64,
+    quadrant: Quadrant,
+    /// Direction of the last move in degrees (see [`heading_and_speed`]);
+    /// `None` without a previous point.
+    heading: Option<f64>,
+    /// Length of the last move in RRG units.
+    speed: Option<f64>,
     tail: Vec<TailPoint>,
+    /// Quadrant changes across the tail and current point, oldest first.
+    transitions: Vec<QuadrantTransition>,
     rs_history: Vec<HistoryPoint>,
 }
 // ── Axum handler ─────────────────────────────────────────────────────────────
 
 /// GET /api/rrg/:ticker?timeframe=weekly&tail=12&history=52
 pub async fn rrg_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(yf): Extension<Arc<YFinance>>,
     Path(ticker): Path<String>,
     Query(params): Query<RrgQuery>,
-) -> Result<Json<RrgResponse>, HtmlError> {
+) -> Result<Json<RrgResponse>, ApiError> {
     trace!("Ticker: {ticker}, params: {params:?}");
-    let store = Store::load_store().await?;
-    let etf_candles = fetch_candles(&store, &YF, &ticker).await?;
-    let bmk_candles = fetch_candles(&store, &YF, &APP_CONFIG.base_ticker).await?;
-
-    if etf_candles.is_empty() || bmk_candles.is_empty() {
-        return Err(anyhow::anyhow!(
-            "{}/{} candles fetched",
-            etf_candles.len(),
-            bmk_candles.len(),
-        )
-        .into());
-    }
+    let etf_candles = fetch_candles(&store, &yf, &ticker).await?;
+    let benchmarks = BenchmarkPeriods::fetch(
+        &store,
+        &yf,
+        std::slice::from_ref(&ticker),
+        &params.timeframe,
+    )
+    .await?;
 
-    let response = compute_rrg(
+    Ok(Json(rrg_for(
         &ticker,
         &etf_candles,
-        &bmk_candles,
+        benchmarks.of(&ticker),
+        &params,
+    )?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RrgBatchRequest {
+    tickers: Vec<String>,
+    #[serde(flatten)]
+    params: RrgQuery,
+    /// Stream one NDJSON row per ticker as soon as it's ready instead of a
+    /// single JSON array in request order.
+    #[serde(default)]
+    stream: bool,
+}
+
+#[derive(Serialize)]
+pub struct RrgBatchRow {
+    ticker: String,
+    rrg: Option<RrgResponse>,
+    error: Option<String>,
+}
+
+/// POST /api/rrg/batch
+///
+/// `{"tickers": ["XLK", "SMH"], "timeframe": "weekly", "tail": 12, "history": 52}`
+///
+/// Same payload per ticker as [`rrg_handler`], but each benchmark is fetched
+/// and resampled once and tickers are computed concurrently. A ticker that
+/// fails gets an `error` row rather than failing the whole batch.
+pub async fn rrg_batch_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(yf): Extension<Arc<YFinance>>,
+    Json(request): Json<RrgBatchRequest>,
+) -> Result<Response, ApiError> {
+    let RrgBatchRequest {
+        tickers,
+        params,
+        stream,
+    } = request;
+    trace!("Batch of {} tickers, params: {params:?}", tickers.len());
+    let tickers = batch_tickers(&tickers);
+    let benchmarks =
+        Arc::new(BenchmarkPeriods::fetch(&store, &yf, &tickers, &params.timeframe).await?);
+    let params = Arc::new(params);
+
+    let rows = futures::stream::iter(tickers).map(move |ticker| {
+        let store = Arc::clone(&store);
+        let yf = Arc::clone(&yf);
+        let benchmarks = Arc::clone(&benchmarks);
+        let params = Arc::clone(&params);
+        async move {
+            let bmk_periods = benchmarks.of(&ticker);
+            batch_row(&store, &yf, bmk_periods, &params, ticker).await
+        }
+    });
+
+    if !stream {
+        let rows = rows
+            .buffered(RRG_BATCH_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await;
+        return Ok(Json(rows).into_response());
+    }
+
+    let lines = rows.buffer_unordered(RRG_BATCH_CONCURRENCY).map(|row| {
+        let line = serde_json::to_string(&row).unwrap_or_else(|err| {
+            serde_json::json!({ "ticker": row.ticker, "rrg": null, "error": err.to_string() })
+                .to_string()
+        });
+        Ok::<Bytes, Infallible>(Bytes::from(line + "\n"))
+    });
+    Ok((
+        [(CONTENT_TYPE, "application/x-ndjson; charset=utf-8")],
+        Body::from_stream(lines),
+    )
+        .into_response())
+}
+
+async fn batch_row(
+    store: &Store,
+    yf: &YFinance,
+    bmk_periods: &[PeriodClose],
+    params: &RrgQuery,
+    ticker: String,
+) -> RrgBatchRow {
+    let result = match fetch_candles(store, yf, &ticker).await {
+        Ok(candles) => rrg_for(&ticker, &candles, bmk_periods, params),
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(rrg) => RrgBatchRow {
+            ticker,
+            rrg: Some(rrg),
+            error: None,
+        },
+        Err(err) => {
+            warn!("RRG batch failed for {ticker}: {err:#}");
+            RrgBatchRow {
+                ticker,
+                rrg: None,
+                error: Some(format!("{err:#}")),
+            }
+        }
+    }
+}
+
+/// Resampled closes of every benchmark the tickers are measured against,
+/// fetched once per [`benchmark_for`].
+struct BenchmarkPeriods(HashMap<String, Vec<PeriodClose>>);
+
+impl BenchmarkPeriods {
+    async fn fetch(
+        store: &Store,
+        yf: &YFinance,
+        tickers: &[String],
+        timeframe: &str,
+    ) -> anyhow::Result<Self> {
+        let mut benchmarks = HashMap::new();
+        let wanted = tickers.iter().map(|ticker| benchmark_for(ticker)).unique();
+        for benchmark in wanted.collect_vec() {
+            let candles = fetch_candles(store, yf, &benchmark).await?;
+            if candles.is_empty() {
+                anyhow::bail!("No candles fetched for {benchmark}");
+            }
+            benchmarks.insert(benchmark, resample(&candles, timeframe));
+        }
+        Ok(BenchmarkPeriods(benchmarks))
+    }
+
+    /// Periods of the benchmark of `ticker`, which must be one it was fetched for.
+    fn of(&self, ticker: &str) -> &[PeriodClose] {
+        &self.0[&benchmark_for(ticker)]
+    }
+
+    /// Every period date of any benchmark, in order.
+    fn dates(&self) -> Vec<NaiveDate> {
+        self.0
+            .values()
+            .flatten()
+            .map(|p| p.date)
+            .sorted()
+            .dedup()
+            .collect()
+    }
+}
+
+fn rrg_for(
+    ticker: &str,
+    etf_candles: &[Candle],
+    bmk_periods: &[PeriodClose],
+    params: &RrgQuery,
+) -> anyhow::Result<RrgResponse> {
+    let mut series = series_for(ticker, etf_candles, bmk_periods, params)?;
+    if let Some(as_of) = params.as_of {
+        series = series.until(as_of);
+        if series.dates.is_empty() {
+            anyhow::bail!("No RRG data for {ticker} on or before {as_of}");
+        }
+    }
+
+    compute_rrg(ticker, series, params.tail, params.history).context("Failed to compute rrg")
+}
+
+fn series_for(
+    ticker: &str,
+    etf_candles: &[Candle],
+    bmk_periods: &[PeriodClose],
+    params: &RrgQuery,
+) -> anyhow::Result<RrgSeries> {
+    if etf_candles.is_empty() {
+        anyhow::bail!("No candles fetched for {ticker}");
+    }
+    series_from_periods(
+        &resample(etf_candles, &params.timeframe),
+        bmk_periods,
         &params.timeframe,
-        params.tail,
-        params.history,
         params.period_weeks.clamp(5, 26),
+        params.normalization,
     )
-    .context("Failed to compute rrg")?;
+    .with_context(|| format!("Not enough aligned history for {ticker}"))
+}
 
-    Ok(Json(response))
+/// Deduplicated, canonical tickers of a batch request.
+fn batch_tickers(tickers: &[String]) -> Vec<String> {
+    tickers
+        .iter()
+        .map(|t| symbol::canonical(t))
+        .filter(|t| !t.is_empty())
+        .unique()
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RrgFramesRequest {
+    tickers: Vec<String>,
+    /// `as_of` is the last frame, the latest period when unset.
+    #[serde(flatten)]
+    params: RrgQuery,
+    /// First frame; defaults to 26 weeks before the last one.
+    from: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+pub struct RrgFrame {
+    date: String,
+    rrg: Vec<RrgResponse>,
+}
+
+#[derive(Serialize)]
+pub struct RrgFramesResponse {
+    frames: Vec<RrgFrame>,
+    /// Tickers that couldn't be computed at all.
+    errors: Vec<RrgBatchRow>,
+}
+
+/// POST /api/rrg/frames
+///
+/// `{"tickers": ["XLK", "SMH"], "timeframe": "weekly", "tail": 8, "history": 0, "from": "2025-01-03"}`
+///
+/// One full RRG snapshot per benchmark period between `from` and `as_of`, as
+/// if each had been requested with that date as `as_of`. Each ticker's series
+/// is computed once and cut at every frame date.
+pub async fn rrg_frames_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(yf): Extension<Arc<YFinance>>,
+    Json(request): Json<RrgFramesRequest>,
+) -> Result<Json<RrgFramesResponse>, ApiError> {
+    let RrgFramesRequest {
+        tickers,
+        params,
+        from,
+    } = request;
+    trace!(
+        "Frames of {} tickers from {from:?}, params: {params:?}",
+        tickers.len()
+    );
+    let tickers = batch_tickers(&tickers);
+    let benchmarks = BenchmarkPeriods::fetch(&store, &yf, &tickers, &params.timeframe).await?;
+
+    let dates = benchmarks.dates();
+    let to = params
+        .as_of
+        .or_else(|| dates.last().copied())
+        .context("No benchmark periods")?;
+    let from = from.unwrap_or(to - Days::new(26 * 7));
+    let dates = dates
+        .into_iter()
+        .filter(|date| (from..=to).contains(date))
+        .collect_vec();
+    if dates.is_empty() {
+        return Err(ApiError::bad_request(format!(
+            "No {} periods between {from} and {to}",
+            params.timeframe
+        )));
+    }
+    if dates.len() > MAX_RRG_FRAMES {
+        return Err(ApiError::bad_request(format!(
+            "{} frames between {from} and {to}, at most {MAX_RRG_FRAMES} are allowed",
+            dates.len()
+        )));
+    }
+
+    let (yf, benchmarks, params) = (&*yf, &benchmarks, &params);
+    let results = futures::stream::iter(tickers)
+        .map(|ticker| {
+            let store = Arc::clone(&store);
+            async move {
+                let result = match fetch_candles(&store, yf, &ticker).await {
+                    Ok(candles) => series_for(&ticker, &candles, benchmarks.of(&ticker), params),
+                    Err(err) => Err(err),
+                };
+                (ticker, result)
+            }
+        })
+        .buffered(RRG_BATCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut series = Vec::new();
+    let mut errors = Vec::new();
+    for (ticker, result) in results {
+        match result {
+            Ok(s) => series.push((ticker, s)),
+            Err(err) => {
+                warn!("RRG frames failed for {ticker}: {err:#}");
+                errors.push(RrgBatchRow {
+                    ticker,
+                    rrg: None,
+                    error: Some(format!("{err:#}")),
+                });
+            }
+        }
+    }
+
+    let frames = dates
+        .into_iter()
+        .map(|date| RrgFrame {
+            date: date.format("%Y-%m-%d").to_string(),
+            rrg: series
+                .iter()
+                .filter_map(|(ticker, s)| {
+                    compute_rrg(ticker, s.until(date), params.tail, params.history)
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(Json(RrgFramesResponse { frames, errors }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsensusRequest {
+    tickers: Vec<String>,
+    #[serde(default = "default_period_weeks")]
+    period_weeks: usize,
+    as_of: Option<NaiveDate>,
+    #[serde(default)]
+    normalization: Normalization,
+}
+
+#[derive(Serialize)]
+pub struct ConsensusRow {
+    ticker: String,
+    daily: Option<RrgResponse>,
+    weekly: Option<RrgResponse>,
+    /// See [`alignment_score`]; `None` unless both timeframes are known.
+    score: Option<i32>,
+    /// Both timeframes in the same quadrant.
+    aligned: bool,
+    error: Option<String>,
+}
+
+/// How strongly the two timeframes agree on strength, from 4 (Leading on
+/// both) down to -4 (Lagging on both). Improving counts for and Weakening
+/// against, so Leading weekly while Improving daily (3) ranks above Leading
+/// weekly while Weakening daily (1).
+fn alignment_score(daily: Quadrant, weekly: Quadrant) -> i32 {
+    let points = |q| match q {
+        Quadrant::Leading => 2,
+        Quadrant::Improving => 1,
+        Quadrant::Weakening => -1,
+        Quadrant::Lagging => -2,
+    };
+    points(daily) + points(weekly)
+}
+
+/// POST /api/rrg/consensus
+///
+/// `{"tickers": ["XLK", "SMH"], "period_weeks": 10}`
+///
+/// The current daily and weekly RRG point of every ticker with their
+/// [`alignment_score`], best aligned first. Candles are fetched once per
+/// ticker and each benchmark once per timeframe.
+pub async fn consensus_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(yf): Extension<Arc<YFinance>>,
+    Json(request): Json<ConsensusRequest>,
+) -> Result<Json<Vec<ConsensusRow>>, ApiError> {
+    let timeframe = |timeframe: &str| RrgQuery {
+        timeframe: timeframe.to_string(),
+        tail: 0,
+        history: 0,
+        period_weeks: request.period_weeks,
+        as_of: request.as_of,
+        normalization: request.normalization,
+    };
+    let (daily, weekly) = (timeframe("daily"), timeframe("weekly"));
+    let tickers = batch_tickers(&request.tickers);
+    let daily_bmk = BenchmarkPeriods::fetch(&store, &yf, &tickers, &daily.timeframe).await?;
+    let weekly_bmk = BenchmarkPeriods::fetch(&store, &yf, &tickers, &weekly.timeframe).await?;
+
+    let (daily, weekly) = (&daily, &weekly);
+    let (yf, daily_bmk, weekly_bmk) = (&*yf, &daily_bmk, &weekly_bmk);
+    let mut rows = futures::stream::iter(tickers)
+        .map(|ticker| {
+            let store = Arc::clone(&store);
+            async move {
+                let candles = match fetch_candles(&store, yf, &ticker).await {
+                    Ok(candles) => candles,
+                    Err(err) => {
+                        warn!("RRG consensus failed for {ticker}: {err:#}");
+                        return ConsensusRow {
+                            ticker,
+                            daily: None,
+                            weekly: None,
+                            score: None,
+                            aligned: false,
+                            error: Some(format!("{err:#}")),
+                        };
+                    }
+                };
+                let daily = rrg_for(&ticker, &candles, daily_bmk.of(&ticker), daily);
+                let weekly = rrg_for(&ticker, &candles, weekly_bmk.of(&ticker), weekly);
+                let error = [&daily, &weekly]
+                    .into_iter()
+                    .filter_map(|r| r.as_ref().err())
+                    .map(|err| format!("{err:#}"))
+                    .next();
+                let (daily, weekly) = (daily.ok(), weekly.ok());
+                let quadrants = daily
+                    .as_ref()
+                    .zip(weekly.as_ref())
+                    .map(|(d, w)| (d.quadrant, w.quadrant));
+                ConsensusRow {
+                    ticker,
+                    daily,
+                    weekly,
+                    score: quadrants.map(|(d, w)| alignment_score(d, w)),
+                    aligned: quadrants.is_some_and(|(d, w)| d == w),
+                    error,
+                }
+            }
+        })
+        .buffered(RRG_BATCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let weekly_ratio = |row: &ConsensusRow| row.weekly.as_ref().map_or(f64::MIN, |w| w.rs_ratio);
+    rows.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| weekly_ratio(b).total_cmp(&weekly_ratio(a)))
+    });
+    Ok(Json(rows))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CrossingsQuery {
+    #[serde(default = "default_timeframe")]
+    timeframe: String,
+    /// Only crossings within this many most recent periods are listed.
+    #[serde(default = "default_crossing_periods")]
+    periods: usize,
+    #[serde(default = "default_period_weeks")]
+    period_weeks: usize,
+    #[serde(default)]
+    normalization: Normalization,
+}
+
+fn default_timeframe() -> String {
+    "weekly".to_string()
+}
+
+fn default_crossing_periods() -> usize {
+    4
+}
+
+#[derive(Serialize)]
+pub struct Crossing {
+    ticker: String,
+    name: String,
+    date: String,
+    from: Quadrant,
+    to: Quadrant,
+    /// 0 = the crossing happened on the latest period.
+    periods_ago: usize,
+    rs_ratio: f64,
+    rs_momentum: f64,
+    quadrant: Quadrant,
+}
+
+/// GET /api/rrg/crossings?timeframe=weekly&periods=4
+///
+/// Every plotted ticker whose latest move into Improving or Leading happened
+/// within the last `periods` periods, most recent first.
+pub async fn crossings_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(yf): Extension<Arc<YFinance>>,
+    mode: Option<Extension<Arc<RrgTickers>>>,
+    Query(params): Query<CrossingsQuery>,
+) -> Result<Json<Vec<Crossing>>, ApiError> {
+    let plotted = current_mode(&store, mode).await?.plotted();
+    let tickers = plotted
+        .iter()
+        .map(|(_, ticker)| ticker.clone())
+        .collect_vec();
+    let benchmarks = BenchmarkPeriods::fetch(&store, &yf, &tickers, &params.timeframe).await?;
+    let period_weeks = params.period_weeks.clamp(5, 26);
+
+    let mut crossings = Vec::new();
+    for (name, ticker) in plotted {
+        let candles = match fetch_candles(&store, &yf, &ticker).await {
+            Ok(candles) => candles,
+            Err(err) => {
+                warn!("Skipping {ticker} in RRG crossings: {err}");
+                continue;
+            }
+        };
+        let Some(series) = series_from_periods(
+            &resample(&candles, &params.timeframe),
+            benchmarks.of(&ticker),
+            &params.timeframe,
+            period_weeks,
+            params.normalization,
+        ) else {
+            continue;
+        };
+
+        let n = series.rs_ratio.len();
+        let start = n.saturating_sub(params.periods + 1);
+        let Some(transition) = transitions(
+            &series.dates[start..],
+            &series.rs_ratio[start..],
+            &series.rs_momentum[start..],
+        )
+        .into_iter()
+        .rfind(|t| matches!(t.to, Quadrant::Improving | Quadrant::Leading)) else {
+            continue;
+        };
+        let periods_ago = series
+            .dates
+            .iter()
+            .rev()
+            .position(|d| d.format("%Y-%m-%d").to_string() == transition.date)
+            .unwrap_or_default();
+        let (rs_ratio, rs_momentum) = (series.rs_ratio[n - 1], series.rs_momentum[n - 1]);
+        crossings.push(Crossing {
+            ticker: symbol::canonical(&ticker),
+            name,
+            date: transition.date,
+            from: transition.from,
+            to: transition.to,
+            periods_ago,
+            rs_ratio: r3(rs_ratio),
+            rs_momentum: r3(rs_momentum),
+            quadrant: Quadrant::of(rs_ratio, rs_momentum),
+        });
+    }
+    crossings.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.ticker.cmp(&b.ticker)));
+
+    Ok(Json(crossings))
 }
 
 // ── Core computation ─────────────────────────────────────────────────────────
@@ -185,6 +884,39 @@ fn sma(src: &[f64], period: usize) -> Vec<f64> {
         .collect()
 }
 
+/// Exponential Moving Average with `alpha = 2 / (period + 1)`, seeded with the
+/// first value. Returns a vec the same length as `src`.
+fn ema(src: &[f64], period: usize) -> Vec<f64> {
+    let alpha = 2.0 / (period as f64 + 1.0);
+    src.iter()
+        .scan(None, |prev: &mut Option<f64>, &v| {
+            let next = prev.map_or(v, |p| p + alpha * (v - p));
+            *prev = Some(next);
+            Some(next)
+        })
+        .collect()
+}
+
+/// Standard score of each value against the trailing `period` values
+/// (population standard deviation, expanding like [`sma`] at the start).
+/// 0 where the window has no spread.
+fn rolling_zscore(src: &[f64], period: usize) -> Vec<f64> {
+    src.iter()
+        .enumerate()
+        .map(|(i, v)| {
+            let window = &src[i.saturating_sub(period - 1)..=i];
+            let n = window.len() as f64;
+            let mean = window.iter().sum::<f64>() / n;
+            let std = (window.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / n).sqrt();
+            if std > f64::EPSILON * mean.abs().max(1.0) {
+                (v - mean) / std
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
 /// Round to 3 decimal places — keeps JSON tidy.
 #[inline]
 fn r3(v: f64) -> f64 {
@@ -216,6 +948,27 @@ fn align(etf: &[PeriodClose], bmk: &[PeriodClose]) -> (Vec<f64>, Vec<chrono::Nai
     (etf_closes, dates, bmk_closes)
 }
 
+/// RS-Ratio and RS-Momentum for every aligned period, oldest → newest.
+pub(crate) struct RrgSeries {
+    pub dates: Vec<chrono::NaiveDate>,
+    pub rs_ratio: Vec<f64>,
+    pub rs_momentum: Vec<f64>,
+}
+
+impl RrgSeries {
+    /// The series as it stood at the last period closing on or before `as_of`.
+    /// Every step only looks backwards, so this is the same as computing it
+    /// from candles that end on that date.
+    fn until(&self, as_of: NaiveDate) -> RrgSeries {
+        let end = self.dates.partition_point(|date| *date <= as_of);
+        RrgSeries {
+            dates: self.dates[..end].to_vec(),
+            rs_ratio: self.rs_ratio[..end].to_vec(),
+            rs_momentum: self.rs_momentum[..end].to_vec(),
+        }
+    }
+}
+
 /// JdK RS-Ratio / RS-Momentum computation.
 ///
 /// Formula (Julius de Kempenaer, "Relative Rotation Graphs"):
@@ -232,33 +985,47 @@ fn align(etf: &[PeriodClose], bmk: &[PeriodClose]) -> (Vec<f64>, Vec<chrono::Nai
 ///   4.  rs_momentum[i]= (rs_ratio[i]  / SMA(rs_ratio,  10)[i]) × 100
 ///                       — rate-of-change of RS-Ratio, also centred at 100
 ///
-/// `tail_len`    — how many historical (rs_ratio, rs_momentum) pairs to return
-///                 in the "tail" array (oldest → newest, excludes current point)
-/// `history_len` — how many (date, rs_ratio) pairs to return for the bottom chart
+/// That is [`Normalization::Sma`]; the other methods swap the averages in
+/// steps 2–4. Every average only looks backwards, so truncating the candles
+/// at a date gives the values as they were known on that date.
 #[allow(clippy::doc_overindented_list_items)]
-fn compute_rrg(
-    ticker: &str,
+pub(crate) fn rrg_series(
     etf_candles: &[Candle],
     bmk_candles: &[Candle],
     timeframe: &str,
-    tail_len: usize,
-    history_len: usize,
     period_weeks: usize,
-) -> Option<RrgResponse> {
+    normalization: Normalization,
+) -> Option<RrgSeries> {
     // ── 1. Resample ──────────────────────────────────────────────────────────
-    let etf_periods = match timeframe {
-        "daily" => to_daily(etf_candles),
-        _ => to_weekly(etf_candles), // "weekly" is the default
-    };
-    let bmk_periods = match timeframe {
-        "daily" => to_daily(bmk_candles),
-        _ => to_weekly(bmk_candles),
-    };
+    series_from_periods(
+        &resample(etf_candles, timeframe),
+        &resample(bmk_candles, timeframe),
+        timeframe,
+        period_weeks,
+        normalization,
+    )
+}
+
+/// Closes per period of `timeframe`.
+fn resample(candles: &[Candle], timeframe: &str) -> Vec<PeriodClose> {
+    match timeframe {
+        "daily" => to_daily(candles),
+        _ => to_weekly(candles), // "weekly" is the default
+    }
+}
 
+/// [`rrg_series`] from already resampled closes, so the benchmark can be
+/// resampled once and shared across tickers.
+fn series_from_periods(
+    etf_periods: &[PeriodClose],
+    bmk_periods: &[PeriodClose],
+    timeframe: &str,
+    period_weeks: usize,
+    normalization: Normalization,
+) -> Option<RrgSeries> {
     // ── 2. Align by date ─────────────────────────────────────────────────────
-    let (etf_close, dates, bmk_close) = align(&etf_periods, &bmk_periods);
-    let n = etf_close.len();
-    if n < 20 {
+    let (etf_close, dates, bmk_close) = align(etf_periods, bmk_periods);
+    if etf_close.len() < 20 {
         return None; // not enough data to compute meaningful SMAs
     }
 
@@ -269,29 +1036,70 @@ fn compute_rrg(
         .map(|(e, b)| e / b)
         .collect();
 
-    // ── 4. RS-Ratio: smooth RS, then normalise against its own SMA ───────────
+    // ── 4–5. RS-Ratio and RS-Momentum ────────────────────────────────────────
     let sma_period = match timeframe {
         "daily" => period_weeks * 5,
         _ => period_weeks,
     };
+    let (rs_ratio, rs_momentum) = normalize(&rs, sma_period, normalization);
 
-    let rs_smooth = sma(&rs, sma_period);
-    let rs_smooth_sma = sma(&rs_smooth, sma_period);
+    Some(RrgSeries {
+        dates,
+        rs_ratio,
+        rs_momentum,
+    })
+}
 
-    let rs_ratio: Vec<f64> = rs_smooth
-        .iter()
-        .zip(rs_smooth_sma.iter())
-        .map(|(s, m)| if *m != 0.0 { (s / m) * 100.0 } else { 100.0 })
-        .collect();
+/// RS-Ratio and RS-Momentum from raw RS (steps 2–4 of [`rrg_series`]).
+fn normalize(rs: &[f64], period: usize, normalization: Normalization) -> (Vec<f64>, Vec<f64>) {
+    // Value over its own moving average, centred at 100.
+    let ratio_to = |src: &[f64], avg: &[f64]| -> Vec<f64> {
+        src.iter()
+            .zip(avg)
+            .map(|(v, m)| if *m != 0.0 { (v / m) * 100.0 } else { 100.0 })
+            .collect()
+    };
 
-    // ── 5. RS-Momentum: normalise RS-Ratio against its own SMA ───────────────
-    let rs_ratio_sma = sma(&rs_ratio, sma_period);
+    match normalization {
+        Normalization::Sma => {
+            let rs_smooth = sma(rs, period);
+            let rs_ratio = ratio_to(&rs_smooth, &sma(&rs_smooth, period));
+            let rs_momentum = ratio_to(&rs_ratio, &sma(&rs_ratio, period));
+            (rs_ratio, rs_momentum)
+        }
+        Normalization::Ema => {
+            let rs_smooth = ema(rs, period);
+            let rs_ratio = ratio_to(&rs_smooth, &ema(&rs_smooth, period));
+            let rs_momentum = ratio_to(&rs_ratio, &ema(&rs_ratio, period));
+            (rs_ratio, rs_momentum)
+        }
+        Normalization::Zscore => {
+            let shift = |z: Vec<f64>| z.into_iter().map(|z| 100.0 + z).collect::<Vec<_>>();
+            let rs_ratio = shift(rolling_zscore(&sma(rs, period), period));
+            let rs_momentum = shift(rolling_zscore(&rs_ratio, period));
+            (rs_ratio, rs_momentum)
+        }
+    }
+}
 
-    let rs_momentum: Vec<f64> = rs_ratio
-        .iter()
-        .zip(rs_ratio_sma.iter())
-        .map(|(r, m)| if *m != 0.0 { (r / m) * 100.0 } else { 100.0 })
-        .collect();
+/// Current RRG point plus its tail and RS-Ratio history (see [`rrg_series`]).
+///
+/// `tail_len`    — how many historical (rs_ratio, rs_momentum) pairs to return
+///                 in the "tail" array (oldest → newest, excludes current point)
+/// `history_len` — how many (date, rs_ratio) pairs to return for the bottom chart
+#[allow(clippy::doc_overindented_list_items)]
+fn compute_rrg(
+    ticker: &str,
+    series: RrgSeries,
+    tail_len: usize,
+    history_len: usize,
+) -> Option<RrgResponse> {
+    let RrgSeries {
+        dates,
+        rs_ratio,
+        rs_momentum,
+    } = series;
+    let n = rs_ratio.len();
 
     // ── 6. Current values (last data point) ──────────────────────────────────
     let current_rs_ratio = r3(*rs_ratio.last()?);
@@ -306,10 +1114,27 @@ fn compute_rrg(
     let tail_start = n.saturating_sub(tail_len + 1);
     let tail: Vec<TailPoint> = (tail_start..n.saturating_sub(1))
         .map(|i| TailPoint {
+            date: dates[i].format("%Y-%m-%d").to_string(),
             rs_ratio: r3(rs_ratio[i]),
             rs_momentum: r3(rs_momentum[i]),
+            quadrant: Quadrant::of(rs_ratio[i], rs_momentum[i]),
         })
         .collect();
+    let (heading, speed) = match n {
+        2.. => {
+            let (heading, speed) = heading_and_speed(
+                (rs_ratio[n - 2], rs_momentum[n - 2]),
+                (rs_ratio[n - 1], rs_momentum[n - 1]),
+            );
+            (Some(r3(heading)), Some(r3(speed)))
+        }
+        _ => (None, None),
+    };
+    let transitions = transitions(
+        &dates[tail_start..],
+        &rs_ratio[tail_start..],
+        &rs_momentum[tail_start..],
+    );
 
     // ── 8. RS history for the bottom chart ───────────────────────────────────
     let hist_start = n.saturating_sub(history_len);
@@ -321,10 +1146,143 @@ fn compute_rrg(
         .collect();
 
     Some(RrgResponse {
-        ticker: ticker.to_uppercase(),
+        ticker: symbol::canonical(ticker),
         rs_ratio: current_rs_ratio,
         rs_momentum: current_rs_momentum,
+        quadrant: Quadrant::of(current_rs_ratio, current_rs_momentum),
+        heading,
+        speed,
         tail,
+        transitions,
         rs_history,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn classifies_quadrants_and_heading() {
+        assert_eq!(Quadrant::of(101.0, 100.0), Quadrant::Leading);
+        assert_eq!(Quadrant::of(101.0, 99.0), Quadrant::Weakening);
+        assert_eq!(Quadrant::of(99.0, 99.0), Quadrant::Lagging);
+        assert_eq!(Quadrant::of(99.0, 101.0), Quadrant::Improving);
+
+        let (heading, speed) = heading_and_speed((99.0, 99.0), (96.0, 103.0));
+        assert!((heading - 126.869_897_645).abs() < 1e-6);
+        assert!((speed - 5.0).abs() < 1e-12);
+    }
+
+    fn assert_close(actual: &[f64], expected: &[f64]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn moving_averages_match_hand_computed_series() {
+        assert_close(&sma(&[1.0, 2.0, 3.0, 4.0], 2), &[1.0, 1.5, 2.5, 3.5]);
+        // alpha = 2 / (3 + 1) = 0.5
+        assert_close(&ema(&[1.0, 2.0, 3.0, 4.0], 3), &[1.0, 1.5, 2.25, 3.125]);
+        // Last window [2, 3, 5]: mean 10/3, population std sqrt(14/9).
+        assert_close(
+            &rolling_zscore(&[1.0, 2.0, 3.0, 5.0], 3),
+            &[0.0, 1.0, 1.224_745, 1.336_306],
+        );
+    }
+
+    #[test]
+    fn normalizations_centre_on_100() {
+        let flat = [1.5; 30];
+        for method in [
+            Normalization::Sma,
+            Normalization::Ema,
+            Normalization::Zscore,
+        ] {
+            let (rs_ratio, rs_momentum) = normalize(&flat, 5, method);
+            assert_close(&rs_ratio, &[100.0; 30]);
+            assert_close(&rs_momentum, &[100.0; 30]);
+        }
+
+        // Steadily outperforming: every method puts the latest point in Leading.
+        let rising = (0..30).map(|i| 1.0 + i as f64 * 0.01).collect_vec();
+        for method in [
+            Normalization::Sma,
+            Normalization::Ema,
+            Normalization::Zscore,
+        ] {
+            let (rs_ratio, _) = normalize(&rising, 5, method);
+            assert!(rs_ratio[29] > 100.0, "{method:?}");
+        }
+
+        // SMA: rs_smooth = [1, 1.5, 2.5], its SMA = [1, 1.25, 2].
+        let (rs_ratio, rs_momentum) = normalize(&[1.0, 2.0, 3.0], 2, Normalization::Sma);
+        assert_close(&rs_ratio, &[100.0, 120.0, 125.0]);
+        assert_close(
+            &rs_momentum,
+            &[100.0, 120.0 / 110.0 * 100.0, 125.0 / 122.5 * 100.0],
+        );
+    }
+
+    #[test]
+    fn scores_timeframe_alignment() {
+        use Quadrant::*;
+        assert_eq!(alignment_score(Leading, Leading), 4);
+        assert_eq!(alignment_score(Improving, Leading), 3);
+        assert_eq!(alignment_score(Weakening, Leading), 1);
+        assert_eq!(alignment_score(Improving, Weakening), 0);
+        assert_eq!(alignment_score(Lagging, Lagging), -4);
+    }
+
+    #[test]
+    fn until_keeps_periods_on_or_before_the_date() {
+        let series = RrgSeries {
+            dates: (1..=4)
+                .map(|d| NaiveDate::from_ymd_opt(2025, 3, d * 7).unwrap())
+                .collect(),
+            rs_ratio: vec![98.0, 99.0, 100.0, 101.0],
+            rs_momentum: vec![101.0, 100.0, 99.0, 98.0],
+        };
+
+        let cut = series.until(NaiveDate::from_ymd_opt(2025, 3, 20).unwrap());
+        assert_eq!(cut.dates.len(), 2);
+        assert_eq!(cut.rs_ratio, vec![98.0, 99.0]);
+        assert_eq!(cut.rs_momentum, vec![101.0, 100.0]);
+        assert!(
+            series
+                .until(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap())
+                .dates
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn detects_transitions_with_their_dates() {
+        let dates = (1..=4)
+            .map(|d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap())
+            .collect_vec();
+        let rs_ratio = [98.0, 99.0, 100.5, 101.0];
+        let rs_momentum = [99.0, 100.5, 101.0, 101.5];
+
+        let found = transitions(&dates, &rs_ratio, &rs_momentum);
+
+        assert_eq!(
+            found,
+            vec![
+                QuadrantTransition {
+                    date: "2025-03-02".to_string(),
+                    from: Quadrant::Lagging,
+                    to: Quadrant::Improving,
+                },
+                QuadrantTransition {
+                    date: "2025-03-03".to_string(),
+                    from: Quadrant::Improving,
+                    to: Quadrant::Leading,
+                },
+            ]
+        );
+    }
+}
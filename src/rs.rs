@@ -5,9 +5,11 @@ use tracing::{info, warn};
 
 use crate::config::APP_CONFIG;
 use crate::fetch_candles;
+use crate::groups::{GroupRanks, load_group_ranks};
+use crate::rs_model::RS_MODEL;
 use crate::store::Store;
-use crate::util::compute_rs_candles;
-use crate::yf::YFinance;
+use crate::symbol::Symbol;
+use crate::yf::{Candle, YFinance};
 use crate::{Stock, etf_map};
 
 pub type RsMap = HashMap<String, f64>;
@@ -26,49 +28,97 @@ pub async fn build_rs_maps(
 ) -> anyhow::Result<RsMaps> {
     let base_candles = fetch_candles(store, yf, &APP_CONFIG.base_ticker).await?;
     info!("Fetched {} baseline candles", base_candles.len());
+    let mut benchmarks = HashMap::from([(APP_CONFIG.base_ticker.clone(), base_candles)]);
 
-    let rs_fn = async |ticker| {
+    let rs_fn = async |ticker, base_candles: &[Candle]| {
         let candles = fetch_candles(store, yf, ticker)
             .await
             .with_context(|| format!("Failed to fetch candles for {ticker:?}"))?;
-        anyhow::Ok(compute_rs_candles(&candles, &base_candles))
+        anyhow::Ok(RS_MODEL.rs_candles(&candles, base_candles))
     };
 
     let mut sector_rs = HashMap::new();
     let mut industrie_rs = HashMap::new();
     let mut stock_rs = HashMap::new();
 
-    let mapping = etf_map::tv_mapping();
+    // Stocks first so their candles are stored before any group fallback reads them.
+    for st in stocks {
+        let benchmark = benchmark_for(&st.ticker);
+        if !benchmarks.contains_key(&benchmark) {
+            let candles = fetch_candles(store, yf, &benchmark).await?;
+            info!("Fetched {} candles of benchmark {benchmark}", candles.len());
+            benchmarks.insert(benchmark.clone(), candles);
+        }
+        let rs = rs_fn(&st.ticker, &benchmarks[&benchmark]).await?;
+        stock_rs.insert(st.ticker.clone(), round_rs(rs));
+    }
+    let base_candles = &benchmarks[&APP_CONFIG.base_ticker];
+
+    let mapping = etf_map::load_mapping(store).await?;
+    let mut group_ranks: Option<GroupRanks> = None;
 
     for sec in stocks.iter().map(|s| &s.sector).unique_by(|sec| &sec.name) {
-        let Some(sec) = mapping
+        let Some(mapped) = mapping
             .iter()
             .find(|s| s.sector.eq_ignore_ascii_case(&sec.name))
         else {
-            warn!("No ETF mapping found for Sector: {}", sec.name);
+            if group_ranks.is_none() {
+                group_ranks = Some(load_group_ranks(store).await?);
+            }
+            match group_ranks.as_ref().and_then(|g| g.sector(&sec.name)) {
+                Some(group) => {
+                    info!(
+                        "No ETF mapping for Sector: {}, using median RS of {} member stocks",
+                        sec.name, group.members
+                    );
+                    sector_rs.insert(sec.name.clone(), round_rs(group.median_rs));
+                }
+                None => warn!(
+                    "No ETF mapping or member stocks found for Sector: {}",
+                    sec.name
+                ),
+            }
             continue;
         };
 
-        sector_rs.insert(sec.sector.clone(), round_rs(rs_fn(&sec.sector_etf).await?));
+        sector_rs.insert(
+            mapped.sector.clone(),
+            round_rs(rs_fn(&mapped.sector_etf, base_candles).await?),
+        );
     }
     for ind in stocks
         .iter()
         .map(|s| &s.industry)
         .unique_by(|ind| &ind.name)
     {
-        let Some(ind) = mapping
+        let Some(mapped) = mapping
             .iter()
             .flat_map(|sec| &sec.industries)
             .find(|&i| i.name.eq_ignore_ascii_case(&ind.name))
         else {
-            warn!("No ETF mapping found for Industry: {}", ind.name);
+            if group_ranks.is_none() {
+                group_ranks = Some(load_group_ranks(store).await?);
+            }
+            match group_ranks.as_ref().and_then(|g| g.industry(&ind.name)) {
+                Some(group) => {
+                    info!(
+                        "No ETF mapping for Industry: {}, using median RS of {} member stocks",
+                        ind.name, group.members
+                    );
+                    industrie_rs.insert(ind.name.clone(), round_rs(group.median_rs));
+                }
+                None => warn!(
+                    "No ETF mapping or member stocks found for Industry: {}",
+                    ind.name
+                ),
+            }
             continue;
         };
 
-        industrie_rs.insert(ind.name.clone(), round_rs(rs_fn(&ind.etf).await?));
-    }
-    for st in stocks {
-        stock_rs.insert(st.ticker.clone(), round_rs(rs_fn(&st.ticker).await?));
+        industrie_rs.insert(
+            ind.name.clone(),
+            round_rs(rs_fn(&mapped.etf, base_candles).await?),
+        );
     }
 
     Ok(RsMaps {
@@ -78,6 +128,43 @@ pub async fn build_rs_maps(
     })
 }
 
+/// The ticker RS of `ticker` is measured against: its market's benchmark for
+/// non-US listings, `base_ticker` otherwise. Sector and industry ETFs are
+/// always measured against `base_ticker`.
+pub fn benchmark_for(ticker: &str) -> String {
+    let Some(market) = Symbol::parse(ticker)
+        .ok()
+        .and_then(|symbol| symbol.market())
+    else {
+        return APP_CONFIG.base_ticker.clone();
+    };
+    APP_CONFIG
+        .benchmarks
+        .get(market.code)
+        .cloned()
+        .unwrap_or_else(|| market.benchmark.to_string())
+}
+
+/// IBD-style 1–99 percentile rating of each raw RS value within `raw`.
+/// Ties share the same rating.
+pub fn rs_ratings(raw: &RsMap) -> HashMap<String, u8> {
+    let mut sorted = raw.values().copied().collect::<Vec<_>>();
+    sorted.sort_by(f64::total_cmp);
+
+    let n = sorted.len();
+    raw.iter()
+        .map(|(ticker, rs)| {
+            let below = sorted.partition_point(|v| v < rs);
+            let pct = if n > 1 {
+                below as f64 / (n - 1) as f64
+            } else {
+                1.0
+            };
+            (ticker.clone(), (1.0 + pct * 98.0).round() as u8)
+        })
+        .collect()
+}
+
 fn round_rs(rs: f64) -> f64 {
     (rs * 100.0).round() / 100.0
 }
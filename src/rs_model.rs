@@ -0,0 +1,251 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+use crate::Performance;
+use crate::api_error::ValidationError;
+use crate::config::{APP_CONFIG, RsModelSpec};
+use crate::yf::Candle;
+
+/// Daily bars per month of lookback.
+const BARS_PER_MONTH: usize = 21;
+
+/// Names accepted for `rs.model` / `rs.compare` in `config.toml`.
+pub const PRESETS: &[&str] = &["ibd", "momentum", "equal", "tv"];
+
+/// Model used for every RS value and rating.
+pub static RS_MODEL: LazyLock<RsModel> = LazyLock::new(|| {
+    RsModel::resolve(&APP_CONFIG.rs.model)
+        .unwrap_or_else(|e| panic!("Invalid rs.model in config: {e:#}"))
+});
+
+/// Extra models reported next to [`RS_MODEL`] in the stock metrics stream.
+pub static RS_COMPARE_MODELS: LazyLock<Vec<RsModel>> = LazyLock::new(|| {
+    APP_CONFIG
+        .rs
+        .compare
+        .iter()
+        .map(RsModel::resolve)
+        .collect::<anyhow::Result<_>>()
+        .unwrap_or_else(|e| panic!("Invalid rs.compare in config: {e:#}"))
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RsPeriod {
+    pub months: u32,
+    /// Relative weight; weights don't need to add up to one.
+    pub weight: f64,
+}
+
+/// Weighted multi-period return compared against the benchmark's. A value
+/// above 1.0 means the ticker outperformed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RsModel {
+    pub name: String,
+    pub periods: Vec<RsPeriod>,
+}
+
+impl RsModel {
+    pub fn preset(name: &str) -> Option<RsModel> {
+        let periods: &[(u32, f64)] = match name.to_lowercase().as_str() {
+            // IBD: latest quarter double weighted against the three before it.
+            "ibd" => &[(3, 0.4), (6, 0.2), (9, 0.2), (12, 0.2)],
+            "momentum" => &[(1, 0.5), (3, 0.3), (6, 0.2)],
+            "equal" => &[(3, 0.25), (6, 0.25), (9, 0.25), (12, 0.25)],
+            // The blend used on TradingView's performance columns.
+            "tv" => &[(1, 0.3), (3, 0.4), (6, 0.2), (12, 0.1)],
+            _ => return None,
+        };
+        Some(RsModel {
+            name: name.to_lowercase(),
+            periods: periods
+                .iter()
+                .map(|&(months, weight)| RsPeriod { months, weight })
+                .collect(),
+        })
+    }
+
+    pub fn resolve(spec: &RsModelSpec) -> anyhow::Result<RsModel> {
+        let model = match spec {
+            RsModelSpec::Preset(name) => RsModel::preset(name).ok_or_else(|| {
+                ValidationError(format!(
+                    "Unknown RS preset {name:?}, expected one of {}",
+                    PRESETS.join(", ")
+                ))
+            })?,
+            RsModelSpec::Custom(model) => model.clone(),
+        };
+        if model.periods.is_empty() {
+            anyhow::bail!("RS model {} has no periods", model.name);
+        }
+        if let Some(period) = model
+            .periods
+            .iter()
+            .find(|p| p.months == 0 || p.months > 12 || p.weight < 0.0)
+        {
+            anyhow::bail!(
+                "RS model {} has an invalid period {period:?}, months must be 1-12 and weights non-negative",
+                model.name
+            );
+        }
+        Ok(model)
+    }
+
+    /// Resolves a comma separated list of preset names.
+    pub fn parse_presets(names: &str) -> anyhow::Result<Vec<RsModel>> {
+        names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| RsModel::resolve(&RsModelSpec::Preset(name.to_string())))
+            .collect()
+    }
+
+    /// RS from daily candles, each period looking back `months * 21` bars.
+    pub fn rs_candles(&self, candles: &[Candle], base: &[Candle]) -> f64 {
+        let multiplier = |candles: &[Candle]| {
+            let n = candles.len();
+            if n == 0 {
+                return 0.0;
+            }
+
+            let current = candles[n - 1].close;
+            self.periods
+                .iter()
+                .map(|p| {
+                    let idx = (n - 1).saturating_sub(p.months as usize * BARS_PER_MONTH);
+                    p.weight * (current / candles[idx].close)
+                })
+                .sum::<f64>()
+        };
+
+        let base_m = multiplier(base);
+        if base_m == 0.0 {
+            0.0
+        } else {
+            multiplier(candles) / base_m
+        }
+    }
+
+    /// RS from TradingView performance percentages. Periods without a column
+    /// of their own (e.g. 9 months) are interpolated from the neighbouring ones.
+    pub fn rs_performance(&self, perf: &Performance, base: &Performance) -> f64 {
+        let multiplier = |perf: &Performance| {
+            self.periods
+                .iter()
+                .map(|p| p.weight * (1.0 + performance_over(perf, p.months) / 100.0))
+                .sum::<f64>()
+        };
+
+        let base_m = multiplier(base);
+        if base_m == 0.0 {
+            0.0
+        } else {
+            multiplier(perf) / base_m
+        }
+    }
+}
+
+impl Display for RsModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = self.periods.iter().map(|p| p.weight).sum::<f64>();
+        let periods = self
+            .periods
+            .iter()
+            .map(|p| format!("{}M {:.0}%", p.months, p.weight * 100.0 / total))
+            .join(", ");
+        write!(f, "{} ({periods})", self.name.to_uppercase())
+    }
+}
+
+fn performance_over(perf: &Performance, months: u32) -> f64 {
+    let points = [
+        (0, 0.0),
+        (1, perf.perf_1m),
+        (3, perf.perf_3m),
+        (6, perf.perf_6m),
+        (12, perf.perf_1y),
+    ];
+    points
+        .iter()
+        .tuple_windows()
+        .find(|(_, (hi, _))| months <= *hi)
+        .map(|(&(lo, lo_perf), &(hi, hi_perf))| {
+            lo_perf + (hi_perf - lo_perf) * (months - lo) as f64 / (hi - lo) as f64
+        })
+        .unwrap_or(perf.perf_1y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TickerType;
+    use chrono::{Local, TimeZone, Utc};
+
+    fn candles(closes: impl IntoIterator<Item = f64>) -> Vec<Candle> {
+        closes
+            .into_iter()
+            .enumerate()
+            .map(|(i, close)| Candle {
+                timestamp: Utc.timestamp_opt(i as i64 * 86_400, 0).unwrap(),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: 0,
+                adj_close: None,
+                last_updated: Local::now(),
+            })
+            .collect()
+    }
+
+    fn perf(perf_1m: f64, perf_3m: f64, perf_6m: f64, perf_1y: f64) -> Performance {
+        Performance {
+            ticker: "TEST".to_string(),
+            ticker_type: TickerType::Sector,
+            perf_1m,
+            perf_3m,
+            perf_6m,
+            perf_1y,
+            last_updated: Local::now(),
+        }
+    }
+
+    #[test]
+    fn ibd_preset_matches_quarterly_formula() {
+        let model = RsModel::preset("IBD").unwrap();
+        // Flat benchmark, stock doubled over the last quarter only.
+        let base = candles(vec![100.0; 300]);
+        let stock = candles((0..300).map(|i| if i < 300 - 63 { 50.0 } else { 100.0 }));
+
+        let expected = 0.4 * 2.0 + 0.2 * 2.0 + 0.2 * 2.0 + 0.2 * 2.0;
+        assert!((model.rs_candles(&stock, &base) - expected).abs() < 1e-9);
+        assert_eq!(model.rs_candles(&stock, &[]), 0.0);
+    }
+
+    #[test]
+    fn tv_preset_matches_performance_formula() {
+        let model = RsModel::preset("tv").unwrap();
+        let stock = perf(10.0, 20.0, 30.0, 40.0);
+        let base = perf(0.0, 0.0, 0.0, 0.0);
+
+        let expected = 1.0 + (10.0 * 0.3 + 20.0 * 0.4 + 30.0 * 0.2 + 40.0 * 0.1) / 100.0;
+        assert!((model.rs_performance(&stock, &base) - expected).abs() < 1e-9);
+        assert!((performance_over(&stock, 9) - 35.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rejects_unknown_presets_and_bad_periods() {
+        assert!(RsModel::parse_presets("ibd, momentum").is_ok());
+        assert!(RsModel::parse_presets("ibd,bogus").is_err());
+        let custom = RsModel {
+            name: "custom".to_string(),
+            periods: vec![RsPeriod {
+                months: 24,
+                weight: 1.0,
+            }],
+        };
+        assert!(RsModel::resolve(&RsModelSpec::Custom(custom)).is_err());
+    }
+}
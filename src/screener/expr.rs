@@ -0,0 +1,470 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::Context;
+
+/// A value a screen field resolves to for one stock. `Missing` is produced
+/// when the underlying data isn't stored (e.g. no cached fundamentals). A
+/// comparison against it is unknown rather than false, so neither
+/// `pe > 50` nor `not pe > 50` matches a stock without fundamentals.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    List(Vec<String>),
+    Missing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    Number,
+    Text,
+    List,
+}
+
+/// Every field a screen expression may reference, with its type.
+pub const FIELDS: [(&str, FieldKind); 17] = [
+    ("ticker", FieldKind::Text),
+    ("exchange", FieldKind::Text),
+    ("sector", FieldKind::Text),
+    ("industry", FieldKind::Text),
+    ("tag", FieldKind::List),
+    ("rs", FieldKind::Number),
+    ("rs_rating", FieldKind::Number),
+    ("adr_pct", FieldKind::Number),
+    ("avg_volume", FieldKind::Number),
+    ("close", FieldKind::Number),
+    ("perf_1m", FieldKind::Number),
+    ("perf_3m", FieldKind::Number),
+    ("perf_6m", FieldKind::Number),
+    ("perf_1y", FieldKind::Number),
+    ("eps_growth_q", FieldKind::Number),
+    ("revenue_growth_q", FieldKind::Number),
+    ("eps_surprise_pct", FieldKind::Number),
+];
+
+/// Anything a parsed expression can be evaluated against.
+pub trait Fields {
+    fn field(&self, name: &str) -> Value;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CmpOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cmp {
+        field: String,
+        op: CmpOp,
+        value: Literal,
+    },
+    In {
+        field: String,
+        values: Vec<Literal>,
+    },
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> anyhow::Result<Expr> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            anyhow::bail!("Unexpected {token} in screen expression");
+        }
+        Ok(expr)
+    }
+
+    pub fn matches(&self, row: &impl Fields) -> bool {
+        self.eval(row) == Some(true)
+    }
+
+    /// Three-valued evaluation: `None` when the outcome depends on a missing
+    /// field. `and`/`or` still decide when the other side settles it.
+    fn eval(&self, row: &impl Fields) -> Option<bool> {
+        match self {
+            Expr::And(left, right) => match (left.eval(row), right.eval(row)) {
+                (Some(false), _) | (_, Some(false)) => Some(false),
+                (Some(true), Some(true)) => Some(true),
+                _ => None,
+            },
+            Expr::Or(left, right) => match (left.eval(row), right.eval(row)) {
+                (Some(true), _) | (_, Some(true)) => Some(true),
+                (Some(false), Some(false)) => Some(false),
+                _ => None,
+            },
+            Expr::Not(inner) => inner.eval(row).map(|matched| !matched),
+            Expr::Cmp { field, op, value } => match row.field(field) {
+                Value::Missing => None,
+                actual => Some(compare(&actual, *op, value)),
+            },
+            Expr::In { field, values } => match row.field(field) {
+                Value::Missing => None,
+                actual => Some(
+                    values
+                        .iter()
+                        .any(|value| compare(&actual, CmpOp::Eq, value)),
+                ),
+            },
+        }
+    }
+}
+
+fn compare(actual: &Value, op: CmpOp, expected: &Literal) -> bool {
+    match (actual, expected) {
+        (Value::Number(actual), Literal::Number(expected)) => match op {
+            CmpOp::Gt => actual > expected,
+            CmpOp::Ge => actual >= expected,
+            CmpOp::Lt => actual < expected,
+            CmpOp::Le => actual <= expected,
+            CmpOp::Eq => actual == expected,
+            CmpOp::Ne => actual != expected,
+        },
+        (Value::Text(actual), Literal::Text(expected)) => match op {
+            CmpOp::Eq => actual.eq_ignore_ascii_case(expected),
+            CmpOp::Ne => !actual.eq_ignore_ascii_case(expected),
+            _ => false,
+        },
+        (Value::List(items), Literal::Text(expected)) => {
+            let contains = items.iter().any(|item| item.eq_ignore_ascii_case(expected));
+            match op {
+                CmpOp::Eq => contains,
+                CmpOp::Ne => !contains,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+// ── Tokenizer ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Text(String),
+    Op(CmpOp),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f, "'{ident}'"),
+            Token::Number(number) => write!(f, "number {number}"),
+            Token::Text(text) => write!(f, "string {text:?}"),
+            Token::Op(op) => write!(f, "operator {op:?}"),
+            Token::LParen => f.write_str("'('"),
+            Token::RParen => f.write_str("')'"),
+            Token::Comma => f.write_str("','"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> anyhow::Result<Vec<Token>> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        match ch {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            '>' | '<' | '=' | '!' => {
+                let next = chars.get(i + 1).copied();
+                let (op, len) = match (ch, next) {
+                    ('>', Some('=')) => (CmpOp::Ge, 2),
+                    ('>', _) => (CmpOp::Gt, 1),
+                    ('<', Some('=')) => (CmpOp::Le, 2),
+                    ('<', Some('>')) => (CmpOp::Ne, 2),
+                    ('<', _) => (CmpOp::Lt, 1),
+                    ('=', Some('=')) => (CmpOp::Eq, 2),
+                    ('=', _) => (CmpOp::Eq, 1),
+                    ('!', Some('=')) => (CmpOp::Ne, 2),
+                    _ => anyhow::bail!("Unexpected '!' at position {i}"),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == ch)
+                    .with_context(|| format!("Unterminated string starting at position {i}"))?;
+                tokens.push(Token::Text(chars[i + 1..i + 1 + end].iter().collect()));
+                i += end + 2;
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text = chars[start..i].iter().collect::<String>();
+                let number = text
+                    .parse::<f64>()
+                    .with_context(|| format!("Invalid number {text:?}"))?;
+                // Allow a trailing '%' so "perf_3m > 25%" reads naturally.
+                if chars.get(i) == Some(&'%') {
+                    i += 1;
+                }
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            other => anyhow::bail!("Unexpected character {other:?} at position {i}"),
+        }
+    }
+
+    Ok(tokens)
+}
+
+// ── Parser ───────────────────────────────────────────────────────────────────
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(ident)) if ident.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> anyhow::Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => anyhow::bail!("Expected {expected}, found {token}"),
+            None => anyhow::bail!("Expected {expected}, found end of expression"),
+        }
+    }
+
+    fn parse_or(&mut self) -> anyhow::Result<Expr> {
+        let mut left = self.parse_and()?;
+        while self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> anyhow::Result<Expr> {
+        let mut left = self.parse_not()?;
+        while self.eat_keyword("and") {
+            let right = self.parse_not()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> anyhow::Result<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> anyhow::Result<Expr> {
+        match self.next() {
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Ident(ident)) => self.parse_predicate(ident),
+            Some(token) => anyhow::bail!("Expected a field name, found {token}"),
+            None => anyhow::bail!("Unexpected end of screen expression"),
+        }
+    }
+
+    fn parse_predicate(&mut self, ident: String) -> anyhow::Result<Expr> {
+        let field = ident.to_lowercase();
+        let kind = FIELDS
+            .iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, kind)| *kind)
+            .with_context(|| {
+                format!(
+                    "Unknown field {ident:?}; expected one of: {}",
+                    FIELDS.map(|(name, _)| name).join(", ")
+                )
+            })?;
+
+        if self.eat_keyword("not") {
+            if !self.eat_keyword("in") {
+                anyhow::bail!("Expected 'in' after '{field} not'");
+            }
+            let values = self.parse_list(kind)?;
+            return Ok(Expr::Not(Box::new(Expr::In { field, values })));
+        }
+        if self.eat_keyword("in") {
+            let values = self.parse_list(kind)?;
+            return Ok(Expr::In { field, values });
+        }
+
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            Some(token) => anyhow::bail!("Expected a comparison after '{field}', found {token}"),
+            None => anyhow::bail!("Expected a comparison after '{field}'"),
+        };
+        if kind != FieldKind::Number && !matches!(op, CmpOp::Eq | CmpOp::Ne) {
+            anyhow::bail!("Field '{field}' only supports '=' and '!='");
+        }
+        let value = self.parse_literal(kind)?;
+        Ok(Expr::Cmp { field, op, value })
+    }
+
+    fn parse_list(&mut self, kind: FieldKind) -> anyhow::Result<Vec<Literal>> {
+        self.expect(Token::LParen)?;
+        let mut values = vec![self.parse_literal(kind)?];
+        while self.peek() == Some(&Token::Comma) {
+            self.pos += 1;
+            values.push(self.parse_literal(kind)?);
+        }
+        self.expect(Token::RParen)?;
+        Ok(values)
+    }
+
+    fn parse_literal(&mut self, kind: FieldKind) -> anyhow::Result<Literal> {
+        match (self.next(), kind) {
+            (Some(Token::Number(number)), FieldKind::Number) => Ok(Literal::Number(number)),
+            (Some(Token::Text(text)), FieldKind::Text | FieldKind::List) => Ok(Literal::Text(text)),
+            // Bare words are accepted for text fields, e.g. `exchange = NASDAQ`.
+            (Some(Token::Ident(text)), FieldKind::Text | FieldKind::List) => {
+                Ok(Literal::Text(text))
+            }
+            (Some(token), FieldKind::Number) => anyhow::bail!("Expected a number, found {token}"),
+            (Some(token), _) => anyhow::bail!("Expected a string, found {token}"),
+            (None, _) => anyhow::bail!("Expected a value, found end of expression"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct Row(HashMap<&'static str, Value>);
+
+    impl Fields for Row {
+        fn field(&self, name: &str) -> Value {
+            self.0.get(name).cloned().unwrap_or(Value::Missing)
+        }
+    }
+
+    fn row() -> Row {
+        Row(HashMap::from([
+            ("rs_rating", Value::Number(91.0)),
+            ("adr_pct", Value::Number(5.2)),
+            ("sector", Value::Text("Electronic Technology".to_string())),
+            (
+                "tag",
+                Value::List(vec!["Semiconductors".to_string(), "AI".to_string()]),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn evaluates_the_documented_example() {
+        let expr = Expr::parse(
+            r#"rs_rating > 80 and adr_pct > 4 and tag in ("Semiconductors","AI Infrastructure") and eps_growth_q > 25"#,
+        )
+        .unwrap();
+
+        // eps_growth_q is missing, so the whole conjunction fails.
+        assert!(!expr.matches(&row()));
+
+        let expr = Expr::parse(
+            r#"rs_rating > 80 and adr_pct > 4 and tag in ("semiconductors","AI Infrastructure")"#,
+        )
+        .unwrap();
+        assert!(expr.matches(&row()));
+    }
+
+    #[test]
+    fn honours_precedence_and_negation() {
+        let expr = Expr::parse("adr_pct > 10 or rs_rating >= 91 and not tag = 'Crypto'").unwrap();
+        assert!(expr.matches(&row()));
+
+        let expr = Expr::parse("(adr_pct > 10 or rs_rating >= 91) and tag not in (AI)").unwrap();
+        assert!(!expr.matches(&row()));
+    }
+
+    #[test]
+    fn negation_never_matches_missing_fields() {
+        for screen in [
+            "eps_growth_q > 25",
+            "not eps_growth_q > 25",
+            "not eps_growth_q in (1)",
+        ] {
+            assert!(!Expr::parse(screen).unwrap().matches(&row()), "{screen}");
+        }
+
+        // A known side can still settle the outcome.
+        let expr = Expr::parse("rs_rating > 80 or not eps_growth_q > 25").unwrap();
+        assert!(expr.matches(&row()));
+        let expr = Expr::parse("not (rs_rating > 95 and eps_growth_q > 25)").unwrap();
+        assert!(expr.matches(&row()));
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_bad_operators() {
+        assert!(Expr::parse("foo > 1").is_err());
+        assert!(Expr::parse("sector > 'x'").is_err());
+        assert!(Expr::parse("rs_rating > 'x'").is_err());
+        assert!(Expr::parse("rs_rating > 80 and").is_err());
+        assert!(Expr::parse("tag in ('a'").is_err());
+    }
+}
@@ -0,0 +1,155 @@
+mod expr;
+
+pub use expr::{Expr, FIELDS, FieldKind, Fields, Value};
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use tracing::{info, warn};
+
+use crate::config::APP_CONFIG;
+use crate::metrics::{StockMetrics, compute_metrics};
+use crate::rs::{RsMap, benchmark_for, rs_ratings};
+use crate::rs_model::RS_MODEL;
+use crate::store::Store;
+use crate::tv::fundamentals::Fundamentals;
+use crate::{Performance, Stock, TickerType};
+
+/// Everything the screener knows about one stored stock. Built purely from
+/// the store — no network requests are made while screening.
+#[derive(Debug, Clone)]
+pub struct ScreenRow {
+    pub stock: Stock,
+    pub tags: Vec<String>,
+    pub rs: Option<f64>,
+    pub rs_rating: Option<u8>,
+    pub metrics: Option<StockMetrics>,
+    pub close: Option<f64>,
+    pub performance: Option<Performance>,
+    pub eps_growth_q: Option<f64>,
+    pub revenue_growth_q: Option<f64>,
+    pub eps_surprise_pct: Option<f64>,
+}
+
+impl Fields for ScreenRow {
+    fn field(&self, name: &str) -> Value {
+        let number = |value: Option<f64>| value.map_or(Value::Missing, Value::Number);
+        match name {
+            "ticker" => Value::Text(self.stock.ticker.clone()),
+            "exchange" => Value::Text(self.stock.exchange.clone()),
+            "sector" => Value::Text(self.stock.sector.name.clone()),
+            "industry" => Value::Text(self.stock.industry.name.clone()),
+            "tag" => Value::List(self.tags.clone()),
+            "rs" => number(self.rs),
+            "rs_rating" => number(self.rs_rating.map(f64::from)),
+            "adr_pct" => number(self.metrics.map(|m| m.adr_pct)),
+            "avg_volume" => number(self.metrics.map(|m| m.avg_volume as f64)),
+            "close" => number(self.close),
+            "perf_1m" => number(self.performance.as_ref().map(|p| p.perf_1m)),
+            "perf_3m" => number(self.performance.as_ref().map(|p| p.perf_3m)),
+            "perf_6m" => number(self.performance.as_ref().map(|p| p.perf_6m)),
+            "perf_1y" => number(self.performance.as_ref().map(|p| p.perf_1y)),
+            "eps_growth_q" => number(self.eps_growth_q),
+            "revenue_growth_q" => number(self.revenue_growth_q),
+            "eps_surprise_pct" => number(self.eps_surprise_pct),
+            _ => Value::Missing,
+        }
+    }
+}
+
+/// Loads one [`ScreenRow`] per stock in the `stocks` table.
+pub async fn load_universe(store: &Store) -> anyhow::Result<Vec<ScreenRow>> {
+    let stocks = store.list_stocks().await?;
+    let tags_by_ticker = store
+        .list_stock_tags()
+        .await?
+        .into_iter()
+        .map(|st| (st.ticker, st.tags.into_iter().map(|t| t.name).collect_vec()))
+        .collect::<HashMap<_, _>>();
+    let mut benchmarks = HashMap::new();
+    let wanted = stocks
+        .iter()
+        .map(|stock| benchmark_for(&stock.ticker))
+        .unique();
+    for benchmark in wanted.collect_vec() {
+        let candles = store.get_candles(&benchmark).await?;
+        if candles.is_empty() {
+            warn!("No stored candles for benchmark {benchmark}, RS fields will be missing");
+        }
+        benchmarks.insert(benchmark, candles);
+    }
+
+    let mut rows = Vec::with_capacity(stocks.len());
+    for stock in stocks {
+        let candles = store.get_candles(&stock.ticker).await?;
+        let base_candles = &benchmarks[&benchmark_for(&stock.ticker)];
+        let fundamentals = match store
+            .get_fundamentals(&stock.exchange, &stock.ticker)
+            .await?
+        {
+            Some(cached) => serde_json::from_str::<Fundamentals>(&cached.payload).ok(),
+            None => None,
+        };
+        let quarters = fundamentals.as_ref().map(|f| f.quarters.as_slice());
+
+        rows.push(ScreenRow {
+            tags: tags_by_ticker
+                .get(&stock.ticker)
+                .cloned()
+                .unwrap_or_default(),
+            rs: (!candles.is_empty() && !base_candles.is_empty())
+                .then(|| RS_MODEL.rs_candles(&candles, base_candles)),
+            rs_rating: None,
+            metrics: compute_metrics(
+                &candles,
+                APP_CONFIG.metrics.adr_days,
+                APP_CONFIG.metrics.avg_volume_days,
+            ),
+            close: candles.last().map(|c| c.close),
+            performance: (!candles.is_empty())
+                .then(|| Performance::compute(&stock.ticker, TickerType::Stock, &candles)),
+            eps_growth_q: quarters.and_then(|q| yoy_growth(q, |q| q.earnings_per_share)),
+            revenue_growth_q: quarters.and_then(|q| yoy_growth(q, |q| q.revenue)),
+            eps_surprise_pct: quarters
+                .and_then(|q| q.first())
+                .and_then(|q| q.earnings_surprise_percent),
+            stock,
+        });
+    }
+
+    let raw_rs = rows
+        .iter()
+        .filter_map(|row| row.rs.map(|rs| (row.stock.ticker.clone(), rs)))
+        .collect::<RsMap>();
+    let ratings = rs_ratings(&raw_rs);
+    for row in &mut rows {
+        row.rs_rating = ratings.get(&row.stock.ticker).copied();
+    }
+    info!("Loaded {} stocks into the screening universe", rows.len());
+
+    Ok(rows)
+}
+
+/// Runs `expr` over the stored universe, returning matches ordered by RS
+/// rating (strongest first).
+pub async fn screen(store: &Store, expr: &Expr) -> anyhow::Result<Vec<ScreenRow>> {
+    let mut rows = load_universe(store)
+        .await?
+        .into_iter()
+        .filter(|row| expr.matches(row))
+        .collect_vec();
+    rows.sort_by(|a, b| {
+        b.rs_rating
+            .cmp(&a.rs_rating)
+            .then_with(|| a.stock.ticker.cmp(&b.stock.ticker))
+    });
+    Ok(rows)
+}
+
+/// Year-over-year growth of the latest quarter against the same quarter a year
+/// earlier. `quarters` is newest-first, as stored by the fundamentals client.
+fn yoy_growth<T>(quarters: &[T], value: impl Fn(&T) -> Option<f64>) -> Option<f64> {
+    let latest = value(quarters.first()?)?;
+    let year_ago = value(quarters.get(4)?)?;
+    (year_ago != 0.0).then(|| (latest - year_ago) / year_ago.abs() * 100.0)
+}
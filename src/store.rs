@@ -7,6 +7,7 @@ use sqlx::{
     sqlite::SqlitePoolOptions,
 };
 
+use crate::symbol;
 use crate::util::is_upto_date;
 use crate::yf::Candle;
 use serde::Serialize;
@@ -179,6 +180,34 @@ impl Store {
         }))
     }
 
+    pub async fn list_stocks(&self) -> anyhow::Result<Vec<Stock>> {
+        let rows = sqlx::query!(
+            "SELECT ticker, exchange, sector_name, sector_url, industry_name, industry_url, last_update
+             FROM stocks
+             ORDER BY ticker",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list stocks")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Stock {
+                ticker: r.ticker,
+                exchange: r.exchange,
+                sector: Group {
+                    name: r.sector_name,
+                    url: r.sector_url,
+                },
+                industry: Group {
+                    name: r.industry_name,
+                    url: r.industry_url,
+                },
+                last_update: r.last_update,
+            })
+            .collect())
+    }
+
     pub async fn add_stocks(&self, stocks: &[Stock]) -> sqlx::Result<()> {
         let mut tx = self.pool.begin().await?;
 
@@ -210,11 +239,46 @@ impl Store {
         tx.commit().await
     }
 
+    /// Replaces tickers that were resolved to another listing before with
+    /// that listing's ID, dropping duplicates.
+    pub async fn resolve_aliases(&self, tickers: Vec<String>) -> sqlx::Result<Vec<String>> {
+        let mut seen = std::collections::HashSet::new();
+        let mut resolved = Vec::with_capacity(tickers.len());
+        for ticker in tickers {
+            let listing: Option<String> =
+                sqlx::query_scalar("SELECT ticker FROM ticker_aliases WHERE alias = $1")
+                    .bind(&ticker)
+                    .fetch_optional(&self.pool)
+                    .await?;
+            let ticker = listing.unwrap_or(ticker);
+            if seen.insert(ticker.clone()) {
+                resolved.push(ticker);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Saves `(alias, ticker)` pairs: `alias` was resolved to the listing `ticker`.
+    pub async fn save_ticker_aliases(&self, aliases: &[(String, String)]) -> sqlx::Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for (alias, ticker) in aliases {
+            sqlx::query(
+                r"INSERT INTO ticker_aliases (alias, ticker) VALUES ($1, $2)
+                 ON CONFLICT(alias) DO UPDATE SET ticker = excluded.ticker",
+            )
+            .bind(alias)
+            .bind(ticker)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
     pub async fn get_company_profile(
         &self,
         ticker: impl AsRef<str>,
     ) -> sqlx::Result<Option<CompanyProfile>> {
-        let ticker = ticker.as_ref().trim().to_uppercase();
+        let ticker = symbol::canonical(ticker.as_ref());
         sqlx::query_as!(
             CompanyProfile,
             r#"
@@ -229,7 +293,7 @@ impl Store {
     }
 
     pub async fn save_company_profile(&self, profile: &CompanyProfile) -> sqlx::Result<()> {
-        let ticker = profile.ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(&profile.ticker);
         sqlx::query!(
             r#"
             INSERT INTO company_profiles
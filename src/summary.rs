@@ -1,10 +1,30 @@
+use crate::api_error::ApiError;
 use crate::config::APP_CONFIG;
-use crate::metrics::MetricsMap;
-use crate::{Stock, Ticker, etf_map};
+use crate::html_error::HtmlError;
+use crate::metrics::{MetricsMap, StockMetrics};
+use crate::rs::{RsMap, RsMaps};
+use crate::rs_model::RS_MODEL;
+use crate::store::Store;
+use crate::tv::screener_api::Ambiguity;
+use crate::watchlist::StockInfo;
+use crate::yf::YFinance;
+use crate::{Stock, Ticker, etf_map, metrics, rs, watchlist};
 use askama::Template;
+use axum::Json;
+use axum::extract::Extension;
+use axum::response::Html;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use chrono::{DateTime, Local};
+use futures::Stream;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{Mutex, RwLock, broadcast};
+use tracing::info;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Summary {
@@ -83,35 +103,525 @@ impl Summary {
         Summary { size, sectors }
     }
 
-    pub fn render(
+    /// The summary tree annotated with ETFs, RS and metrics. Names are
+    /// matched case-insensitively, like the page does.
+    pub fn report_tree(
         &self,
-        sector_rs: HashMap<String, f64>,
-        industry_rs: HashMap<String, f64>,
-        stock_rs: HashMap<String, f64>,
-        stock_metrics: MetricsMap,
-    ) -> String {
-        #[derive(Template)]
-        #[template(path = "./stocks_themes.html")]
-        struct Html<'a> {
-            summary: &'a Summary,
-            base_ticker: &'a str,
-            sectors: Vec<etf_map::Sector>,
-            sector_rs: HashMap<String, f64>,
-            industry_rs: HashMap<String, f64>,
-            stock_rs: HashMap<String, f64>,
-            stock_metrics: MetricsMap,
+        mapping: &[etf_map::Sector],
+        rs_maps: &RsMaps,
+        stock_metrics: &MetricsMap,
+    ) -> Vec<ReportSector> {
+        let lookup = |map: &RsMap, name: &str| {
+            map.get(name).copied().or_else(|| {
+                map.iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                    .map(|(_, v)| *v)
+            })
+        };
+
+        self.sectors
+            .iter()
+            .map(|sector| ReportSector {
+                name: sector.name.clone(),
+                url: sector.url.clone(),
+                etf: mapping
+                    .iter()
+                    .find(|m| m.sector.eq_ignore_ascii_case(&sector.name))
+                    .map(|m| m.sector_etf.clone()),
+                rs: lookup(&rs_maps.sectors, &sector.name),
+                size: sector.size,
+                industries: sector
+                    .industries
+                    .iter()
+                    .map(|industry| ReportIndustry {
+                        name: industry.name.clone(),
+                        url: industry.url.clone(),
+                        etf: mapping
+                            .iter()
+                            .flat_map(|m| &m.industries)
+                            .find(|i| i.name.eq_ignore_ascii_case(&industry.name))
+                            .map(|i| i.etf.clone()),
+                        rs: lookup(&rs_maps.industries, &industry.name),
+                        size: industry.size,
+                        tickers: industry
+                            .tickers
+                            .iter()
+                            .map(|t| ReportTicker {
+                                ticker: t.ticker.clone(),
+                                exchange: t.exchange.clone(),
+                                rs: rs_maps.stocks.get(&t.ticker).copied(),
+                                metrics: stock_metrics.get(&t.ticker).copied(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// Machine-readable form of the stock_themes page, served at `/api/summary`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SummaryReport {
+    pub benchmark: String,
+    pub rs_model: String,
+    pub generated_at: DateTime<Local>,
+    pub size: usize,
+    pub sectors: Vec<ReportSector>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReportSector {
+    pub name: String,
+    pub url: String,
+    pub etf: Option<String>,
+    pub rs: Option<f64>,
+    pub size: usize,
+    pub industries: Vec<ReportIndustry>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReportIndustry {
+    pub name: String,
+    pub url: String,
+    pub etf: Option<String>,
+    pub rs: Option<f64>,
+    pub size: usize,
+    pub tickers: Vec<ReportTicker>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ReportTicker {
+    pub ticker: String,
+    pub exchange: String,
+    pub rs: Option<f64>,
+    pub metrics: Option<StockMetrics>,
+}
+
+/// What changed in a reload, pushed to open pages over `/api/summary/events`.
+#[derive(Clone, Debug, Serialize)]
+pub struct SummaryUpdate {
+    pub generated_at: DateTime<Local>,
+    pub size: usize,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Added tickers TradingView matched several listings for.
+    pub ambiguous: Vec<Ambiguity>,
+}
+
+/// The served stocks, everything computed for them and the report built from
+/// that. The watchlist can change while the server runs; only new tickers
+/// are looked up and computed.
+pub struct SummaryState {
+    data: RwLock<SummaryData>,
+    /// Serializes reloads so two changes can't compute the same tickers.
+    reload: Mutex<()>,
+    updates: broadcast::Sender<SummaryUpdate>,
+    yf: Arc<YFinance>,
+}
+
+struct SummaryData {
+    stocks: Vec<Stock>,
+    rs_maps: RsMaps,
+    stock_metrics: MetricsMap,
+    report: Arc<SummaryReport>,
+}
+
+impl SummaryState {
+    pub async fn build(
+        store: &Store,
+        yf: Arc<YFinance>,
+        stocks: Vec<Stock>,
+    ) -> anyhow::Result<SummaryState> {
+        let start = Instant::now();
+        let rs_maps = rs::build_rs_maps(store, &yf, &stocks).await?;
+        let stock_metrics = metrics::build_stock_metrics(store, &yf, &stocks).await?;
+        info!("Computed metrics for {} stocks", stock_metrics.len());
+        let report = build_report(store, &stocks, &rs_maps, &stock_metrics).await?;
+        info!(
+            "Built summary of {} stocks in {:.2?}",
+            report.size,
+            start.elapsed()
+        );
+
+        Ok(SummaryState {
+            data: RwLock::new(SummaryData {
+                stocks,
+                rs_maps,
+                stock_metrics,
+                report: Arc::new(report),
+            }),
+            reload: Mutex::new(()),
+            updates: broadcast::channel(16).0,
+            yf,
+        })
+    }
+
+    pub async fn report(&self) -> Arc<SummaryReport> {
+        Arc::clone(&self.data.read().await.report)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SummaryUpdate> {
+        self.updates.subscribe()
+    }
+
+    pub async fn stocks(&self) -> Vec<Stock> {
+        self.data.read().await.stocks.clone()
+    }
+
+    pub async fn tickers(&self) -> Vec<String> {
+        let data = self.data.read().await;
+        data.stocks.iter().map(|s| s.ticker.clone()).collect()
+    }
+
+    /// RS and metrics already computed for `ticker`, if it is served.
+    pub async fn stock_stats(&self, ticker: &str) -> Option<(f64, Option<StockMetrics>)> {
+        let data = self.data.read().await;
+        let rs = *data.rs_maps.stocks.get(ticker)?;
+        Some((rs, data.stock_metrics.get(ticker).copied()))
+    }
+
+    /// Recomputes RS and metrics of every stock, e.g. after new candles.
+    pub async fn refresh(&self, store: &Store) -> anyhow::Result<Arc<SummaryReport>> {
+        let _reload = self.reload.lock().await;
+        let stocks = self.data.read().await.stocks.clone();
+        let rs_maps = rs::build_rs_maps(store, &self.yf, &stocks).await?;
+        let stock_metrics = metrics::build_stock_metrics(store, &self.yf, &stocks).await?;
+        let report = Arc::new(build_report(store, &stocks, &rs_maps, &stock_metrics).await?);
+
+        let mut data = self.data.write().await;
+        data.rs_maps = rs_maps;
+        data.stock_metrics = stock_metrics;
+        data.report = Arc::clone(&report);
+        drop(data);
+        self.notify(&report, Vec::new(), Vec::new(), Vec::new());
+        Ok(report)
+    }
+
+    /// Replaces the served watchlist with `tickers`. Stock info, RS and
+    /// metrics are only computed for tickers that weren't served before;
+    /// returns `None` when nothing changed.
+    pub async fn set_tickers(
+        &self,
+        store: &Store,
+        tickers: Vec<String>,
+    ) -> anyhow::Result<Option<SummaryUpdate>> {
+        let _reload = self.reload.lock().await;
+        let tickers = store.resolve_aliases(tickers).await?;
+        let (added, removed) = diff_tickers(&self.data.read().await.stocks, &tickers);
+        if added.is_empty() && removed.is_empty() {
+            return Ok(None);
+        }
+        info!(
+            "Watchlist changed: {} added, {} removed",
+            added.len(),
+            removed.len()
+        );
+
+        let StockInfo {
+            stocks: new_stocks,
+            ambiguous,
+        } = watchlist::fetch_stock_info(store, added.clone()).await?;
+        let new_rs = rs::build_rs_maps(store, &self.yf, &new_stocks).await?;
+        let new_metrics = metrics::build_stock_metrics(store, &self.yf, &new_stocks).await?;
+
+        let mut data = self.data.write().await;
+        let removed_set = removed.iter().collect::<HashSet<_>>();
+        data.stocks.retain(|s| !removed_set.contains(&s.ticker));
+        // A new ticker can resolve to a listing that is already served.
+        let served = data
+            .stocks
+            .iter()
+            .map(|s| s.ticker.clone())
+            .collect::<HashSet<_>>();
+        data.stocks.extend(
+            new_stocks
+                .into_iter()
+                .filter(|s| !served.contains(&s.ticker)),
+        );
+        for ticker in &removed {
+            data.rs_maps.stocks.remove(ticker);
+            data.stock_metrics.remove(ticker);
+        }
+        data.rs_maps.stocks.extend(new_rs.stocks);
+        data.rs_maps.sectors.extend(new_rs.sectors);
+        data.rs_maps.industries.extend(new_rs.industries);
+        data.stock_metrics.extend(new_metrics);
+        let SummaryData {
+            stocks, rs_maps, ..
+        } = &mut *data;
+        prune_groups(rs_maps, stocks);
+
+        let report =
+            Arc::new(build_report(store, &data.stocks, &data.rs_maps, &data.stock_metrics).await?);
+        data.report = Arc::clone(&report);
+        drop(data);
+        Ok(Some(self.notify(&report, added, removed, ambiguous)))
+    }
+
+    fn notify(
+        &self,
+        report: &SummaryReport,
+        added: Vec<String>,
+        removed: Vec<String>,
+        ambiguous: Vec<Ambiguity>,
+    ) -> SummaryUpdate {
+        let update = SummaryUpdate {
+            generated_at: report.generated_at,
+            size: report.size,
+            added,
+            removed,
+            ambiguous,
+        };
+        // No receivers just means no page is open.
+        let _ = self.updates.send(update.clone());
+        update
+    }
+}
+
+/// Tickers of `wanted` that aren't in `current`, and tickers of `current`
+/// that aren't wanted any more.
+fn diff_tickers(current: &[Stock], wanted: &[String]) -> (Vec<String>, Vec<String>) {
+    let current_set = current
+        .iter()
+        .map(|s| s.ticker.as_str())
+        .collect::<HashSet<_>>();
+    let wanted_set = wanted.iter().map(String::as_str).collect::<HashSet<_>>();
+    let added = wanted
+        .iter()
+        .filter(|t| !current_set.contains(t.as_str()))
+        .unique()
+        .cloned()
+        .collect_vec();
+    let removed = current
+        .iter()
+        .filter(|s| !wanted_set.contains(s.ticker.as_str()))
+        .map(|s| s.ticker.clone())
+        .collect_vec();
+    (added, removed)
+}
+
+/// Drops the sector and industry RS of groups no served stock belongs to any
+/// more. Keys are the mapping's spelling, so names compare case-insensitively.
+fn prune_groups(rs_maps: &mut RsMaps, stocks: &[Stock]) {
+    let sectors = stocks
+        .iter()
+        .map(|s| s.sector.name.to_lowercase())
+        .collect::<HashSet<_>>();
+    let industries = stocks
+        .iter()
+        .map(|s| s.industry.name.to_lowercase())
+        .collect::<HashSet<_>>();
+    rs_maps
+        .sectors
+        .retain(|name, _| sectors.contains(&name.to_lowercase()));
+    rs_maps
+        .industries
+        .retain(|name, _| industries.contains(&name.to_lowercase()));
+}
+
+async fn build_report(
+    store: &Store,
+    stocks: &[Stock],
+    rs_maps: &RsMaps,
+    stock_metrics: &MetricsMap,
+) -> anyhow::Result<SummaryReport> {
+    let mapping = etf_map::load_mapping(store).await?;
+    let summary = Summary::summarize(stocks.iter().cloned());
+    Ok(SummaryReport {
+        benchmark: APP_CONFIG.base_ticker.to_uppercase(),
+        rs_model: RS_MODEL.to_string(),
+        generated_at: Local::now(),
+        size: summary.size,
+        sectors: summary.report_tree(&mapping, rs_maps, stock_metrics),
+    })
+}
+
+/// The stock_themes page. Served pages load everything from the API; an
+/// exported page carries the data and scripts it needs in `offline`.
+#[derive(Template)]
+#[template(path = "./stocks_themes.html")]
+pub(crate) struct SummaryPage<'a> {
+    pub base_ticker: &'a str,
+    pub rs_model: String,
+    pub offline: Option<OfflineAssets>,
+}
+
+/// Inlined into an exported page in place of the API and `/assets` requests.
+pub(crate) struct OfflineAssets {
+    /// Canned API responses keyed by URL, as JSON.
+    pub data_json: String,
+    pub tag_mappings_js: String,
+    pub application_js: String,
+}
+
+/// GET / — the page shell; the data comes from `/api/summary`.
+pub async fn summary_home() -> Result<Html<String>, HtmlError> {
+    let html = SummaryPage {
+        base_ticker: &APP_CONFIG.base_ticker,
+        rs_model: RS_MODEL.to_string(),
+        offline: None,
+    }
+    .render()?;
+    Ok(Html(html))
+}
+
+/// GET /api/summary
+pub async fn summary_handler(
+    Extension(state): Extension<Arc<SummaryState>>,
+) -> Json<Arc<SummaryReport>> {
+    Json(state.report().await)
+}
+
+/// GET /api/summary/events — a `summary` SSE event with a [`SummaryUpdate`]
+/// whenever the report is rebuilt.
+pub async fn events_handler(
+    Extension(state): Extension<Arc<SummaryState>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let updates = futures::stream::unfold(state.subscribe(), |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(update) => {
+                    let event = Event::default()
+                        .event("summary")
+                        .json_data(&update)
+                        .unwrap_or_else(|_| Event::default().event("summary"));
+                    return Some((Ok(event), rx));
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
         }
+    });
+    Sse::new(updates).keep_alive(KeepAlive::default())
+}
 
-        let html = Html {
-            summary: self,
-            base_ticker: &APP_CONFIG.base_ticker,
-            sectors: etf_map::tv_mapping(),
-            sector_rs,
-            industry_rs,
-            stock_rs,
-            stock_metrics,
+/// POST /api/summary/refresh — rebuilds the report and returns it.
+pub async fn refresh_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(state): Extension<Arc<SummaryState>>,
+) -> Result<Json<Arc<SummaryReport>>, ApiError> {
+    Ok(Json(state.refresh(&store).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Group;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    fn stock(ticker: &str, sector: &str, industry: &str) -> Stock {
+        let group = |name: &str| Group {
+            name: name.to_string(),
+            url: format!("https://example.com/{name}"),
         };
+        Stock {
+            ticker: ticker.to_string(),
+            exchange: "NASDAQ".to_string(),
+            sector: group(sector),
+            industry: group(industry),
+            last_update: Utc::now().date_naive(),
+        }
+    }
+
+    #[test]
+    fn report_tree_attaches_etfs_rs_and_metrics() {
+        let summary = Summary::summarize([
+            stock("NVDA", "Electronic Technology", "Semiconductors"),
+            stock("AMD", "Electronic Technology", "Semiconductors"),
+            stock(
+                "AAPL",
+                "Electronic Technology",
+                "Telecommunications Equipment",
+            ),
+        ]);
+        let mapping = vec![etf_map::Sector {
+            sector: "Electronic Technology".to_string(),
+            sector_etf: "XLK".to_string(),
+            industries: vec![etf_map::Industry {
+                name: "semiconductors".to_string(),
+                etf: "SMH".to_string(),
+            }],
+        }];
+        let rs_maps = RsMaps {
+            sectors: HashMap::from([("ELECTRONIC TECHNOLOGY".to_string(), 1.1)]),
+            industries: HashMap::from([("Semiconductors".to_string(), 1.3)]),
+            stocks: HashMap::from([("NVDA".to_string(), 1.5)]),
+        };
+        let metrics = HashMap::from([(
+            "NVDA".to_string(),
+            StockMetrics {
+                adr_pct: 3.2,
+                avg_volume: 1_000,
+            },
+        )]);
+
+        let tree = summary.report_tree(&mapping, &rs_maps, &metrics);
+
+        assert_eq!(tree.len(), 1);
+        let sector = &tree[0];
+        assert_eq!(
+            (sector.etf.as_deref(), sector.rs, sector.size),
+            (Some("XLK"), Some(1.1), 3)
+        );
+        let semis = &sector.industries[0];
+        assert_eq!(
+            (semis.name.as_str(), semis.etf.as_deref(), semis.rs),
+            ("Semiconductors", Some("SMH"), Some(1.3))
+        );
+        let nvda = semis.tickers.iter().find(|t| t.ticker == "NVDA").unwrap();
+        assert_eq!(nvda.rs, Some(1.5));
+        assert_eq!(nvda.metrics.map(|m| m.avg_volume), Some(1_000));
+        let telecom = &sector.industries[1];
+        assert_eq!((telecom.etf.as_ref(), telecom.rs), (None, None));
+        assert_eq!(telecom.tickers[0].metrics.map(|m| m.adr_pct), None);
+    }
+
+    #[test]
+    fn diff_tickers_reports_added_and_removed() {
+        let current = vec![
+            stock("NVDA", "Electronic Technology", "Semiconductors"),
+            stock("XOM", "Energy Minerals", "Integrated Oil"),
+        ];
+        let wanted = ["NVDA", "AMD", "AMD"].map(String::from);
+
+        let (added, removed) = diff_tickers(&current, &wanted);
+
+        assert_eq!(added, vec!["AMD"]);
+        assert_eq!(removed, vec!["XOM"]);
+        assert_eq!(
+            diff_tickers(&current, &["XOM".to_string(), "NVDA".to_string()]),
+            (Vec::new(), Vec::new())
+        );
+    }
+
+    #[test]
+    fn prune_groups_drops_groups_without_stocks() {
+        let mut rs_maps = RsMaps {
+            sectors: HashMap::from([
+                ("ELECTRONIC TECHNOLOGY".to_string(), 1.1),
+                ("Energy Minerals".to_string(), 0.9),
+            ]),
+            industries: HashMap::from([
+                ("Semiconductors".to_string(), 1.3),
+                ("Integrated Oil".to_string(), 0.8),
+            ]),
+            stocks: HashMap::new(),
+        };
+
+        prune_groups(
+            &mut rs_maps,
+            &[stock("NVDA", "Electronic Technology", "Semiconductors")],
+        );
 
-        html.render().expect("Failed to render html")
+        assert_eq!(
+            rs_maps.sectors.keys().collect_vec(),
+            vec!["ELECTRONIC TECHNOLOGY"]
+        );
+        assert_eq!(
+            rs_maps.industries.keys().collect_vec(),
+            vec!["Semiconductors"]
+        );
     }
 }
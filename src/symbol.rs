@@ -0,0 +1,416 @@
+//! Ticker symbology. Yahoo, TradingView and ThinkorSwim spell the same
+//! instrument differently:
+//!
+//! | Canonical | Yahoo   | TradingView     | ThinkorSwim |
+//! |-----------|---------|-----------------|-------------|
+//! | `BRK.B`   | `BRK-B` | `NYSE:BRK.B`    | `BRK/B`     |
+//! | `^GSPC`   | `^GSPC` | `SP:SPX`        | `.SPX`      |
+//! | `/ES`     | `ES=F`  | `CME_MINI:ES1!` | `/ES`       |
+//! | `SHOP.TO` | `SHOP.TO` | `TSX:SHOP`    | `SHOP.TO`   |
+//!
+//! Listings outside the US (see [`MARKETS`]) keep Yahoo's market suffix in
+//! their canonical ID, so `SHOP` (NYSE) and `SHOP.TO` (TSX) never collide.
+//! Everything stored or served uses the canonical ID; vendor notation is only
+//! produced at the edge, when calling or exporting to that vendor.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::api_error::ValidationError;
+
+/// Indexes known under different names per vendor: (Yahoo, TradingView, ThinkorSwim).
+const INDEXES: &[(&str, &str, &str)] = &[
+    ("GSPC", "SP:SPX", "SPX"),
+    ("NDX", "NASDAQ:NDX", "NDX"),
+    ("IXIC", "NASDAQ:IXIC", "COMPX"),
+    ("DJI", "DJ:DJI", "DJI"),
+    ("RUT", "TVC:RUT", "RUT"),
+    ("VIX", "CBOE:VIX", "VIX"),
+];
+
+/// TradingView prefixes of US exchanges; tickers listed there carry no suffix.
+const US_EXCHANGES: &[&str] = &["NASDAQ", "NYSE", "AMEX", "ARCA", "CBOE", "BATS", "OTC"];
+
+/// A non-US market tickers can be qualified with.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Market {
+    /// TradingView exchange prefix.
+    pub code: &'static str,
+    /// Yahoo ticker suffix.
+    pub suffix: &'static str,
+    /// How Yahoo names the exchange in quotes.
+    pub yahoo_exchanges: &'static [&'static str],
+    /// Broad-market ETF RS is measured against, unless configured otherwise.
+    pub benchmark: &'static str,
+}
+
+pub const MARKETS: &[Market] = &[
+    Market {
+        code: "TSX",
+        suffix: ".TO",
+        yahoo_exchanges: &["Toronto"],
+        benchmark: "XIU.TO",
+    },
+    Market {
+        code: "TSXV",
+        suffix: ".V",
+        yahoo_exchanges: &["TSXV", "Canadian Venture"],
+        benchmark: "XIU.TO",
+    },
+    Market {
+        code: "LSE",
+        suffix: ".L",
+        yahoo_exchanges: &["LSE"],
+        benchmark: "ISF.L",
+    },
+    Market {
+        code: "ASX",
+        suffix: ".AX",
+        yahoo_exchanges: &["ASX"],
+        benchmark: "STW.AX",
+    },
+    Market {
+        code: "XETR",
+        suffix: ".DE",
+        yahoo_exchanges: &["XETRA"],
+        benchmark: "EXS1.DE",
+    },
+    Market {
+        code: "HKEX",
+        suffix: ".HK",
+        yahoo_exchanges: &["HKSE"],
+        benchmark: "2800.HK",
+    },
+];
+
+impl Market {
+    pub fn by_code(code: &str) -> Option<&'static Market> {
+        MARKETS
+            .iter()
+            .find(|market| market.code.eq_ignore_ascii_case(code))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    /// Stocks, ETFs and anything else quoted like them.
+    Equity,
+    Index,
+    /// Continuous front-month future.
+    Future,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    /// Canonical name without the kind marker: `BRK.B`, `GSPC`, `ES`.
+    pub root: String,
+    /// TradingView exchange prefix, when the input had one.
+    pub exchange: Option<String>,
+}
+
+impl Symbol {
+    /// Reads a symbol in any vendor's notation.
+    pub fn parse(value: &str) -> Result<Symbol, &'static str> {
+        let value = value.trim().trim_matches('"').trim().to_uppercase();
+        if value.is_empty() {
+            return Err("empty");
+        }
+        if value.chars().any(char::is_whitespace) {
+            return Err("not a ticker");
+        }
+        let (exchange, symbol) = match value.split_once(':') {
+            Some((exchange, symbol))
+                if !exchange.is_empty()
+                    && exchange
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                (Some(exchange.to_string()), symbol)
+            }
+            Some(_) => return Err("not a ticker"),
+            None => (None, value.as_str()),
+        };
+        let valid = symbol.len() <= 24
+            && symbol.chars().any(|c| c.is_ascii_alphanumeric())
+            && symbol.chars().all(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '/' | '^' | '=' | '!')
+            });
+        if !valid {
+            return Err("not a ticker");
+        }
+
+        let make = |kind, root: &str| Symbol {
+            kind,
+            root: root.to_string(),
+            exchange: exchange.clone(),
+        };
+        if let Some(&(yahoo, ..)) = INDEXES.iter().find(|(_, tv, _)| *tv == value) {
+            return Ok(make(SymbolKind::Index, yahoo));
+        }
+        if let Some(root) = symbol_root(symbol, '^') {
+            return Ok(make(SymbolKind::Index, root));
+        }
+        if let Some(root) = symbol_root(symbol, '.') {
+            let yahoo = INDEXES
+                .iter()
+                .find(|(_, _, tos)| *tos == root)
+                .map_or(root, |(yahoo, ..)| yahoo);
+            return Ok(make(SymbolKind::Index, yahoo));
+        }
+        if let Some(root) = symbol_root(symbol, '/') {
+            return Ok(make(SymbolKind::Future, root));
+        }
+        if let Some(root) = symbol.strip_suffix("=F").filter(|root| is_root(root)) {
+            return Ok(make(SymbolKind::Future, root));
+        }
+        if let Some(root) = symbol
+            .strip_suffix('!')
+            .and_then(|root| root.strip_suffix(|c: char| c.is_ascii_digit()))
+            .filter(|root| is_root(root))
+        {
+            return Ok(make(SymbolKind::Future, root));
+        }
+
+        // A market from the exchange prefix (TSX:SHOP) or Yahoo suffix (SHOP.TO).
+        let market = exchange.as_deref().and_then(Market::by_code);
+        let (symbol, market) = match market {
+            Some(market) => (symbol, Some(market)),
+            None => MARKETS
+                .iter()
+                .find_map(|market| {
+                    symbol
+                        .strip_suffix(market.suffix)
+                        .filter(|base| {
+                            base.chars()
+                                .next()
+                                .is_some_and(|c| c.is_ascii_alphanumeric())
+                        })
+                        .map(|base| (base, Some(market)))
+                })
+                .unwrap_or((symbol, None)),
+        };
+
+        // One-letter share classes: BRK-B / BRK/B → BRK.B. Longer suffixes are
+        // currencies and the like (BTC-USD) and are left alone.
+        let class = symbol
+            .rsplit_once(['-', '/'])
+            .filter(|(base, class)| {
+                is_root(base) && class.len() == 1 && class.chars().all(|c| c.is_ascii_alphabetic())
+            })
+            .map(|(base, class)| format!("{base}.{class}"));
+        Ok(Symbol {
+            kind: SymbolKind::Equity,
+            root: class.unwrap_or_else(|| symbol.to_string()),
+            exchange: market.map(|market| market.code.to_string()).or(exchange),
+        })
+    }
+
+    /// The non-US market the symbol is listed on.
+    pub fn market(&self) -> Option<&'static Market> {
+        match self.kind {
+            SymbolKind::Equity => self.exchange.as_deref().and_then(Market::by_code),
+            _ => None,
+        }
+    }
+
+    /// The ID stored and served everywhere: `BRK.B`, `SHOP.TO`, `^GSPC`, `/ES`.
+    pub fn id(&self) -> String {
+        match self.kind {
+            SymbolKind::Equity => match self.market() {
+                Some(market) => format!("{}{}", self.root, market.suffix),
+                None => self.root.clone(),
+            },
+            SymbolKind::Index => format!("^{}", self.root),
+            SymbolKind::Future => format!("/{}", self.root),
+        }
+    }
+
+    pub fn yahoo(&self) -> String {
+        match self.kind {
+            SymbolKind::Equity => {
+                let root = match class_share(&self.root) {
+                    Some((base, class)) => format!("{base}-{class}"),
+                    None => self.root.clone(),
+                };
+                format!("{root}{}", self.market().map_or("", |market| market.suffix))
+            }
+            SymbolKind::Index => format!("^{}", self.root),
+            SymbolKind::Future => format!("{}=F", self.root),
+        }
+    }
+
+    /// `EXCHANGE:TICKER` when the exchange is known, else the bare ticker
+    /// for TradingView to resolve.
+    pub fn tradingview(&self) -> String {
+        let name = match self.kind {
+            SymbolKind::Equity => self.root.clone(),
+            SymbolKind::Index => {
+                if let Some((_, tv, _)) = INDEXES.iter().find(|(yahoo, ..)| *yahoo == self.root) {
+                    return tv.to_string();
+                }
+                self.root.clone()
+            }
+            SymbolKind::Future => format!("{}1!", self.root),
+        };
+        match &self.exchange {
+            Some(exchange) => format!("{exchange}:{name}"),
+            None => name,
+        }
+    }
+
+    /// ThinkorSwim only trades US listings; others keep their canonical ID.
+    pub fn thinkorswim(&self) -> String {
+        match self.kind {
+            SymbolKind::Equity if self.market().is_some() => self.id(),
+            SymbolKind::Equity => match class_share(&self.root) {
+                Some((base, class)) => format!("{base}/{class}"),
+                None => self.root.clone(),
+            },
+            SymbolKind::Index => {
+                let tos = INDEXES
+                    .iter()
+                    .find(|(yahoo, ..)| *yahoo == self.root)
+                    .map_or(self.root.as_str(), |(_, _, tos)| tos);
+                format!(".{tos}")
+            }
+            SymbolKind::Future => format!("/{}", self.root),
+        }
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id())
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Symbol::parse(s)
+            .map_err(|reason| ValidationError(format!("Invalid symbol {s:?}: {reason}")).into())
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.id())
+    }
+}
+
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Canonical ID of `ticker` in any notation. Values that aren't symbols are
+/// only trimmed and uppercased so lookups still fail the usual way.
+pub fn canonical(ticker: &str) -> String {
+    Symbol::parse(ticker)
+        .map(|symbol| symbol.id())
+        .unwrap_or_else(|_| ticker.trim().to_uppercase())
+}
+
+/// Yahoo notation of `ticker` for quote and chart requests.
+pub fn yahoo(ticker: &str) -> String {
+    Symbol::parse(ticker)
+        .map(|symbol| symbol.yahoo())
+        .unwrap_or_else(|_| ticker.trim().to_uppercase())
+}
+
+/// TradingView exchange prefix for a Yahoo exchange name.
+pub fn tradingview_exchange(yahoo_exchange: &str) -> Option<&'static str> {
+    match yahoo_exchange {
+        "NYSE" => Some("NYSE"),
+        "NYSE American" => Some("ARCA"),
+        "NYSEArca" => Some("ARCA"),
+        "BATS" | "Cboe BZX" => Some("CBOE"),
+        _ if yahoo_exchange.starts_with("Nasdaq") => Some("NASDAQ"),
+        _ if yahoo_exchange.starts_with("OTC") => Some("OTC"),
+        _ => MARKETS
+            .iter()
+            .find(|market| market.yahoo_exchanges.contains(&yahoo_exchange))
+            .map(|market| market.code),
+    }
+}
+
+/// Whether `exchange` (a TradingView prefix) is a US exchange.
+pub fn is_us_exchange(exchange: &str) -> bool {
+    US_EXCHANGES
+        .iter()
+        .any(|us| exchange.eq_ignore_ascii_case(us))
+}
+
+/// The root after `marker`, e.g. `GSPC` for `^GSPC`.
+fn symbol_root(symbol: &str, marker: char) -> Option<&str> {
+    symbol.strip_prefix(marker).filter(|root| is_root(root))
+}
+
+fn is_root(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn class_share(root: &str) -> Option<(&str, &str)> {
+    root.rsplit_once('.')
+        .filter(|(base, class)| is_root(base) && class.len() == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(value: &str) -> String {
+        Symbol::parse(value).unwrap().id()
+    }
+
+    #[test]
+    fn parses_every_vendor_notation() {
+        for value in ["BRK.B", "brk-b", "BRK/B", "NYSE:BRK.B"] {
+            assert_eq!(id(value), "BRK.B", "{value}");
+        }
+        for value in ["^GSPC", ".SPX", "SP:SPX"] {
+            assert_eq!(id(value), "^GSPC", "{value}");
+        }
+        for value in ["/ES", "ES=F", "CME_MINI:ES1!"] {
+            assert_eq!(id(value), "/ES", "{value}");
+        }
+        for value in ["SHOP.TO", "TSX:SHOP", "shop.to"] {
+            assert_eq!(id(value), "SHOP.TO", "{value}");
+        }
+        assert_eq!(id("TSX:RCI.B"), "RCI.B.TO");
+        assert_eq!(id("RCI-B.TO"), "RCI.B.TO");
+        assert_eq!(id("LSE:BP"), "BP.L");
+        assert_eq!(id("BTC-USD"), "BTC-USD");
+        assert_eq!(Symbol::parse("Count: 100"), Err("not a ticker"));
+        assert_eq!(Symbol::parse("======="), Err("not a ticker"));
+    }
+
+    #[test]
+    fn renders_vendor_notation() {
+        let brk = Symbol::parse("NYSE:BRK-B").unwrap();
+        assert_eq!(brk.yahoo(), "BRK-B");
+        assert_eq!(brk.tradingview(), "NYSE:BRK.B");
+        assert_eq!(brk.thinkorswim(), "BRK/B");
+
+        let spx = Symbol::parse(".SPX").unwrap();
+        assert_eq!(spx.yahoo(), "^GSPC");
+        assert_eq!(spx.tradingview(), "SP:SPX");
+        assert_eq!(spx.thinkorswim(), ".SPX");
+
+        let es = Symbol::parse("ES=F").unwrap();
+        assert_eq!(es.tradingview(), "ES1!");
+        assert_eq!(es.thinkorswim(), "/ES");
+
+        let rci = Symbol::parse("TSX:RCI.B").unwrap();
+        assert_eq!(rci.yahoo(), "RCI-B.TO");
+        assert_eq!(rci.tradingview(), "TSX:RCI.B");
+        assert_eq!(rci.market().map(|market| market.benchmark), Some("XIU.TO"));
+
+        assert_eq!(yahoo("SPY"), "SPY");
+    }
+}
@@ -0,0 +1,210 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use crate::correlation::{Cluster, cluster, correlation_matrix, daily_returns};
+use crate::store::Store;
+
+/// Most traded tickers (by dollar volume over the window) that get clustered;
+/// average linkage is cubic in the universe size.
+const MAX_TICKERS: usize = 500;
+
+/// Clusterings are reused for this long per universe, window and threshold;
+/// candles only change once a day.
+const CACHE_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// (hash of the sorted tickers, window, threshold bits)
+type ClusteringKey = (u64, usize, u64);
+type ClusteringCache = HashMap<ClusteringKey, (Instant, Arc<Clustering>)>;
+
+static CACHE: LazyLock<Mutex<ClusteringCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Correlation clusters of the universe, before tags are attached.
+struct Clustering {
+    tickers: Vec<String>,
+    clusters: Vec<Cluster>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscoveryParams {
+    /// Number of trailing daily returns to correlate.
+    #[serde(default = "default_window")]
+    pub window: usize,
+    /// Minimum average correlation for two groups to merge.
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+    #[serde(default = "default_min_size")]
+    pub min_size: usize,
+    /// Only return clusters whose members share no tag.
+    #[serde(default)]
+    pub untagged_only: bool,
+}
+
+fn default_window() -> usize {
+    63
+}
+
+fn default_threshold() -> f64 {
+    0.6
+}
+
+fn default_min_size() -> usize {
+    3
+}
+
+#[derive(Debug, Serialize)]
+pub struct ThemeDiscovery {
+    pub window: usize,
+    pub threshold: f64,
+    pub tickers_considered: usize,
+    pub clusters: Vec<CandidateTheme>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CandidateTheme {
+    pub members: Vec<ClusterMember>,
+    pub avg_correlation: f64,
+    /// Tags carried by every member.
+    pub shared_tags: Vec<String>,
+    /// Most common tag among members and the fraction of members carrying it.
+    pub dominant_tag: Option<String>,
+    pub dominant_tag_coverage: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClusterMember {
+    pub ticker: String,
+    pub tags: Vec<String>,
+}
+
+/// Clusters the stored universe by trailing return correlation and compares
+/// each cluster with the tags already assigned to its members.
+pub async fn discover_themes(
+    store: &Store,
+    params: &DiscoveryParams,
+) -> anyhow::Result<ThemeDiscovery> {
+    let stock_tags = store.list_stock_tags().await?;
+    let untagged = store.list_untagged_stocks().await?;
+    let tags_by_ticker = stock_tags
+        .into_iter()
+        .map(|st| (st.ticker, st.tags.into_iter().map(|t| t.name).collect_vec()))
+        .chain(untagged.into_iter().map(|ticker| (ticker, Vec::new())))
+        .collect::<HashMap<_, _>>();
+
+    let clustering = clustering(store, tags_by_ticker.keys(), params).await?;
+    let clusters = clustering
+        .clusters
+        .iter()
+        .filter(|c| c.members.len() >= params.min_size)
+        .map(|c| {
+            let members = c
+                .members
+                .iter()
+                .map(|&idx| ClusterMember {
+                    ticker: clustering.tickers[idx].clone(),
+                    tags: tags_by_ticker
+                        .get(&clustering.tickers[idx])
+                        .cloned()
+                        .unwrap_or_default(),
+                })
+                .collect_vec();
+            candidate_theme(members, c.avg_correlation)
+        })
+        .filter(|theme| !params.untagged_only || theme.shared_tags.is_empty())
+        .collect();
+
+    Ok(ThemeDiscovery {
+        window: params.window,
+        threshold: params.threshold,
+        tickers_considered: clustering.tickers.len(),
+        clusters,
+    })
+}
+
+async fn clustering<'a>(
+    store: &Store,
+    tickers: impl Iterator<Item = &'a String>,
+    params: &DiscoveryParams,
+) -> anyhow::Result<Arc<Clustering>> {
+    let tickers = tickers.sorted().collect_vec();
+    // Tagging a stock adds it to the universe, which must not hit an entry
+    // computed without it.
+    let mut hasher = DefaultHasher::new();
+    tickers.hash(&mut hasher);
+    let key = (hasher.finish(), params.window, params.threshold.to_bits());
+    if let Some((computed_at, clustering)) = CACHE.lock().unwrap().get(&key)
+        && computed_at.elapsed() < CACHE_TTL
+    {
+        return Ok(clustering.clone());
+    }
+
+    let mut candidates = Vec::new();
+    for ticker in tickers {
+        let candles = store.get_candles(ticker).await?;
+        let returns = daily_returns(&candles, params.window);
+        // Require most of the window so recent IPOs don't skew the matrix.
+        if returns.len() * 4 >= params.window * 3 {
+            let dollar_volume = candles[candles.len().saturating_sub(params.window)..]
+                .iter()
+                .map(|c| c.close * c.volume as f64)
+                .sum::<f64>();
+            candidates.push((ticker.clone(), returns, dollar_volume));
+        }
+    }
+    if candidates.len() > MAX_TICKERS {
+        info!(
+            "Keeping the {MAX_TICKERS} most traded of {} tickers",
+            candidates.len()
+        );
+        candidates.sort_by(|a, b| b.2.total_cmp(&a.2));
+        candidates.truncate(MAX_TICKERS);
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    let (tickers, series): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .map(|(ticker, returns, _)| (ticker, returns))
+        .unzip();
+    info!(
+        "Correlating {} tickers over {} daily returns",
+        tickers.len(),
+        params.window
+    );
+
+    let (window, threshold) = (params.window, params.threshold);
+    let clusters = tokio::task::spawn_blocking(move || {
+        cluster(&correlation_matrix(&series, window / 2), threshold)
+    })
+    .await?;
+    let clustering = Arc::new(Clustering { tickers, clusters });
+    let mut cache = CACHE.lock().unwrap();
+    // Every universe change adds a key; drop the entries that can't be hit.
+    cache.retain(|_, (computed_at, _)| computed_at.elapsed() < CACHE_TTL);
+    cache.insert(key, (Instant::now(), clustering.clone()));
+    Ok(clustering)
+}
+
+fn candidate_theme(members: Vec<ClusterMember>, avg_correlation: f64) -> CandidateTheme {
+    let counts = members.iter().flat_map(|m| m.tags.iter().unique()).counts();
+    let shared_tags = counts
+        .iter()
+        .filter(|&(_, &count)| count == members.len())
+        .map(|(tag, _)| tag.to_string())
+        .sorted()
+        .collect_vec();
+    let dominant = counts
+        .iter()
+        .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(tag, count)| (tag.to_string(), *count as f64 / members.len() as f64));
+
+    CandidateTheme {
+        members,
+        avg_correlation: (avg_correlation * 1000.0).round() / 1000.0,
+        shared_tags,
+        dominant_tag_coverage: dominant.as_ref().map_or(0.0, |(_, cov)| *cov),
+        dominant_tag: dominant.map(|(tag, _)| tag),
+    }
+}
@@ -2,6 +2,8 @@ use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::symbol;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TagAssignment {
     pub ticker: String,
@@ -38,7 +40,7 @@ pub fn validate(assignments: &[TagAssignment]) -> Vec<ImportError> {
 pub fn normalize_assignments(assignments: Vec<TagAssignment>) -> Vec<TagAssignment> {
     let mut merged = HashMap::<String, Vec<String>>::new();
     for assignment in assignments {
-        let ticker = assignment.ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(&assignment.ticker);
         if ticker.is_empty() {
             continue;
         }
@@ -99,4 +101,22 @@ mod tests {
         assert!(rows[0].tags.is_empty());
         assert!(validate(&rows).is_empty());
     }
+
+    #[test]
+    fn merges_class_share_notations() {
+        let rows = normalize_assignments(vec![
+            TagAssignment {
+                ticker: "BRK-B".to_string(),
+                tags: vec!["Insurance".to_string()],
+            },
+            TagAssignment {
+                ticker: "brk.b".to_string(),
+                tags: vec!["Conglomerates".to_string()],
+            },
+        ]);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].ticker, "BRK.B");
+        assert_eq!(rows[0].tags.len(), 2);
+    }
 }
@@ -1,7 +1,10 @@
+pub mod discovery;
 pub mod import;
+pub mod peers;
 pub mod routes;
 pub mod stock_tags;
 pub mod store;
 pub mod suggest;
+pub mod tradingview;
 
 pub use routes::router;
@@ -0,0 +1,307 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::APP_CONFIG;
+use crate::correlation::{ReturnSeries, correlation, daily_returns};
+use crate::metrics::{StockMetrics, compute_metrics};
+use crate::rs::benchmark_for;
+use crate::rs_model::RS_MODEL;
+use crate::store::Store;
+use crate::summary::SummaryState;
+
+const TAG_WEIGHT: f64 = 0.4;
+const INDUSTRY_WEIGHT: f64 = 0.2;
+const CORRELATION_WEIGHT: f64 = 0.4;
+/// Below this correlation a candidate needs a shared tag or industry.
+const MIN_CORRELATION: f64 = 0.5;
+
+/// Trailing returns of the universe are reused for this long per window;
+/// candles only change once a day.
+const RETURNS_TTL: Duration = Duration::from_secs(30 * 60);
+
+type UniverseReturns = Arc<HashMap<String, ReturnSeries>>;
+
+static RETURNS: LazyLock<Mutex<HashMap<usize, (Instant, UniverseReturns)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerParams {
+    /// Number of trailing daily returns used for the correlation leg.
+    #[serde(default = "default_window")]
+    pub window: usize,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_window() -> usize {
+    63
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+#[derive(Debug, Serialize)]
+pub struct PeersResponse {
+    pub ticker: String,
+    pub industry: Option<String>,
+    pub tags: Vec<String>,
+    pub peers: Vec<Peer>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Peer {
+    pub ticker: String,
+    pub exchange: Option<String>,
+    pub score: f64,
+    pub shared_tags: Vec<String>,
+    pub same_industry: bool,
+    pub correlation: Option<f64>,
+    pub rs: Option<f64>,
+    pub metrics: Option<StockMetrics>,
+}
+
+/// Ranks every other stored ticker by how much it has in common with `ticker`:
+/// tag overlap (Jaccard), the same TradingView industry and trailing return
+/// correlation. RS and metrics of the kept peers come from `summary` when it
+/// serves them. Returns `None` when nothing is known about `ticker`.
+pub async fn find_peers(
+    store: &Store,
+    summary: Option<&SummaryState>,
+    ticker: &str,
+    params: &PeerParams,
+) -> anyhow::Result<Option<PeersResponse>> {
+    let stocks = store
+        .list_stocks()
+        .await?
+        .into_iter()
+        .map(|stock| (stock.ticker.clone(), stock))
+        .collect::<HashMap<_, _>>();
+    let tags_by_ticker = store
+        .list_stock_tags()
+        .await?
+        .into_iter()
+        .map(|st| {
+            let tags = st.tags.into_iter().map(|t| t.name).collect::<HashSet<_>>();
+            (st.ticker, tags)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let target_stock = store.get_stock(ticker).await?;
+    let target_tags = tags_by_ticker.get(ticker).cloned().unwrap_or_default();
+    let target_candles = store.get_candles(ticker).await?;
+    if target_stock.is_none() && target_tags.is_empty() && target_candles.is_empty() {
+        return Ok(None);
+    }
+    let target_industry = target_stock.as_ref().map(|s| s.industry.name.clone());
+    let target_returns = daily_returns(&target_candles, params.window);
+
+    let candidates = stocks
+        .keys()
+        .chain(tags_by_ticker.keys())
+        .filter(|&candidate| candidate != ticker)
+        .unique()
+        .cloned()
+        .collect_vec();
+    let returns = universe_returns(store, &candidates, params.window).await?;
+
+    let mut peers = Vec::new();
+    for candidate in candidates {
+        let stock = stocks.get(&candidate);
+        let tags = tags_by_ticker.get(&candidate);
+        let shared_tags = tags
+            .map(|tags| {
+                tags.intersection(&target_tags)
+                    .cloned()
+                    .sorted()
+                    .collect_vec()
+            })
+            .unwrap_or_default();
+        let tag_union = tags.map_or(0, |t| t.union(&target_tags).count());
+        let same_industry = matches!(
+            (stock, &target_industry),
+            (Some(stock), Some(industry)) if stock.industry.name.eq_ignore_ascii_case(industry)
+        );
+        let corr = returns
+            .get(&candidate)
+            .and_then(|series| correlation(&target_returns, series, params.window / 2));
+        let Some(score) = peer_score(shared_tags.len(), tag_union, same_industry, corr) else {
+            continue;
+        };
+
+        peers.push(Peer {
+            ticker: candidate,
+            exchange: stock.map(|s| s.exchange.clone()),
+            score: round(score, 3),
+            shared_tags,
+            same_industry,
+            correlation: corr.map(|c| round(c, 3)),
+            rs: None,
+            metrics: None,
+        });
+    }
+    rank_peers(&mut peers, params.limit);
+
+    let mut benchmarks = HashMap::new();
+    for peer in &mut peers {
+        if let Some(summary) = summary
+            && let Some((rs, metrics)) = summary.stock_stats(&peer.ticker).await
+        {
+            peer.rs = Some(round(rs, 2));
+            peer.metrics = metrics;
+            continue;
+        }
+        let candles = store.get_candles(&peer.ticker).await?;
+        let benchmark = benchmark_for(&peer.ticker);
+        if !benchmarks.contains_key(&benchmark) {
+            let candles = store.get_candles(&benchmark).await?;
+            benchmarks.insert(benchmark.clone(), candles);
+        }
+        let base_candles = &benchmarks[&benchmark];
+        peer.rs = (!candles.is_empty() && !base_candles.is_empty())
+            .then(|| round(RS_MODEL.rs_candles(&candles, base_candles), 2));
+        peer.metrics = compute_metrics(
+            &candles,
+            APP_CONFIG.metrics.adr_days,
+            APP_CONFIG.metrics.avg_volume_days,
+        );
+    }
+
+    Ok(Some(PeersResponse {
+        ticker: ticker.to_string(),
+        industry: target_industry,
+        tags: target_tags.into_iter().sorted().collect(),
+        peers,
+    }))
+}
+
+/// Weighted similarity of a candidate, or `None` when it shares no tag, no
+/// industry and isn't correlated enough to be a peer.
+fn peer_score(
+    shared_tags: usize,
+    tag_union: usize,
+    same_industry: bool,
+    corr: Option<f64>,
+) -> Option<f64> {
+    if shared_tags == 0 && !same_industry && corr.is_none_or(|c| c < MIN_CORRELATION) {
+        return None;
+    }
+    let tag_score = if tag_union == 0 {
+        0.0
+    } else {
+        shared_tags as f64 / tag_union as f64
+    };
+    Some(
+        TAG_WEIGHT * tag_score
+            + INDUSTRY_WEIGHT * f64::from(u8::from(same_industry))
+            + CORRELATION_WEIGHT * corr.unwrap_or(0.0).max(0.0),
+    )
+}
+
+/// Best score first, ties by ticker, keeping `limit` peers.
+fn rank_peers(peers: &mut Vec<Peer>, limit: usize) {
+    peers.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.ticker.cmp(&b.ticker))
+    });
+    peers.truncate(limit);
+}
+
+/// Trailing returns of `tickers`, cached per window. Tickers stored since
+/// the cache was filled are loaded and added.
+async fn universe_returns(
+    store: &Store,
+    tickers: &[String],
+    window: usize,
+) -> anyhow::Result<UniverseReturns> {
+    let cached = RETURNS
+        .lock()
+        .unwrap()
+        .get(&window)
+        .filter(|(loaded_at, _)| loaded_at.elapsed() < RETURNS_TTL)
+        .cloned();
+    let (loaded_at, mut returns) = match cached {
+        Some((loaded_at, returns)) => (loaded_at, returns),
+        None => (Instant::now(), Arc::new(HashMap::new())),
+    };
+    let missing = tickers
+        .iter()
+        .filter(|ticker| !returns.contains_key(*ticker))
+        .collect_vec();
+    if missing.is_empty() {
+        return Ok(returns);
+    }
+
+    let mut updated = HashMap::clone(&returns);
+    for ticker in missing {
+        let candles = store.get_candles(ticker).await?;
+        updated.insert(ticker.clone(), daily_returns(&candles, window));
+    }
+    returns = Arc::new(updated);
+    RETURNS
+        .lock()
+        .unwrap()
+        .insert(window, (loaded_at, returns.clone()));
+    Ok(returns)
+}
+
+fn round(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(ticker: &str, score: f64) -> Peer {
+        Peer {
+            ticker: ticker.to_string(),
+            exchange: None,
+            score,
+            shared_tags: Vec::new(),
+            same_industry: false,
+            correlation: None,
+            rs: None,
+            metrics: None,
+        }
+    }
+
+    #[test]
+    fn score_weights_tags_industry_and_correlation() {
+        // 2 of 4 tags shared, same industry, correlation 0.5.
+        let score = peer_score(2, 4, true, Some(0.5)).unwrap();
+        assert!((score - (0.4 * 0.5 + 0.2 + 0.4 * 0.5)).abs() < 1e-12);
+
+        // Negative correlation never adds to the score.
+        let score = peer_score(1, 1, false, Some(-0.9)).unwrap();
+        assert!((score - 0.4).abs() < 1e-12);
+    }
+
+    #[test]
+    fn uncorrelated_strangers_are_not_peers() {
+        assert_eq!(peer_score(0, 3, false, Some(0.49)), None);
+        assert_eq!(peer_score(0, 0, false, None), None);
+        assert!(peer_score(0, 3, false, Some(0.5)).is_some());
+        assert!(peer_score(0, 3, true, None).is_some());
+    }
+
+    #[test]
+    fn ranks_by_score_then_ticker() {
+        let mut peers = vec![
+            peer("MU", 0.3),
+            peer("AMD", 0.6),
+            peer("NVDA", 0.6),
+            peer("INTC", 0.1),
+        ];
+
+        rank_peers(&mut peers, 3);
+
+        let tickers = peers.iter().map(|p| p.ticker.as_str()).collect_vec();
+        assert_eq!(tickers, vec!["AMD", "NVDA", "MU"]);
+    }
+}
@@ -1,30 +1,36 @@
 use askama::Template;
 use axum::{
-    Json, Router,
-    extract::{Path, State},
+    Extension, Json, Router,
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::{Html, IntoResponse, Response},
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::{Html, IntoResponse},
     routing,
 };
 use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, LazyLock};
+use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::info;
 
+use crate::api_error::ApiError;
 use crate::config::APP_CONFIG;
 use crate::html_error::HtmlError;
 use crate::store::{CompanyProfile, DeleteTagResult, Store, Tag};
+use crate::summary::SummaryState;
+use crate::symbol;
+use crate::tags::discovery::{DiscoveryParams, discover_themes};
 use crate::tags::import::{ImportError, TagAssignment, normalize_assignments, parse_import};
+use crate::tags::peers::{PeerParams, find_peers};
 use crate::tags::suggest::{SuggestionStatus, TagSuggestionHandle};
+use crate::tags::tradingview::{TradingViewExportQuery, tradingview_export};
 use crate::yf::YFinance;
 
-static YF: LazyLock<YFinance> = LazyLock::new(YFinance::new);
-
 #[derive(Clone)]
 struct TagState {
     store: Arc<Store>,
+    yf: Arc<YFinance>,
     tag_suggestions: Option<TagSuggestionHandle>,
     suggestion_action_lock: Arc<Mutex<()>>,
 }
@@ -82,12 +88,7 @@ struct ImportRequest {
 }
 
 #[derive(Debug, Serialize)]
-struct ErrorResponse {
-    error: String,
-}
-
-#[derive(Debug, Serialize)]
-struct TagStockView {
+pub(crate) struct TagStockView {
     ticker: String,
     tags: Vec<Tag>,
 }
@@ -168,54 +169,9 @@ struct ApplySuggestionsResponse {
     items: Vec<ApplySuggestionItem>,
 }
 
-struct ApiError {
-    status: StatusCode,
-    message: String,
-}
-
-impl ApiError {
-    fn bad_request(message: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::BAD_REQUEST,
-            message: message.into(),
-        }
-    }
-
-    fn conflict(message: impl Into<String>) -> Self {
-        Self {
-            status: StatusCode::CONFLICT,
-            message: message.into(),
-        }
-    }
-}
-
-impl<E> From<E> for ApiError
-where
-    E: Into<anyhow::Error>,
-{
-    fn from(error: E) -> Self {
-        Self {
-            status: StatusCode::INTERNAL_SERVER_ERROR,
-            message: error.into().to_string(),
-        }
-    }
-}
-
-impl IntoResponse for ApiError {
-    fn into_response(self) -> Response {
-        (
-            self.status,
-            Json(ErrorResponse {
-                error: self.message,
-            }),
-        )
-            .into_response()
-    }
-}
-
-pub fn router(store: Arc<Store>) -> Router {
+pub fn router(store: Arc<Store>, yf: Arc<YFinance>) -> Router {
     let tag_suggestions = APP_CONFIG.tag_suggestion.clone().and_then(|config| {
-        match TagSuggestionHandle::new(config, store.clone()) {
+        match TagSuggestionHandle::new(config, store.clone(), yf.clone()) {
             Ok(handle) => {
                 info!(
                     "Tag suggestions enabled with provider={} model={}",
@@ -232,6 +188,7 @@ pub fn router(store: Arc<Store>) -> Router {
     });
     let state = TagState {
         store,
+        yf,
         tag_suggestions,
         suggestion_action_lock: Arc::new(Mutex::new(())),
     };
@@ -248,6 +205,10 @@ pub fn router(store: Arc<Store>) -> Router {
         )
         .route("/api/stock-tags", routing::get(list_stock_tags))
         .route("/api/stock-tags/tags", routing::put(set_stock_tags))
+        .route(
+            "/api/stock-tags/export/tradingview",
+            routing::get(export_tradingview),
+        )
         .route(
             "/api/stock-tags/suggest",
             routing::post(queue_tag_suggestion),
@@ -278,6 +239,11 @@ pub fn router(store: Arc<Store>) -> Router {
         )
         .route("/api/tag-import/preview", routing::post(preview_import))
         .route("/api/tag-import", routing::post(apply_import))
+        .route("/api/peers/{ticker}", routing::get(get_peers))
+        .route(
+            "/api/tag-discovery/clusters",
+            routing::get(discover_clusters),
+        )
         .with_state(state)
 }
 
@@ -381,6 +347,21 @@ async fn list_stock_tags(State(state): State<TagState>) -> Result<impl IntoRespo
     Ok(Json(stock_views(&state.store).await?))
 }
 
+/// GET /api/stock-tags/export/tradingview?by=category&order=rs&tags=AI,Cloud
+async fn export_tradingview(
+    State(state): State<TagState>,
+    Query(query): Query<TradingViewExportQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let content = tradingview_export(&state.store, &state.yf, &query).await?;
+    Ok((
+        [
+            (CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (CONTENT_DISPOSITION, "attachment; filename=\"tags.txt\""),
+        ],
+        content,
+    ))
+}
+
 async fn list_untagged_stocks(
     State(state): State<TagState>,
 ) -> Result<impl IntoResponse, ApiError> {
@@ -411,10 +392,10 @@ async fn set_stock_tags(
         .map(str::to_string)
         .collect::<Vec<_>>();
     if !unknown_tags.is_empty() {
-        return Err(ApiError::bad_request(format!(
-            "Unknown tags: {}",
-            unknown_tags.join(", ")
-        )));
+        return Err(
+            ApiError::bad_request(format!("Unknown tags: {}", unknown_tags.join(", ")))
+                .with_details(unknown_tags),
+        );
     }
 
     let result = state
@@ -422,7 +403,7 @@ async fn set_stock_tags(
         .set_tags_for_stock(&req.ticker, &req.tags)
         .await?;
     Ok(Json(SetStockTagsResponse {
-        ticker: req.ticker.trim().to_uppercase(),
+        ticker: symbol::canonical(&req.ticker),
         set_tags: result.set_tags,
         removed_tags: result.removed_tags,
     }))
@@ -441,7 +422,10 @@ async fn queue_tag_suggestion(
     for ticker in tickers {
         match queue_one_tag_suggestion(&state, handle, &ticker).await {
             Ok(item) => items.push(item),
-            Err(err) => items.push(BatchSuggestionItem::request_error(ticker, err.message)),
+            Err(err) => items.push(BatchSuggestionItem::request_error(
+                ticker,
+                err.message().to_string(),
+            )),
         }
     }
 
@@ -462,7 +446,7 @@ async fn queue_one_tag_suggestion(
 
     let suggestion = state
         .store
-        .get_tag_suggestion(&ticker)
+        .get_tag_suggestion(ticker)
         .await?
         .ok_or_else(|| ApiError::bad_request("Failed to queue tag suggestion"))?;
     Ok(BatchSuggestionItem::from(suggestion))
@@ -585,7 +569,7 @@ async fn get_company_profile(
     let ticker = normalize_ticker(&ticker)?;
     let profile = match state.store.get_company_profile(&ticker).await? {
         Some(profile) => profile,
-        None => fetch_and_cache_company_profile(&state.store, &ticker).await?,
+        None => fetch_and_cache_company_profile(&state.store, &state.yf, &ticker).await?,
     };
     Ok(Json(profile))
 }
@@ -596,17 +580,18 @@ async fn refresh_company_profile(
 ) -> Result<impl IntoResponse, ApiError> {
     let ticker = normalize_ticker(&ticker)?;
     Ok(Json(
-        fetch_and_cache_company_profile(&state.store, &ticker).await?,
+        fetch_and_cache_company_profile(&state.store, &state.yf, &ticker).await?,
     ))
 }
 
 async fn fetch_and_cache_company_profile(
     store: &Store,
+    yf: &YFinance,
     ticker: &str,
 ) -> Result<CompanyProfile, ApiError> {
-    let yf_profile = YF.fetch_company_profile(ticker).await?;
+    let yf_profile = yf.fetch_company_profile(ticker).await?;
     let profile = CompanyProfile {
-        ticker: yf_profile.symbol.trim().to_uppercase(),
+        ticker: symbol::canonical(&yf_profile.symbol),
         summary: yf_profile
             .summary
             .map(|summary| summary.split_whitespace().collect::<Vec<_>>().join(" "))
@@ -621,7 +606,7 @@ async fn fetch_and_cache_company_profile(
 }
 
 fn normalize_ticker(ticker: &str) -> Result<String, ApiError> {
-    let ticker = ticker.trim().to_uppercase();
+    let ticker = symbol::canonical(ticker);
     if ticker.is_empty() {
         return Err(ApiError::bad_request("Ticker is required"));
     }
@@ -717,7 +702,7 @@ async fn apply_import(
     let tags = state.store.list_tags().await?;
     let errors = validate_import_assignments(&assignments, &tags);
     if !errors.is_empty() {
-        return Err(ApiError::bad_request("Import has validation errors"));
+        return Err(ApiError::bad_request("Import has validation errors").with_details(errors));
     }
 
     let import_rows = assignments
@@ -733,6 +718,38 @@ async fn apply_import(
     }))
 }
 
+async fn get_peers(
+    State(state): State<TagState>,
+    summary: Option<Extension<Arc<SummaryState>>>,
+    Path(ticker): Path<String>,
+    Query(params): Query<PeerParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let ticker = normalize_ticker(&ticker)?;
+    if params.window < 10 {
+        return Err(ApiError::bad_request("Window must be at least 10 days"));
+    }
+
+    let summary = summary.as_ref().map(|Extension(summary)| summary.as_ref());
+    match find_peers(&state.store, summary, &ticker, &params).await? {
+        Some(peers) => Ok(Json(peers)),
+        None => Err(ApiError::not_found(format!("Unknown ticker: {ticker}"))),
+    }
+}
+
+async fn discover_clusters(
+    State(state): State<TagState>,
+    Query(params): Query<DiscoveryParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    if params.window < 10 {
+        return Err(ApiError::bad_request("Window must be at least 10 days"));
+    }
+    if !(-1.0..=1.0).contains(&params.threshold) {
+        return Err(ApiError::bad_request("Threshold must be between -1 and 1"));
+    }
+
+    Ok(Json(discover_themes(&state.store, &params).await?))
+}
+
 fn parse_request_import(req: &ImportRequest) -> Result<Vec<TagAssignment>, ApiError> {
     if req.content.trim().is_empty() {
         return Err(ApiError::bad_request("Import content is required"));
@@ -769,6 +786,7 @@ fn validate_import_assignments(assignments: &[TagAssignment], tags: &[Tag]) -> V
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -814,7 +832,7 @@ mod tests {
     }
 }
 
-async fn stock_views(store: &Store) -> sqlx::Result<Vec<TagStockView>> {
+pub(crate) async fn stock_views(store: &Store) -> sqlx::Result<Vec<TagStockView>> {
     Ok(store
         .list_stock_tags()
         .await?
@@ -1,6 +1,6 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
-use std::sync::{Arc, LazyLock};
+use std::sync::Arc;
 
 use askama::Template;
 use axum::{
@@ -11,24 +11,28 @@ use axum::{
     response::{Html, IntoResponse, Response},
 };
 use futures::{StreamExt, stream};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+use crate::api_error::ApiError;
 use crate::config::APP_CONFIG;
 use crate::fetch_candles;
 use crate::html_error::HtmlError;
 use crate::metrics;
+use crate::rs::benchmark_for;
+use crate::rs_model::{RS_COMPARE_MODELS, RS_MODEL, RsModel};
 use crate::store::{StockTags, Store, Tag, TagCategory};
-use crate::util::compute_rs_candles;
+use crate::symbol;
 use crate::yf::YFinance;
 use tracing::warn;
 
-static YF: LazyLock<YFinance> = LazyLock::new(YFinance::new);
 const METRIC_STREAM_CONCURRENCY: usize = 2;
 
 #[derive(Template)]
 #[template(path = "stock_tags.html")]
 struct StockTagsTemplate {
     benchmark_symbol: String,
+    rs_model: String,
     real_tag_count: usize,
     tagged_stock_count: usize,
     untagged_stock_count: usize,
@@ -68,15 +72,26 @@ struct TickerInfoView {
 #[derive(Debug, Deserialize)]
 pub struct MetricsQuery {
     tickers: String,
+    /// Comma separated RS presets to report next to the configured model;
+    /// defaults to `rs.compare` from the config.
+    models: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct StockTagMetricView {
     rs: f64,
+    /// RS under the configured model followed by each compared model.
+    rs_models: Vec<ModelRs>,
     adr_pct: Option<f64>,
     avg_volume: Option<u64>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelRs {
+    model: String,
+    rs: f64,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct StockTagMetricStreamRow {
     ticker: String,
@@ -93,8 +108,9 @@ pub async fn stock_tags_home(
 
 pub async fn stock_tag_metrics_stream(
     Extension(store): Extension<Arc<Store>>,
+    Extension(yf): Extension<Arc<YFinance>>,
     Query(query): Query<MetricsQuery>,
-) -> Result<Response, HtmlError> {
+) -> Result<Response, ApiError> {
     let tickers = parse_metric_tickers(&query.tickers);
     if tickers.is_empty() {
         return Ok((
@@ -104,14 +120,31 @@ pub async fn stock_tag_metrics_stream(
             .into_response());
     }
 
-    let base_candles = Arc::new(fetch_candles(&store, &YF, &APP_CONFIG.base_ticker).await?);
+    let compare = match &query.models {
+        Some(names) => RsModel::parse_presets(names)?,
+        None => RS_COMPARE_MODELS.clone(),
+    };
+    let models = Arc::new(
+        std::iter::once(RS_MODEL.clone())
+            .chain(compare.into_iter().filter(|m| *m != *RS_MODEL))
+            .collect::<Vec<_>>(),
+    );
+    let mut benchmarks = HashMap::new();
+    let wanted = tickers.iter().map(|ticker| benchmark_for(ticker)).unique();
+    for benchmark in wanted.collect_vec() {
+        let candles = fetch_candles(&store, &yf, &benchmark).await?;
+        benchmarks.insert(benchmark, candles);
+    }
+    let benchmarks = Arc::new(benchmarks);
 
     let rows = stream::iter(tickers)
         .map(move |ticker| {
             let store = Arc::clone(&store);
-            let base_candles = Arc::clone(&base_candles);
+            let yf = Arc::clone(&yf);
+            let benchmarks = Arc::clone(&benchmarks);
+            let models = Arc::clone(&models);
             async move {
-                let row = metric_stream_row(store, base_candles, ticker).await;
+                let row = metric_stream_row(store, yf, benchmarks, models, ticker).await;
                 let line = match serde_json::to_string(&row) {
                     Ok(json) => json + "\n",
                     Err(err) => format!(
@@ -149,6 +182,7 @@ async fn build_template(store: &Store) -> anyhow::Result<StockTagsTemplate> {
 
     Ok(StockTagsTemplate {
         benchmark_symbol: APP_CONFIG.base_ticker.to_uppercase(),
+        rs_model: RS_MODEL.to_string(),
         real_tag_count,
         tagged_stock_count,
         untagged_stock_count,
@@ -178,10 +212,13 @@ fn collect_unique_tickers(stock_tags: &[StockTags], untagged: &[String]) -> Vec<
 
 async fn metric_stream_row(
     store: Arc<Store>,
-    base_candles: Arc<Vec<crate::yf::Candle>>,
+    yf: Arc<YFinance>,
+    benchmarks: Arc<HashMap<String, Vec<crate::yf::Candle>>>,
+    models: Arc<Vec<RsModel>>,
     ticker: String,
 ) -> StockTagMetricStreamRow {
-    match stock_tag_metric(&store, &ticker, &base_candles).await {
+    let base_candles = &benchmarks[&benchmark_for(&ticker)];
+    match stock_tag_metric(&store, &yf, &ticker, base_candles, &models).await {
         Ok(metric) => StockTagMetricStreamRow {
             ticker,
             metric: Some(metric),
@@ -200,17 +237,27 @@ async fn metric_stream_row(
 
 async fn stock_tag_metric(
     store: &Store,
+    yf: &YFinance,
     ticker: &str,
     base_candles: &[crate::yf::Candle],
+    models: &[RsModel],
 ) -> anyhow::Result<StockTagMetricView> {
-    let candles = fetch_candles(store, &YF, ticker).await?;
+    let candles = fetch_candles(store, yf, ticker).await?;
     let metrics = metrics::compute_metrics(
         &candles,
         APP_CONFIG.metrics.adr_days,
         APP_CONFIG.metrics.avg_volume_days,
     );
+    let rs_models = models
+        .iter()
+        .map(|model| ModelRs {
+            model: model.name.clone(),
+            rs: round_rs(model.rs_candles(&candles, base_candles)),
+        })
+        .collect::<Vec<_>>();
     Ok(StockTagMetricView {
-        rs: round_rs(compute_rs_candles(&candles, base_candles)),
+        rs: rs_models[0].rs,
+        rs_models,
         adr_pct: metrics.map(|m| m.adr_pct),
         avg_volume: metrics.map(|m| m.avg_volume),
     })
@@ -240,7 +287,7 @@ fn parse_metric_tickers(input: &str) -> Vec<String> {
     let mut tickers = Vec::new();
 
     for ticker in input.split(',') {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         if ticker.is_empty() {
             continue;
         }
@@ -3,6 +3,7 @@ use sqlx::Sqlite;
 use std::collections::HashMap;
 
 use crate::store::{DeleteTagResult, StockTags, Store, Tag, TagCategory};
+use crate::symbol;
 
 #[derive(Debug, Clone, Default)]
 pub struct ReplaceTagsResult {
@@ -253,7 +254,7 @@ impl Store {
         ticker: &str,
         tags: &[String],
     ) -> sqlx::Result<ReplaceTagsResult> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         let tags = normalize_tag_names(tags);
         let mut result = ReplaceTagsResult::default();
 
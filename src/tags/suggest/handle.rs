@@ -1,5 +1,5 @@
 use std::collections::HashSet;
-use std::sync::{Arc, LazyLock};
+use std::sync::Arc;
 use std::time::Instant;
 
 use anyhow::anyhow;
@@ -10,6 +10,7 @@ use tracing::{debug, error, info, warn};
 
 use crate::config::{TagSuggestionConfig, TagSuggestionProvider};
 use crate::store::{CompanyProfile, Store};
+use crate::symbol;
 use crate::yf::YFinance;
 
 use super::SuggestionInput;
@@ -18,8 +19,6 @@ use super::prompt::{build_prompt, suggestion_input};
 use super::providers::{call_deepseek, call_ollama};
 use super::providers::{model_for_config, provider_name, validate_config};
 
-static YF: LazyLock<YFinance> = LazyLock::new(YFinance::new);
-
 #[derive(Clone)]
 pub struct TagSuggestionHandle {
     config: Arc<TagSuggestionConfig>,
@@ -35,12 +34,17 @@ pub(super) struct SuggestionJob {
 struct TagSuggestionActor {
     config: Arc<TagSuggestionConfig>,
     store: Arc<Store>,
+    yf: Arc<YFinance>,
     client: Client,
     queued_tickers: Arc<Mutex<HashSet<String>>>,
 }
 
 impl TagSuggestionHandle {
-    pub fn new(config: TagSuggestionConfig, store: Arc<Store>) -> anyhow::Result<Self> {
+    pub fn new(
+        config: TagSuggestionConfig,
+        store: Arc<Store>,
+        yf: Arc<YFinance>,
+    ) -> anyhow::Result<Self> {
         validate_config(&config)?;
         let config = Arc::new(config);
         let (sender, mut receiver) = mpsc::unbounded_channel::<SuggestionJob>();
@@ -48,6 +52,7 @@ impl TagSuggestionHandle {
         let actor = TagSuggestionActor {
             config: Arc::clone(&config),
             store: Arc::clone(&store),
+            yf,
             client: Client::new(),
             queued_tickers: Arc::clone(&queued_tickers),
         };
@@ -75,7 +80,7 @@ impl TagSuggestionHandle {
     }
 
     pub async fn enqueue(&self, ticker: String) -> anyhow::Result<bool> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(&ticker);
         let provider = self.provider_name().to_string();
         let model = self.model()?;
         {
@@ -230,9 +235,9 @@ impl TagSuggestionActor {
         &self,
         ticker: &str,
     ) -> anyhow::Result<CompanyProfile> {
-        let yf_profile = YF.fetch_company_profile(ticker).await?;
+        let yf_profile = self.yf.fetch_company_profile(ticker).await?;
         let profile = CompanyProfile {
-            ticker: yf_profile.symbol.trim().to_uppercase(),
+            ticker: symbol::canonical(&yf_profile.symbol),
             summary: yf_profile
                 .summary
                 .map(|summary| summary.split_whitespace().collect::<Vec<_>>().join(" "))
@@ -1,6 +1,7 @@
 use chrono::Local;
 
 use crate::store::Store;
+use crate::symbol;
 
 use super::{CachedTagSuggestion, SuggestionInput, SuggestionStatus};
 
@@ -9,7 +10,7 @@ impl Store {
         &self,
         ticker: &str,
     ) -> sqlx::Result<Option<CachedTagSuggestion>> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         let row = sqlx::query!(
             r#"
             SELECT
@@ -50,7 +51,7 @@ impl Store {
         provider: &str,
         model: &str,
     ) -> sqlx::Result<()> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         let requested_at = Local::now();
         sqlx::query!(
             r#"
@@ -84,7 +85,7 @@ impl Store {
         ticker: &str,
         input: &SuggestionInput,
     ) -> sqlx::Result<bool> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         let result = sqlx::query!(
             r#"
             UPDATE tag_suggestions
@@ -105,7 +106,7 @@ impl Store {
         ticker: &str,
         suggested_tags: &[String],
     ) -> sqlx::Result<bool> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         let suggested_tags =
             serde_json::to_string(suggested_tags).unwrap_or_else(|_| "[]".to_string());
         let generated_at = Local::now();
@@ -133,7 +134,7 @@ impl Store {
         ticker: &str,
         error: &str,
     ) -> sqlx::Result<bool> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         let generated_at = Local::now();
         let result = sqlx::query!(
             r#"
@@ -154,7 +155,7 @@ impl Store {
     }
 
     pub async fn ignore_tag_suggestion(&self, ticker: &str) -> sqlx::Result<bool> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         let result = sqlx::query!(
             r#"
             UPDATE tag_suggestions
@@ -195,7 +196,7 @@ impl Store {
     }
 
     pub async fn delete_tag_suggestion(&self, ticker: &str) -> sqlx::Result<()> {
-        let ticker = ticker.trim().to_uppercase();
+        let ticker = symbol::canonical(ticker);
         sqlx::query!(
             r#"
             DELETE FROM tag_suggestions
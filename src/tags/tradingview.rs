@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+
+use futures::{StreamExt, stream};
+use itertools::Itertools;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::fetch_candles;
+use crate::rs::benchmark_for;
+use crate::rs_model::RS_MODEL;
+use crate::store::{StockTags, Store, Tag, TagCategory};
+use crate::watchlist::formats::WatchlistFormat;
+use crate::watchlist::store::WatchlistItem;
+use crate::yf::YFinance;
+
+const RS_CONCURRENCY: usize = 4;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionBy {
+    #[default]
+    Tag,
+    Category,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TickerOrder {
+    #[default]
+    Ticker,
+    /// Strongest first, and sections by their members' average RS.
+    Rs,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TradingViewExportQuery {
+    #[serde(default)]
+    pub by: SectionBy,
+    #[serde(default)]
+    pub order: TickerOrder,
+    /// Comma separated tag names to export; every tag when missing.
+    pub tags: Option<String>,
+}
+
+/// The tagged stocks as a TradingView watchlist: a `###Section` per tag or
+/// category with `EXCHANGE:TICKER` entries. TradingView keeps a symbol once
+/// per list, so a ticker is only listed under the first section it falls in.
+pub async fn tradingview_export(
+    store: &Store,
+    yf: &YFinance,
+    query: &TradingViewExportQuery,
+) -> anyhow::Result<String> {
+    let tags = store.list_tags().await?;
+    let categories = store.list_tag_categories().await?;
+    let stock_tags = store.list_stock_tags().await?;
+    let exchanges = store
+        .list_stocks()
+        .await?
+        .into_iter()
+        .map(|stock| (stock.ticker, stock.exchange))
+        .collect::<HashMap<_, _>>();
+    let wanted = query.tags.as_deref().map(|tags| {
+        tags.split(',')
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect::<HashSet<_>>()
+    });
+
+    let sections = tag_sections(&tags, &categories, &stock_tags, query.by, wanted.as_ref());
+    let rs = match query.order {
+        TickerOrder::Rs => {
+            let tickers = sections.iter().flat_map(|(_, t)| t).unique().cloned();
+            stock_rs(store, yf, tickers.collect()).await?
+        }
+        TickerOrder::Ticker => HashMap::new(),
+    };
+    let exchanges = &exchanges;
+    let items = arrange(sections, &rs, query.order)
+        .into_iter()
+        .flat_map(|(section, tickers)| {
+            tickers.into_iter().map(move |ticker| WatchlistItem {
+                exchange: exchanges.get(&ticker).cloned().unwrap_or_default(),
+                ticker,
+                section: section.clone(),
+            })
+        })
+        .collect_vec();
+    Ok(WatchlistFormat::Tradingview.render(&items))
+}
+
+/// Tickers per tag or category, sections in name (or category) order.
+fn tag_sections(
+    tags: &[Tag],
+    categories: &[TagCategory],
+    stock_tags: &[StockTags],
+    by: SectionBy,
+    wanted: Option<&HashSet<String>>,
+) -> Vec<(String, Vec<String>)> {
+    let section_of = |tag: &Tag| match by {
+        SectionBy::Tag => Some(((0, tag.name.to_lowercase()), tag.name.clone())),
+        SectionBy::Category => categories
+            .iter()
+            .find(|category| category.id == tag.category_id)
+            .map(|category| {
+                (
+                    (category.sort_order, category.name.to_lowercase()),
+                    category.name.clone(),
+                )
+            }),
+    };
+    let included = tags
+        .iter()
+        .filter(|tag| wanted.is_none_or(|wanted| wanted.contains(&tag.name.to_lowercase())))
+        .map(|tag| tag.id)
+        .collect::<HashSet<_>>();
+
+    let mut sections = HashMap::<_, (String, Vec<String>)>::new();
+    for stock in stock_tags {
+        for tag in stock.tags.iter().filter(|tag| included.contains(&tag.id)) {
+            let Some((key, name)) = section_of(tag) else {
+                continue;
+            };
+            let (_, tickers) = sections.entry(key).or_insert_with(|| (name, Vec::new()));
+            if !tickers.contains(&stock.ticker) {
+                tickers.push(stock.ticker.clone());
+            }
+        }
+    }
+    sections
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, section)| section)
+        .collect()
+}
+
+/// Orders sections and their tickers, then drops tickers already listed
+/// under an earlier section.
+fn arrange(
+    mut sections: Vec<(String, Vec<String>)>,
+    rs: &HashMap<String, f64>,
+    order: TickerOrder,
+) -> Vec<(String, Vec<String>)> {
+    // Higher RS first, tickers without one last.
+    let by_rs =
+        |a: Option<f64>, b: Option<f64>| b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal);
+    for (_, tickers) in &mut sections {
+        match order {
+            TickerOrder::Ticker => tickers.sort(),
+            TickerOrder::Rs => tickers.sort_by(|a, b| {
+                by_rs(rs.get(a).copied(), rs.get(b).copied()).then_with(|| a.cmp(b))
+            }),
+        }
+    }
+    if order == TickerOrder::Rs {
+        let average = |tickers: &[String]| {
+            let values = tickers.iter().filter_map(|t| rs.get(t)).collect_vec();
+            (!values.is_empty()).then(|| values.iter().copied().sum::<f64>() / values.len() as f64)
+        };
+        sections.sort_by(|(_, a), (_, b)| by_rs(average(a), average(b)));
+    }
+
+    let mut listed = HashSet::new();
+    sections
+        .into_iter()
+        .map(|(name, tickers)| {
+            let tickers = tickers
+                .into_iter()
+                .filter(|ticker| listed.insert(ticker.clone()))
+                .collect_vec();
+            (name, tickers)
+        })
+        .filter(|(_, tickers)| !tickers.is_empty())
+        .collect()
+}
+
+async fn stock_rs(
+    store: &Store,
+    yf: &YFinance,
+    tickers: Vec<String>,
+) -> anyhow::Result<HashMap<String, f64>> {
+    let mut benchmarks = HashMap::new();
+    let wanted = tickers.iter().map(|ticker| benchmark_for(ticker)).unique();
+    for benchmark in wanted.collect_vec() {
+        let candles = fetch_candles(store, yf, &benchmark).await?;
+        benchmarks.insert(benchmark, candles);
+    }
+    let benchmarks = &benchmarks;
+    Ok(stream::iter(tickers)
+        .map(|ticker| async move {
+            let base_candles = &benchmarks[&benchmark_for(&ticker)];
+            match fetch_candles(store, yf, &ticker).await {
+                Ok(candles) => Some((ticker, RS_MODEL.rs_candles(&candles, base_candles))),
+                Err(err) => {
+                    warn!("No RS for {ticker} in the TradingView export: {err}");
+                    None
+                }
+            }
+        })
+        .buffered(RS_CONCURRENCY)
+        .filter_map(|row| async move { row.filter(|(_, rs)| rs.is_finite()) })
+        .collect()
+        .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(id: i64, name: &str, category_id: i64) -> Tag {
+        Tag {
+            id,
+            name: name.into(),
+            category_id,
+            stock_count: 0,
+            assigned_at: None,
+        }
+    }
+
+    fn stock(ticker: &str, tags: &[&Tag]) -> StockTags {
+        StockTags {
+            ticker: ticker.into(),
+            tags: tags.iter().map(|&tag| tag.clone()).collect(),
+        }
+    }
+
+    #[test]
+    fn sections_by_tag_category_and_filter() {
+        let ai = tag(1, "AI", 2);
+        let cloud = tag(2, "Cloud", 2);
+        let nuclear = tag(3, "Nuclear", 1);
+        let tags = [ai.clone(), cloud.clone(), nuclear.clone()];
+        let categories = [
+            TagCategory {
+                id: 1,
+                name: "Energy".into(),
+                sort_order: 1,
+                stock_count: 0,
+            },
+            TagCategory {
+                id: 2,
+                name: "Tech".into(),
+                sort_order: 0,
+                stock_count: 0,
+            },
+        ];
+        let stocks = [
+            stock("MSFT", &[&ai, &cloud]),
+            stock("NVDA", &[&ai]),
+            stock("CEG", &[&nuclear, &ai]),
+        ];
+
+        let by_tag = tag_sections(&tags, &categories, &stocks, SectionBy::Tag, None);
+        assert_eq!(
+            by_tag,
+            [
+                (
+                    "AI".to_string(),
+                    vec!["MSFT".to_string(), "NVDA".into(), "CEG".into()]
+                ),
+                ("Cloud".to_string(), vec!["MSFT".to_string()]),
+                ("Nuclear".to_string(), vec!["CEG".to_string()]),
+            ]
+        );
+
+        let by_category = tag_sections(&tags, &categories, &stocks, SectionBy::Category, None);
+        assert_eq!(by_category[0].0, "Tech");
+        assert_eq!(by_category[0].1, ["MSFT", "NVDA", "CEG"]);
+        assert_eq!(by_category[1].0, "Energy");
+
+        let wanted = HashSet::from(["nuclear".to_string()]);
+        let filtered = tag_sections(&tags, &categories, &stocks, SectionBy::Tag, Some(&wanted));
+        assert_eq!(filtered, [("Nuclear".to_string(), vec!["CEG".to_string()])]);
+    }
+
+    #[test]
+    fn arrange_orders_by_rs_and_lists_tickers_once() {
+        let sections = vec![
+            (
+                "AI".to_string(),
+                vec!["MSFT".to_string(), "NVDA".into(), "CEG".into()],
+            ),
+            ("Nuclear".to_string(), vec!["CEG".to_string(), "SMR".into()]),
+        ];
+        let rs = HashMap::from([
+            ("MSFT".to_string(), 60.0),
+            ("NVDA".to_string(), 90.0),
+            ("CEG".to_string(), 95.0),
+            ("SMR".to_string(), 99.0),
+        ]);
+
+        let by_rs = arrange(sections.clone(), &rs, TickerOrder::Rs);
+        assert_eq!(
+            by_rs,
+            [
+                ("Nuclear".to_string(), vec!["SMR".to_string(), "CEG".into()]),
+                ("AI".to_string(), vec!["NVDA".to_string(), "MSFT".into()]),
+            ]
+        );
+
+        let by_ticker = arrange(sections, &rs, TickerOrder::Ticker);
+        assert_eq!(by_ticker[0].1, ["CEG", "MSFT", "NVDA"]);
+        assert_eq!(by_ticker[1].1, ["SMR"]);
+    }
+}
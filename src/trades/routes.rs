@@ -1,4 +1,3 @@
-use anyhow::Context;
 use askama::Template;
 use axum::{
     Json, Router,
@@ -11,16 +10,18 @@ use chrono::DateTime;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::net::TcpListener;
+use tokio::sync::RwLock;
 use tracing::info;
 
 use super::candles::fetch_hourly_candles;
+use crate::api_error::ApiError;
 use crate::config::APP_CONFIG;
 use crate::html_error::HtmlError;
-use crate::no_cache;
 use crate::store::Store;
-use crate::trades::TradeView;
+use crate::trades::parser::parse_tos_csv;
+use crate::trades::{TradeView, build_views};
 use crate::yf::YFinance;
+use crate::{no_cache, serve};
 
 // ── Shared state ──────────────────────────────────────────────────────────────
 
@@ -28,7 +29,20 @@ use crate::yf::YFinance;
 pub struct AppState {
     pub store: Arc<Store>,
     pub yf: Arc<YFinance>,
-    pub html: String,
+    pub benchmark: String,
+    /// Replaced by `POST /api/trades`.
+    pub trades: Arc<RwLock<Vec<TradeView>>>,
+}
+
+impl AppState {
+    pub fn new(store: Arc<Store>, yf: Arc<YFinance>, trade_views: Vec<TradeView>) -> Self {
+        Self {
+            store,
+            yf,
+            benchmark: APP_CONFIG.base_ticker.to_uppercase(),
+            trades: Arc::new(RwLock::new(trade_views)),
+        }
+    }
 }
 
 // ── Query params ──────────────────────────────────────────────────────────────
@@ -106,19 +120,57 @@ fn calc_ema(candles: &[crate::yf::Candle], period: usize, ts_offset: i64) -> Vec
 
 // ── Handlers ──────────────────────────────────────────────────────────────────
 
-pub async fn home(State(state): State<AppState>) -> impl IntoResponse {
-    Html(state.html.clone())
+pub async fn home(State(state): State<AppState>) -> Result<impl IntoResponse, HtmlError> {
+    let cfg = &APP_CONFIG.trade_analysis;
+    // Expected hourly candles: calendar days → trading days (×5/7), 6.5h/day, 10% holiday buffer
+    let min_hourly_candles =
+        (cfg.hourly_chart_days + cfg.hourly_chart_post_days) as f64 * (5.0 / 7.0) * 6.5 * 0.9;
+    let tz_offset_secs = chrono::Local::now().offset().local_minus_utc();
+    let html = TradeAnalyzerTemplate {
+        trades_json: serde_json::to_string(&*state.trades.read().await)?,
+        benchmark_json: serde_json::to_string(&state.benchmark)?,
+        min_hourly_candles,
+        tz_offset_secs,
+    }
+    .render()?;
+    Ok(Html(html))
+}
+
+#[derive(Serialize)]
+pub struct TradesUpload {
+    trades: usize,
+    open: usize,
+}
+
+/// POST /api/trades with a ThinkorSwim account statement CSV as the body.
+/// Replaces the trades shown on the page.
+pub async fn upload_trades(
+    State(state): State<AppState>,
+    body: String,
+) -> Result<Json<TradesUpload>, ApiError> {
+    let trades = parse_tos_csv(&body);
+    if trades.is_empty() {
+        return Err(ApiError::bad_request("No trades found in the uploaded CSV"));
+    }
+    let upload = TradesUpload {
+        trades: trades.len(),
+        open: trades.iter().filter(|t| t.is_open()).count(),
+    };
+    let views = build_views(&trades, &APP_CONFIG.trade_analysis);
+    *state.trades.write().await = views.trade_views;
+    info!("Loaded {} trades ({} open)", upload.trades, upload.open);
+    Ok(Json(upload))
 }
 
 pub async fn daily_candles(
     State(state): State<AppState>,
     Path(ticker): Path<String>,
     Query(q): Query<CandleQuery>,
-) -> Result<impl IntoResponse, HtmlError> {
+) -> Result<impl IntoResponse, ApiError> {
     let from = DateTime::from_timestamp(q.from, 0)
-        .ok_or_else(|| anyhow::anyhow!("Invalid from timestamp"))?;
-    let to =
-        DateTime::from_timestamp(q.to, 0).ok_or_else(|| anyhow::anyhow!("Invalid to timestamp"))?;
+        .ok_or_else(|| ApiError::bad_request("Invalid from timestamp"))?;
+    let to = DateTime::from_timestamp(q.to, 0)
+        .ok_or_else(|| ApiError::bad_request("Invalid to timestamp"))?;
 
     let candles = crate::fetch_candles(&state.store, &state.yf, &ticker).await?;
 
@@ -158,11 +210,11 @@ pub async fn hourly_candles(
     State(state): State<AppState>,
     Path(ticker): Path<String>,
     Query(q): Query<CandleQuery>,
-) -> Result<impl IntoResponse, HtmlError> {
+) -> Result<impl IntoResponse, ApiError> {
     let from = DateTime::from_timestamp(q.from, 0)
-        .ok_or_else(|| anyhow::anyhow!("Invalid from timestamp"))?;
-    let to =
-        DateTime::from_timestamp(q.to, 0).ok_or_else(|| anyhow::anyhow!("Invalid to timestamp"))?;
+        .ok_or_else(|| ApiError::bad_request("Invalid from timestamp"))?;
+    let to = DateTime::from_timestamp(q.to, 0)
+        .ok_or_else(|| ApiError::bad_request("Invalid to timestamp"))?;
 
     let tz_offset = chrono::Local::now().offset().local_minus_utc() as i64;
     let candles = fetch_hourly_candles(&state.store, &state.yf, &ticker, from, to).await?;
@@ -201,41 +253,27 @@ struct TradeAnalyzerTemplate {
     tz_offset_secs: i32,
 }
 
+/// Trade analysis page at `/trades.html` and the APIs behind it.
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/trades.html", routing::get(home))
+        .route("/api/trades", routing::post(upload_trades))
+        .route("/api/candles/daily/{ticker}", routing::get(daily_candles))
+        .route("/api/candles/hourly/{ticker}", routing::get(hourly_candles))
+        .with_state(state)
+        .layer(middleware::from_fn(no_cache))
+}
+
 pub async fn start_server(
     store: Arc<Store>,
     yf: Arc<YFinance>,
     trade_views: Vec<TradeView>,
-    benchmark: &str,
 ) -> anyhow::Result<()> {
-    let cfg = &APP_CONFIG.trade_analysis;
-    // Expected hourly candles: calendar days → trading days (×5/7), 6.5h/day, 10% holiday buffer
-    let min_hourly_candles =
-        (cfg.hourly_chart_days + cfg.hourly_chart_post_days) as f64 * (5.0 / 7.0) * 6.5 * 0.9;
-    let tz_offset_secs = chrono::Local::now().offset().local_minus_utc();
-    let html = TradeAnalyzerTemplate {
-        trades_json: serde_json::to_string(&trade_views)?,
-        benchmark_json: serde_json::to_string(benchmark)?,
-        min_hourly_candles,
-        tz_offset_secs,
-    }
-    .render()?;
-
-    let state = AppState { store, yf, html };
-
+    let state = AppState::new(store, yf, trade_views);
     let app = Router::new()
         .route("/", routing::get(home))
-        .route("/api/candles/daily/{ticker}", routing::get(daily_candles))
-        .route("/api/candles/hourly/{ticker}", routing::get(hourly_candles))
-        .with_state(state)
-        .layer(middleware::from_fn(no_cache));
-
-    let addr = format!("127.0.0.1:{}", APP_CONFIG.http_port);
-    let listener = TcpListener::bind(&addr)
-        .await
-        .with_context(|| format!("Failed to bind at {addr}"))?;
-
-    info!("Trade analyzer running at http://{addr}");
+        .with_state(state.clone())
+        .merge(router(state));
 
-    axum::serve(listener, app).await?;
-    Ok(())
+    serve(app).await
 }
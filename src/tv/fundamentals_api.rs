@@ -1,5 +1,5 @@
 use crate::Ticker;
-use crate::html_error::HtmlError;
+use crate::api_error::{ApiError, ValidationError};
 use crate::store::Store;
 use crate::tv::fundamentals::{Fundamentals, FundamentalsClient};
 use axum::Json;
@@ -19,7 +19,7 @@ pub async fn get(
     Path((exchange, ticker)): Path<(String, String)>,
     Extension(store): Extension<Arc<Store>>,
     Extension(client): Extension<FundamentalsClient>,
-) -> Result<Json<FundamentalsResponse>, HtmlError> {
+) -> Result<Json<FundamentalsResponse>, ApiError> {
     let ticker = normalize(exchange, ticker)?;
     if let Some(cached) = store
         .get_fundamentals(&ticker.exchange, &ticker.ticker)
@@ -37,7 +37,7 @@ pub async fn refresh(
     Path((exchange, ticker)): Path<(String, String)>,
     Extension(store): Extension<Arc<Store>>,
     Extension(client): Extension<FundamentalsClient>,
-) -> Result<Json<FundamentalsResponse>, HtmlError> {
+) -> Result<Json<FundamentalsResponse>, ApiError> {
     fetch_and_cache(&store, &client, normalize(exchange, ticker)?).await
 }
 
@@ -45,7 +45,7 @@ async fn fetch_and_cache(
     store: &Store,
     client: &FundamentalsClient,
     ticker: Ticker,
-) -> Result<Json<FundamentalsResponse>, HtmlError> {
+) -> Result<Json<FundamentalsResponse>, ApiError> {
     let fundamentals = client
         .fetch(std::slice::from_ref(&ticker))
         .await?
@@ -79,7 +79,7 @@ fn normalize(exchange: String, ticker: String) -> anyhow::Result<Ticker> {
     let exchange = exchange.trim().to_uppercase();
     let ticker = ticker.trim().to_uppercase();
     if exchange.is_empty() || ticker.is_empty() {
-        anyhow::bail!("Exchange and ticker are required");
+        return Err(ValidationError("Exchange and ticker are required".to_string()).into());
     }
     Ok(Ticker { exchange, ticker })
 }
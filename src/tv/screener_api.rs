@@ -1,7 +1,9 @@
+use crate::symbol::{self, Symbol};
 use crate::{Group, Stock, StockInfoFetcher, util::BROWSER_UA};
 use anyhow::Context;
 use chrono::Local;
 use futures::{StreamExt, TryStreamExt, stream};
+use itertools::Itertools;
 use reqwest::{Client, header};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -14,6 +16,8 @@ const SCANNER_URL: &str = "https://scanner.tradingview.com/global/scan";
 const SYMBOL_SEARCH_URL: &str = "https://symbol-search.tradingview.com/symbol_search/v3/";
 const FIELDS: [&str; 4] = ["name", "exchange", "sector", "industry"];
 const SYMBOL_SEARCH_CONCURRENCY: usize = 8;
+/// Other listings named in a disambiguation report.
+const MAX_ALTERNATIVES: usize = 5;
 
 #[derive(Clone)]
 pub struct ScreenerApi {
@@ -40,67 +44,49 @@ impl ScreenerApi {
         }
     }
 
-    pub async fn fetch_stocks(&self, tickers: &[String]) -> anyhow::Result<HashMap<String, Stock>> {
+    pub async fn fetch_stocks(&self, tickers: &[String]) -> anyhow::Result<FetchedStocks> {
         let resolved = self.resolve_tickers(tickers).await?;
         let qualified = resolved
             .iter()
-            .map(|(_, qualified)| qualified.clone())
+            .map(|(_, resolution)| resolution.qualified.clone())
             .collect::<Vec<_>>();
         let stocks = self.scan_qualified(&qualified).await?;
-        let qualified_to_input = resolved
-            .into_iter()
-            .map(|(input, qualified)| (qualified, input))
-            .collect::<HashMap<_, _>>();
-
-        let mut result = HashMap::with_capacity(stocks.len());
-        for stock in stocks {
-            let Some(input) = qualified_to_input.get(&stock.ticker) else {
-                continue;
-            };
-            result.insert(input.clone(), stock_from_scanner(stock, input));
-        }
-
-        Ok(result)
+        Ok(match_listings(resolved, stocks))
     }
 
-    async fn resolve_tickers(&self, tickers: &[String]) -> anyhow::Result<Vec<(String, String)>> {
+    async fn resolve_tickers(
+        &self,
+        tickers: &[String],
+    ) -> anyhow::Result<Vec<(String, Resolution)>> {
         stream::iter(tickers.iter().cloned())
             .map(|ticker| async move {
                 self.resolve_ticker(&ticker)
                     .await
-                    .map(|qualified| (ticker, qualified))
+                    .map(|resolution| (ticker, resolution))
             })
             .buffer_unordered(SYMBOL_SEARCH_CONCURRENCY)
             .try_collect()
             .await
     }
 
-    async fn resolve_ticker(&self, ticker: &str) -> anyhow::Result<String> {
-        if ticker.contains(':') {
-            return Ok(ticker.to_uppercase());
+    /// Exchange-qualified tickers pass through; bare ones are looked up,
+    /// preferring a US listing of that exact symbol.
+    async fn resolve_ticker(&self, ticker: &str) -> anyhow::Result<Resolution> {
+        let symbol = ticker.parse::<Symbol>()?.tradingview();
+        if symbol.contains(':') {
+            return Ok(Resolution {
+                qualified: symbol,
+                ambiguity: None,
+            });
         }
+        let ticker = symbol.as_str();
 
         let response = match self.search_symbol(ticker, Some("stocks")).await {
             Ok(response) if !response.symbols.is_empty() => response,
             _ => self.search_symbol(ticker, None).await?,
         };
-
-        let best = response
-            .symbols
-            .iter()
-            .find(|m| {
-                m.symbol.eq_ignore_ascii_case(ticker) && !m.exchange.eq_ignore_ascii_case("BOATS")
-            })
-            .or_else(|| {
-                response
-                    .symbols
-                    .iter()
-                    .find(|m| m.symbol.eq_ignore_ascii_case(ticker))
-            })
-            .or_else(|| response.symbols.first())
-            .with_context(|| format!("Could not resolve TradingView symbol {ticker}"))?;
-
-        Ok(best.qualified())
+        pick_listing(ticker, &response.symbols)
+            .with_context(|| format!("Could not resolve TradingView symbol {ticker}"))
     }
 
     async fn search_symbol(
@@ -167,6 +153,7 @@ impl StockInfoFetcher for ScreenerApi {
     async fn fetch(&self, ticker: &str) -> anyhow::Result<Stock> {
         let stocks = self.fetch_stocks(&[ticker.to_owned()]).await?;
         stocks
+            .stocks
             .into_values()
             .next()
             .with_context(|| format!("TradingView screener returned no stock info for {ticker}"))
@@ -192,15 +179,53 @@ fn scanner_stock_from_row(row: SymbolRow) -> anyhow::Result<ScannerStock> {
     })
 }
 
-fn stock_from_scanner(stock: ScannerStock, input_ticker: &str) -> Stock {
-    let ticker = input_ticker
-        .split_once(':')
-        .map(|(_, ticker)| ticker)
-        .unwrap_or(input_ticker)
-        .to_uppercase();
+/// Picks the listing of `ticker` among symbol search matches: an exact match
+/// on a US exchange, then an exact match elsewhere, then the top result.
+/// Reports the other candidates when more than one listing could be meant.
+fn pick_listing(ticker: &str, matches: &[SymbolMatch]) -> Option<Resolution> {
+    let exact = matches
+        .iter()
+        .filter(|m| {
+            m.symbol.eq_ignore_ascii_case(ticker) && !m.exchange.eq_ignore_ascii_case("BOATS")
+        })
+        .collect::<Vec<_>>();
+    let best = exact
+        .iter()
+        .find(|m| m.is_us())
+        .or_else(|| exact.first())
+        .copied()
+        .or_else(|| matches.first())?;
+
+    let qualified = best.qualified();
+    let candidates = if exact.is_empty() {
+        matches.iter().collect()
+    } else {
+        exact
+    };
+    let alternatives = candidates
+        .into_iter()
+        .map(SymbolMatch::qualified)
+        .filter(|other| *other != qualified)
+        .unique()
+        .take(MAX_ALTERNATIVES)
+        .collect::<Vec<_>>();
+    let ambiguity =
+        (!alternatives.is_empty() || !best.symbol.eq_ignore_ascii_case(ticker)).then(|| {
+            Ambiguity {
+                ticker: ticker.to_owned(),
+                chosen: qualified.clone(),
+                alternatives,
+            }
+        });
+    Some(Resolution {
+        qualified,
+        ambiguity,
+    })
+}
 
+fn stock_from_scanner(stock: ScannerStock) -> Stock {
     Stock {
-        ticker,
+        ticker: symbol::canonical(&stock.ticker),
         exchange: stock.exchange,
         sector: Group {
             name: stock.sector,
@@ -214,6 +239,73 @@ fn stock_from_scanner(stock: ScannerStock, input_ticker: &str) -> Stock {
     }
 }
 
+/// Keys scanned `stocks` by listing ID and records which requested tickers
+/// ended up on a listing with another ID.
+fn match_listings(resolved: Vec<(String, Resolution)>, stocks: Vec<ScannerStock>) -> FetchedStocks {
+    let mut ambiguous = Vec::new();
+    // Several inputs (SHOP, TSX:SHOP) can resolve to the same listing.
+    let mut qualified_to_inputs = HashMap::<String, Vec<String>>::new();
+    for (input, resolution) in resolved {
+        if let Some(ambiguity) = resolution.ambiguity {
+            ambiguous.push(ambiguity);
+        }
+        qualified_to_inputs
+            .entry(resolution.qualified)
+            .or_default()
+            .push(input);
+    }
+
+    let mut result = HashMap::with_capacity(stocks.len());
+    let mut aliases = Vec::new();
+    for stock in stocks {
+        let Some(inputs) = qualified_to_inputs.get(&stock.ticker) else {
+            continue;
+        };
+        let stock = stock_from_scanner(stock);
+        aliases.extend(
+            inputs
+                .iter()
+                .map(|input| symbol::canonical(input))
+                .filter(|input| *input != stock.ticker)
+                .map(|input| (input, stock.ticker.clone())),
+        );
+        result.insert(stock.ticker.clone(), stock);
+    }
+
+    FetchedStocks {
+        stocks: result,
+        aliases,
+        ambiguous,
+    }
+}
+
+/// Stocks found by [`ScreenerApi::fetch_stocks`], keyed by their listing's
+/// canonical ID (`Stock::ticker`).
+#[derive(Debug, Default)]
+pub struct FetchedStocks {
+    pub stocks: HashMap<String, Stock>,
+    /// `(requested, listing)` for requested tickers whose listing has another
+    /// ID, e.g. `("SHOP", "SHOP.TO")` when only the TSX listing matched.
+    pub aliases: Vec<(String, String)>,
+    /// Tickers that matched several listings, or none exactly.
+    pub ambiguous: Vec<Ambiguity>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Ambiguity {
+    pub ticker: String,
+    /// Exchange-qualified listing that was used.
+    pub chosen: String,
+    /// Other listings the ticker could mean.
+    pub alternatives: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Resolution {
+    qualified: String,
+    ambiguity: Option<Ambiguity>,
+}
+
 #[derive(Debug)]
 struct ScannerStock {
     ticker: String,
@@ -265,9 +357,18 @@ struct SymbolMatch {
     exchange: String,
     #[serde(default)]
     prefix: Option<String>,
+    #[serde(default)]
+    country: Option<String>,
 }
 
 impl SymbolMatch {
+    fn is_us(&self) -> bool {
+        match &self.country {
+            Some(country) => country.eq_ignore_ascii_case("US"),
+            None => symbol::is_us_exchange(self.prefix.as_deref().unwrap_or(&self.exchange)),
+        }
+    }
+
     fn qualified(&self) -> String {
         let prefix = self
             .prefix
@@ -289,11 +390,77 @@ mod tests {
             symbol: "aapl".to_owned(),
             exchange: "XNGS".to_owned(),
             prefix: Some("NASDAQ".to_owned()),
+            country: Some("US".to_owned()),
         };
 
         assert_eq!(matched.qualified(), "NASDAQ:AAPL");
     }
 
+    #[test]
+    fn listings_are_keyed_by_id_with_aliases_for_other_inputs() {
+        let resolution = |qualified: &str| Resolution {
+            qualified: qualified.to_owned(),
+            ambiguity: None,
+        };
+        let resolved = vec![
+            ("XYZ".to_owned(), resolution("TSX:XYZ")),
+            ("TSX:XYZ".to_owned(), resolution("TSX:XYZ")),
+            ("AAPL".to_owned(), resolution("NASDAQ:AAPL")),
+        ];
+        let scanned = |ticker: &str, exchange: &str| ScannerStock {
+            ticker: ticker.to_owned(),
+            name: String::new(),
+            exchange: exchange.to_owned(),
+            sector: "Technology Services".to_owned(),
+            industry: "Packaged Software".to_owned(),
+        };
+
+        let fetched = match_listings(
+            resolved,
+            vec![scanned("TSX:XYZ", "TSX"), scanned("NASDAQ:AAPL", "NASDAQ")],
+        );
+
+        assert_eq!(
+            fetched.stocks.keys().sorted().collect_vec(),
+            vec!["AAPL", "XYZ.TO"]
+        );
+        assert_eq!(fetched.stocks["XYZ.TO"].ticker, "XYZ.TO");
+        assert_eq!(
+            fetched.aliases,
+            vec![("XYZ".to_owned(), "XYZ.TO".to_owned())]
+        );
+    }
+
+    #[test]
+    fn pick_listing_prefers_us_and_reports_alternatives() {
+        let listing = |exchange: &str, country: &str| SymbolMatch {
+            symbol: "SHOP".to_owned(),
+            exchange: exchange.to_owned(),
+            prefix: None,
+            country: Some(country.to_owned()),
+        };
+        let matches = [
+            listing("TSX", "CA"),
+            listing("NYSE", "US"),
+            listing("BOATS", "US"),
+        ];
+
+        let resolution = pick_listing("SHOP", &matches).unwrap();
+
+        assert_eq!(resolution.qualified, "NYSE:SHOP");
+        assert_eq!(
+            resolution.ambiguity,
+            Some(Ambiguity {
+                ticker: "SHOP".to_owned(),
+                chosen: "NYSE:SHOP".to_owned(),
+                alternatives: vec!["TSX:SHOP".to_owned()],
+            })
+        );
+
+        let only = pick_listing("SHOP", &matches[1..2]).unwrap();
+        assert_eq!(only.ambiguity, None);
+    }
+
     #[test]
     fn scanner_row_maps_to_stock_with_existing_domain_types() {
         let scanner_stock = scanner_stock_from_row(SymbolRow {
@@ -306,7 +473,7 @@ mod tests {
             ],
         })
         .unwrap();
-        let stock = stock_from_scanner(scanner_stock, "aapl");
+        let stock = stock_from_scanner(scanner_stock);
 
         assert_eq!(stock.ticker, "AAPL");
         assert_eq!(stock.exchange, "NASDAQ");
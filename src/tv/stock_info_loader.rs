@@ -8,6 +8,7 @@ use tracing::warn;
 
 use super::TV_HOME;
 
+use crate::symbol::{self, Market, Symbol};
 use crate::util::normalize;
 use crate::{Group, Stock, StockInfoFetcher};
 
@@ -57,10 +58,14 @@ impl<'a> StockInfoLoader<'a> {
         .click()
         .await?;
 
+        let search = ticker
+            .parse::<Symbol>()
+            .map(|symbol| symbol.tradingview())
+            .unwrap_or_else(|_| ticker.to_owned());
         self.page
             .sleep()
             .await
-            .execute(InsertTextParams::new(ticker))
+            .execute(InsertTextParams::new(search))
             .await?;
         self.send_enter().await?;
         self.page.wait_for_navigation().await?.sleep().await;
@@ -87,7 +92,7 @@ impl<'a> StockInfoLoader<'a> {
             .find_element(r#"div[data-test-id-widget-type="detail"]"#)
             .await
             .context("No detail widget found")?;
-        let symbol = detail_widget
+        let shown = detail_widget
             .find_element(r#"span[data-qa-id="details-element symbol"]"#)
             .await
             .context("No exchange info found")?
@@ -96,9 +101,9 @@ impl<'a> StockInfoLoader<'a> {
             .unwrap_or_default()
             .trim()
             .to_uppercase();
-        if symbol != ticker {
+        if symbol::canonical(&shown) != symbol::canonical(ticker) {
             anyhow::bail!(
-                "Wrong ticker got loaded in TradingView, expected {ticker:?} found {symbol:?}"
+                "Wrong ticker got loaded in TradingView, expected {ticker:?} found {shown:?}"
             )
         }
 
@@ -129,7 +134,7 @@ impl<'a> StockInfoLoader<'a> {
         }
 
         Ok(Stock {
-            ticker: ticker.to_owned(),
+            ticker: symbol::canonical(ticker),
             exchange: Self::map_exchange(&exchange).to_owned(),
             sector: find_group(&sector).await.context("Couldn't find sector")?,
             industry: find_group(&industry)
@@ -176,6 +181,7 @@ impl<'a> StockInfoLoader<'a> {
             "NYSE Arca" => "NYSEARCA",
             "CBOE" | "Cboe BZX" | "BATS" => "CBOE",
             "OTC Bulletin Board" | "Other OTC" | "Pink Sheets" | "OTC Markets" => "OTC",
+            other if Market::by_code(other).is_some() => other,
             // Unknown — pass through as-is
             other => {
                 warn!("Invalid Exchange detected {other}");
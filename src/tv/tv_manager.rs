@@ -1,4 +1,5 @@
 use crate::config::APP_CONFIG;
+use crate::rotation;
 use crate::store::Store;
 use crate::tv::screenshot::SnapOnErr;
 use crate::tv::stock_info_loader::StockInfoLoader;
@@ -52,6 +53,11 @@ impl TvManager {
             .snap_on_err(&page, "fetch_sectors")
             .await?;
         self.store.save_performances(&sectors).await?;
+        // History only feeds the rotation heatmap; the performances are saved.
+        if let Err(err) = rotation::record_snapshot(&self.store, TickerType::Sector, &sectors).await
+        {
+            warn!("Failed to record sector performance history: {err:#}");
+        }
 
         Ok(sectors)
     }
@@ -77,6 +83,11 @@ impl TvManager {
             .snap_on_err(&page, "fetch_industries")
             .await?;
         self.store.save_performances(&industries).await?;
+        if let Err(err) =
+            rotation::record_snapshot(&self.store, TickerType::Industry, &industries).await
+        {
+            warn!("Failed to record industry performance history: {err:#}");
+        }
 
         Ok(industries)
     }
@@ -2,29 +2,74 @@ use anyhow::Context;
 use chrono::{DateTime, Datelike, Local, TimeDelta, Weekday};
 use futures::stream;
 use itertools::Itertools;
+use serde::Serialize;
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
 };
 use tokio::fs;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use futures::{StreamExt, TryStreamExt};
 
 use rand::seq::SliceRandom;
 
-use crate::Performance;
 use crate::config::APP_CONFIG;
-use crate::yf::Candle;
+use crate::symbol::{Symbol, SymbolKind};
 
 pub const BROWSER_UA: &str =
     "Mozilla/5.0 (Macintosh; Intel Mac OS X 10.15; rv:152.0) Gecko/20100101 Firefox/152.0";
 
+/// Column headers that hold the ticker in broker and screener exports.
+const SYMBOL_COLUMNS: &[&str] = &["symbol", "ticker", "symbols", "tickers", "ticker symbol"];
+/// How far down a file the header row is looked for.
+const HEADER_SCAN_LINES: usize = 20;
+/// Rejected lines listed individually in the log before summarizing.
+const MAX_LOGGED_REJECTS: usize = 10;
+
+/// Reads watchlist files of any of the supported layouts (see
+/// [`parse_stock_content`]). `skip_lines` forces lines to be skipped before
+/// sniffing; normally the preamble is detected.
 pub async fn read_stocks(
     files: &[PathBuf],
-    skip_lines: usize,
+    skip_lines: Option<usize>,
     skip_stocks: &str,
 ) -> anyhow::Result<Vec<String>> {
+    let skips = skip_set(skip_stocks);
+    let mut stocks = stream::iter(files)
+        .then(|file| parse_stocks(file, skip_lines))
+        .try_collect::<Vec<_>>()
+        .await?
+        .into_iter()
+        .flatten()
+        .filter(|s| !skips.contains(s))
+        .unique()
+        .collect_vec();
+    stocks.shuffle(&mut rand::rng());
+
+    Ok(stocks)
+}
+
+/// [`read_stocks`] for an uploaded watchlist rather than files on disk.
+pub fn read_stocks_content(
+    content: &str,
+    skip_lines: Option<usize>,
+    skip_stocks: &str,
+) -> ParsedStocks {
+    let skips = skip_set(skip_stocks);
+    let mut parsed = parse_stock_content(content, skip_lines);
+    log_rejected("upload", &parsed.rejected);
+    parsed.tickers = parsed
+        .tickers
+        .into_iter()
+        .filter(|s| !skips.contains(s))
+        .unique()
+        .collect();
+    parsed
+}
+
+/// Uppercased `skip_stocks` plus the configured ignored stocks.
+pub fn skip_set(skip_stocks: &str) -> HashSet<String> {
     let skips = skip_stocks
         .split(',')
         .chain(APP_CONFIG.ignored_stocks.iter().map(|s| s.as_str()))
@@ -39,24 +84,12 @@ pub async fn read_stocks(
             info!("Skipping {} stocks", skips.len());
         }
     }
-
-    let mut stocks = stream::iter(files)
-        .then(|file| parse_stocks(file, skip_lines))
-        .try_collect::<Vec<_>>()
-        .await?
-        .into_iter()
-        .flatten()
-        .filter(|s| !skips.contains(s))
-        .unique()
-        .collect_vec();
-    stocks.shuffle(&mut rand::rng());
-
-    Ok(stocks)
+    skips
 }
 
 async fn parse_stocks(
     csv_file: impl AsRef<Path>,
-    skip_lines: usize,
+    skip_lines: Option<usize>,
 ) -> anyhow::Result<Vec<String>> {
     let csv_file = csv_file.as_ref();
     let csv_file = fs::canonicalize(csv_file)
@@ -68,29 +101,211 @@ async fn parse_stocks(
         .await
         .with_context(|| format!("Couldn't read {csv_file:?}"))?;
 
-    let result: Vec<String> = content
-        .lines()
-        .skip(skip_lines)
-        .filter_map(|line| {
-            line.trim()
-                .split(',')
-                .next()
-                .map(str::trim)
-                .map(|stock| stock.trim().to_uppercase())
-        })
-        .filter(|s| !s.is_empty())
-        .filter(|s| !s.starts_with('#'))
-        .filter(|s| !s.ends_with("/WS"))
-        .collect();
+    let result = parse_stock_content(&content, skip_lines);
+    log_rejected(&csv_file.display().to_string(), &result.rejected);
 
     let total_lines = content.lines().count();
     info!(
         "Processed {} lines, found {} stocks",
         total_lines,
-        result.len(),
+        result.tickers.len(),
     );
 
-    Ok(result)
+    Ok(result.tickers)
+}
+
+/// A line of a watchlist file that didn't yield a ticker.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RejectedLine {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: String,
+    pub reason: &'static str,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ParsedStocks {
+    pub tickers: Vec<String>,
+    pub rejected: Vec<RejectedLine>,
+}
+
+impl ParsedStocks {
+    fn push(&mut self, line: usize, text: &str, value: &str) {
+        match normalize_ticker(value) {
+            Ok(ticker) => self.tickers.push(ticker),
+            Err(reason) => self.rejected.push(RejectedLine {
+                line,
+                text: text.to_string(),
+                reason,
+            }),
+        }
+    }
+}
+
+fn log_rejected(source: &str, rejected: &[RejectedLine]) {
+    for reject in rejected.iter().take(MAX_LOGGED_REJECTS) {
+        warn!(
+            "{source}:{}: {} ({:?})",
+            reject.line, reject.reason, reject.text
+        );
+    }
+    if rejected.len() > MAX_LOGGED_REJECTS {
+        warn!(
+            "{source}: {} more lines rejected",
+            rejected.len() - MAX_LOGGED_REJECTS
+        );
+    }
+}
+
+/// Pulls tickers out of a watchlist file, working out its layout:
+///
+/// - a header row with a `Symbol`/`Ticker` column anywhere in the first lines
+///   (broker and screener CSVs, quoted or not) — that column is read;
+/// - TradingView `.txt` exports (`###Section,NASDAQ:AAPL,...`) — every entry;
+/// - otherwise the first column of each line, with leading lines that aren't
+///   tickers (titles, `Source:`/`Count:` preambles) skipped.
+///
+/// Tickers are normalized with [`normalize_ticker`]; lines that should hold
+/// one but don't are returned as rejected.
+pub fn parse_stock_content(content: &str, skip_lines: Option<usize>) -> ParsedStocks {
+    let content = content.trim_start_matches('\u{feff}');
+    let records = content
+        .lines()
+        .enumerate()
+        .skip(skip_lines.unwrap_or(0))
+        .map(|(index, line)| (index + 1, line, split_csv_line(line)))
+        .collect_vec();
+    let mut parsed = ParsedStocks::default();
+
+    let header = records
+        .iter()
+        .take(HEADER_SCAN_LINES)
+        .find_map(|(index, _, fields)| {
+            fields
+                .iter()
+                .position(|field| SYMBOL_COLUMNS.contains(&field.to_lowercase().as_str()))
+                .map(|column| (*index, column))
+        });
+    if let Some((header_line, column)) = header {
+        for (index, line, fields) in records.iter().filter(|(i, _, _)| *i > header_line) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match fields.get(column) {
+                Some(value) => parsed.push(*index, line, value),
+                None => parsed.rejected.push(RejectedLine {
+                    line: *index,
+                    text: line.to_string(),
+                    reason: "no symbol column",
+                }),
+            }
+        }
+        return parsed;
+    }
+
+    let is_tradingview = records.iter().any(|(_, _, fields)| {
+        fields.iter().any(|field| field.starts_with("###"))
+            || (fields.len() > 1
+                && fields
+                    .iter()
+                    .filter(|field| !field.is_empty())
+                    .all(|field| field.contains(':')))
+    });
+    if is_tradingview {
+        for (index, line, fields) in &records {
+            for field in fields {
+                if !field.is_empty() && !field.starts_with("###") {
+                    parsed.push(*index, line, field);
+                }
+            }
+        }
+        return parsed;
+    }
+
+    let mut in_data = skip_lines.is_some();
+    for (index, line, fields) in &records {
+        let Some(value) = fields.first().filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        if value.starts_with('#') {
+            continue;
+        }
+        match normalize_ticker(value) {
+            Ok(ticker) => {
+                in_data = true;
+                parsed.tickers.push(ticker);
+            }
+            Err(reason) if in_data => parsed.rejected.push(RejectedLine {
+                line: *index,
+                text: line.to_string(),
+                reason,
+            }),
+            Err(_) => debug!("Skipping preamble line {index}: {line:?}"),
+        }
+    }
+    parsed
+}
+
+/// Splits a CSV line into trimmed fields, honouring double quotes. A
+/// leading byte order mark is dropped.
+pub fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// The canonical ID (see [`Symbol`]) of a watchlist entry in any vendor's
+/// notation. Warrants and values that can't be tickers are rejected with the
+/// reason.
+pub fn normalize_ticker(value: &str) -> Result<String, &'static str> {
+    let symbol = Symbol::parse(value)?;
+    if symbol.kind == SymbolKind::Equity
+        && ["/WS", ".WS", "-WS"]
+            .iter()
+            .any(|suffix| symbol.root.ends_with(suffix))
+    {
+        return Err("warrant");
+    }
+    Ok(symbol.id())
+}
+
+/// Writes `stocks` as a watchlist CSV in the layout [`read_stocks`] expects:
+/// four header lines (title, source, count, blank) followed by one ticker per line.
+pub async fn save_stocks_csv(
+    file: &Path,
+    title: &str,
+    source: &str,
+    stocks: &[String],
+) -> anyhow::Result<()> {
+    use std::fmt::Write;
+
+    let mut content = String::new();
+    writeln!(content, "======= {title} ======")?;
+    writeln!(content, "Source: {source}")?;
+    writeln!(content, "Count: {}", stocks.len())?;
+    writeln!(content)?;
+    for stock in stocks {
+        writeln!(content, "{stock}")?;
+    }
+    fs::write(file, content)
+        .await
+        .with_context(|| format!("Error writing output to {file:?}"))?;
+    info!("Saved the output to {:?}\n", file.canonicalize()?);
+    Ok(())
 }
 
 pub fn is_upto_date(time: DateTime<Local>) -> bool {
@@ -181,40 +396,53 @@ pub fn parse_percentage(s: impl AsRef<str>) -> anyhow::Result<f64> {
         .with_context(|| format!("Failed to parse percentage: {s:?}"))
 }
 
-pub fn compute_rs(perf: &Performance, base: &Performance) -> f64 {
-    fn multiplier(p: &Performance) -> f64 {
-        1.0 + (p.perf_1m * 0.3 + p.perf_3m * 0.4 + p.perf_6m * 0.2 + p.perf_1y * 0.1) / 100.0
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_ticker_notations() {
+        assert_eq!(normalize_ticker("brk-b"), Ok("BRK.B".into()));
+        assert_eq!(normalize_ticker("BRK/B"), Ok("BRK.B".into()));
+        assert_eq!(normalize_ticker("NYSE:BRK.B"), Ok("BRK.B".into()));
+        assert_eq!(normalize_ticker("\"NASDAQ:AAPL\""), Ok("AAPL".into()));
+        assert_eq!(normalize_ticker("SHOP.TO"), Ok("SHOP.TO".into()));
+        assert_eq!(normalize_ticker("BTC-USD"), Ok("BTC-USD".into()));
+        assert_eq!(normalize_ticker("ABC/WS"), Err("warrant"));
+        assert_eq!(normalize_ticker("Count: 100"), Err("not a ticker"));
+        assert_eq!(normalize_ticker("======="), Err("not a ticker"));
     }
 
-    multiplier(perf) / multiplier(base)
-}
+    #[test]
+    fn reads_legacy_preamble_files() {
+        let content = "======= Top Performing Stocks ======\nSource: https://tv/screener\nCount: 3\n\nNVDA\nAMD,extra\nabc/ws\n";
 
-pub fn compute_rs_candles(candles: &[Candle], base: &[Candle]) -> f64 {
-    const IBD_QUARTER_BARS: usize = 63;
-    const IBD_WEIGHTS: [f64; 4] = [0.4, 0.2, 0.2, 0.2];
+        let parsed = parse_stock_content(content, None);
 
-    fn multiplier(candles: &[Candle]) -> f64 {
-        let n = candles.len();
-        if n == 0 {
-            return 0.0;
-        }
+        assert_eq!(parsed.tickers, ["NVDA", "AMD"]);
+        assert_eq!(parsed.rejected.len(), 1);
+        assert_eq!(parsed.rejected[0].line, 7);
+        assert_eq!(parsed.rejected[0].reason, "warrant");
+    }
 
-        let current = candles[n - 1].close;
-        IBD_WEIGHTS
-            .iter()
-            .enumerate()
-            .map(|(i, w)| {
-                let lookback = (i + 1) * IBD_QUARTER_BARS;
-                let idx = (n - 1).saturating_sub(lookback);
-                w * (current / candles[idx].close)
-            })
-            .sum()
-    }
-
-    let base_m = multiplier(base);
-    if base_m == 0.0 {
-        0.0
-    } else {
-        multiplier(candles) / base_m
+    #[test]
+    fn finds_symbol_column_in_quoted_csv() {
+        let content = "Exported 2026-01-05\n\"Company, Inc\",\"Symbol\",\"Last\"\n\"Berkshire, Hathaway\",\"BRK/B\",\"410.2\"\n\"Apple\",\"AAPL\",\"190.1\"\n\"Totals\"\n";
+
+        let parsed = parse_stock_content(content, None);
+
+        assert_eq!(parsed.tickers, ["BRK.B", "AAPL"]);
+        assert_eq!(parsed.rejected.len(), 1);
+        assert_eq!(parsed.rejected[0].reason, "no symbol column");
+    }
+
+    #[test]
+    fn reads_tradingview_exports() {
+        let content = "###Semis,NASDAQ:NVDA,NASDAQ:AMD,###Other,NYSE:BRK.B,OTC:BAD VALUE";
+
+        let parsed = parse_stock_content(content, None);
+
+        assert_eq!(parsed.tickers, ["NVDA", "AMD", "BRK.B"]);
+        assert_eq!(parsed.rejected.len(), 1);
     }
 }
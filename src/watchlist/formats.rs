@@ -0,0 +1,215 @@
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::symbol::Symbol;
+use crate::util::normalize_ticker;
+use crate::watchlist::store::WatchlistItem;
+
+/// File formats watchlists are imported from and exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchlistFormat {
+    /// TradingView `.txt`: `EXCHANGE:TICKER` entries separated by commas, with
+    /// `###Section` entries starting sections
+    Tradingview,
+    /// ThinkorSwim: a `Symbol` column in ThinkorSwim notation (`BRK/B`, `.SPX`, `/ES`)
+    Tos,
+    /// `ticker,exchange,section` with a header row
+    Csv,
+}
+
+impl WatchlistFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            WatchlistFormat::Tradingview => "txt",
+            WatchlistFormat::Tos | WatchlistFormat::Csv => "csv",
+        }
+    }
+
+    pub fn parse(self, content: &str) -> Vec<WatchlistItem> {
+        let items = match self {
+            WatchlistFormat::Tradingview => parse_tradingview(content),
+            WatchlistFormat::Tos => parse_tos(content),
+            WatchlistFormat::Csv => parse_csv(content),
+        };
+        items
+            .into_iter()
+            .unique_by(|item| item.ticker.clone())
+            .collect()
+    }
+
+    pub fn render(self, items: &[WatchlistItem]) -> String {
+        match self {
+            WatchlistFormat::Tradingview => render_tradingview(items),
+            WatchlistFormat::Tos => render_tos(items),
+            WatchlistFormat::Csv => render_csv(items),
+        }
+    }
+}
+
+/// Rejects preamble and header lines that end up in a ticker column.
+fn item(ticker: &str, exchange: &str, section: &str) -> Option<WatchlistItem> {
+    normalize_ticker(ticker).ok().map(|ticker| WatchlistItem {
+        ticker,
+        exchange: exchange.trim().trim_matches('"').trim().to_uppercase(),
+        section: section.trim().to_string(),
+    })
+}
+
+fn parse_tradingview(content: &str) -> Vec<WatchlistItem> {
+    let mut section = String::new();
+    let mut items = Vec::new();
+    for entry in content.split([',', '\n', '\r']).map(str::trim) {
+        if let Some(name) = entry.strip_prefix("###") {
+            section = name.trim().to_string();
+            continue;
+        }
+        let exchange = entry.split_once(':').map_or("", |(exchange, _)| exchange);
+        items.extend(item(entry, exchange, &section));
+    }
+    items
+}
+
+fn render_tradingview(items: &[WatchlistItem]) -> String {
+    let mut entries = Vec::new();
+    for (section, items) in &items.iter().chunk_by(|item| item.section.as_str()) {
+        if !section.is_empty() {
+            entries.push(format!("###{section}"));
+        }
+        entries.extend(items.map(|item| match Symbol::parse(&item.ticker) {
+            Ok(mut symbol) => {
+                symbol.exchange = Some(item.exchange.clone()).filter(|e| !e.is_empty());
+                symbol.tradingview()
+            }
+            Err(_) => item.ticker.clone(),
+        }));
+    }
+    entries.join(",")
+}
+
+fn parse_tos(content: &str) -> Vec<WatchlistItem> {
+    let lines = content.lines().collect_vec();
+    // ThinkorSwim exports put a few lines of preamble above the header.
+    let start = lines
+        .iter()
+        .position(|line| {
+            first_column(line).is_some_and(|column| column.eq_ignore_ascii_case("symbol"))
+        })
+        .map_or(0, |header| header + 1);
+    lines[start..]
+        .iter()
+        .filter_map(|line| first_column(line))
+        .filter_map(|symbol| item(symbol, "", ""))
+        .collect()
+}
+
+fn render_tos(items: &[WatchlistItem]) -> String {
+    let mut out = String::from("Symbol\n");
+    for item in items {
+        let symbol = Symbol::parse(&item.ticker).map(|symbol| symbol.thinkorswim());
+        let _ = writeln!(out, "{}", symbol.as_deref().unwrap_or(&item.ticker));
+    }
+    out
+}
+
+fn parse_csv(content: &str) -> Vec<WatchlistItem> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let columns = header
+        .split(',')
+        .map(|column| column.trim().trim_matches('"').to_lowercase())
+        .collect_vec();
+    let find = |names: &[&str]| columns.iter().position(|c| names.contains(&c.as_str()));
+    let Some(ticker) = find(&["ticker", "symbol"]) else {
+        // Headerless: a ticker per line, preamble lines dropped by `item`.
+        return content
+            .lines()
+            .filter_map(first_column)
+            .filter_map(|ticker| item(ticker, "", ""))
+            .collect();
+    };
+    let exchange = find(&["exchange"]);
+    let section = find(&["section"]);
+
+    lines
+        .filter_map(|line| {
+            let fields = line.split(',').collect_vec();
+            let field = |index: Option<usize>| index.and_then(|i| fields.get(i)).copied();
+            item(
+                field(Some(ticker))?,
+                field(exchange).unwrap_or_default(),
+                field(section).unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+fn render_csv(items: &[WatchlistItem]) -> String {
+    let mut out = String::from("ticker,exchange,section\n");
+    for item in items {
+        let _ = writeln!(
+            out,
+            "{},{},{}",
+            item.ticker,
+            item.exchange,
+            item.section.replace(',', " ")
+        );
+    }
+    out
+}
+
+fn first_column(line: &str) -> Option<&str> {
+    let column = line.split(',').next()?.trim().trim_matches('"').trim();
+    (!column.is_empty()).then_some(column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tickers(items: &[WatchlistItem]) -> Vec<&str> {
+        items.iter().map(|item| item.ticker.as_str()).collect()
+    }
+
+    #[test]
+    fn tradingview_round_trips_sections() {
+        let content = "###Semis,NASDAQ:NVDA,NASDAQ:AMD,###Software,NASDAQ:MSFT,NYSE:ORCL";
+
+        let items = WatchlistFormat::Tradingview.parse(content);
+
+        assert_eq!(tickers(&items), ["NVDA", "AMD", "MSFT", "ORCL"]);
+        assert_eq!(items[1].exchange, "NASDAQ");
+        assert_eq!(items[3].section, "Software");
+        assert_eq!(WatchlistFormat::Tradingview.render(&items), content);
+    }
+
+    #[test]
+    fn tos_skips_preamble_and_converts_class_shares() {
+        let content =
+            "Watchlist Export\n\nSymbol,Last,Net Chng\nBRK/B,410.2,1.1\nAAPL,190.1,-0.5\n";
+
+        let items = WatchlistFormat::Tos.parse(content);
+
+        assert_eq!(tickers(&items), ["BRK.B", "AAPL"]);
+        assert_eq!(WatchlistFormat::Tos.render(&items), "Symbol\nBRK/B\nAAPL\n");
+    }
+
+    #[test]
+    fn csv_reads_header_columns_or_legacy_lists() {
+        let items =
+            WatchlistFormat::Csv.parse("exchange,ticker\nNYSE,ibm\nNASDAQ,AAPL\nNYSE,IBM\n");
+        assert_eq!(tickers(&items), ["IBM", "AAPL"]);
+        assert_eq!(items[0].exchange, "NYSE");
+
+        let legacy =
+            "======= Top Performing Stocks ======\nSource: https://tv\nCount: 2\n\nNVDA\nAMD\n";
+        assert_eq!(
+            tickers(&WatchlistFormat::Csv.parse(legacy)),
+            ["NVDA", "AMD"]
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use axum::Json;
+use axum::extract::{Extension, Path, Query};
+use chrono::Utc;
+use itertools::Itertools;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::api_error::{ApiError, NotFoundError};
+use crate::store::Store;
+use crate::summary::{SummaryState, SummaryUpdate};
+use crate::tv::screener_api::{Ambiguity, ScreenerApi};
+use crate::{Group, Stock, util};
+
+pub mod formats;
+pub mod routes;
+pub mod runs;
+pub mod store;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The CSV files a server was started with and how to read them.
+#[derive(Debug, Clone)]
+pub struct WatchlistFiles {
+    pub files: Vec<PathBuf>,
+    pub skip_lines: Option<usize>,
+    pub skip_stocks: String,
+}
+
+impl WatchlistFiles {
+    /// How runs read from these files are labelled.
+    pub fn source(&self) -> String {
+        self.files.iter().map(|file| file.display()).join(", ")
+    }
+}
+
+/// Polls `source` for modified files and swaps the served watchlist whenever
+/// one changes. Runs until the server exits.
+pub fn watch_files(source: WatchlistFiles, store: Arc<Store>, state: Arc<SummaryState>) {
+    tokio::spawn(async move {
+        let mut last_seen = modified_times(&source.files).await;
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let modified = modified_times(&source.files).await;
+            if modified == last_seen {
+                continue;
+            }
+            last_seen = modified;
+
+            info!("Watchlist files changed, reloading");
+            let result = async {
+                let tickers =
+                    util::read_stocks(&source.files, source.skip_lines, &source.skip_stocks)
+                        .await?;
+                state.set_tickers(&store, tickers).await
+            }
+            .await;
+            match result {
+                Ok(Some(_)) => {
+                    runs::record_run(&store, &state.stocks().await, &source.source()).await
+                }
+                Ok(None) => {}
+                Err(err) => warn!("Failed to reload watchlist: {err:#}"),
+            }
+        }
+    });
+}
+
+/// Polls the stored watchlist `name` and swaps the served watchlist whenever
+/// it is saved, e.g. through `/api/watchlists/{name}`. Runs until the server
+/// exits.
+pub fn watch_watchlist(
+    name: String,
+    skip_stocks: String,
+    store: Arc<Store>,
+    state: Arc<SummaryState>,
+) {
+    tokio::spawn(async move {
+        let mut last_seen = store.watchlist_updated_at(&name).await.ok().flatten();
+        loop {
+            tokio::time::sleep(POLL_INTERVAL).await;
+            let updated_at = match store.watchlist_updated_at(&name).await {
+                // Deleted or renamed: keep serving what we have.
+                Ok(None) => continue,
+                Ok(updated_at) => updated_at,
+                Err(err) => {
+                    warn!("Failed to check watchlist {name}: {err:#}");
+                    continue;
+                }
+            };
+            if updated_at == last_seen {
+                continue;
+            }
+            last_seen = updated_at;
+
+            info!("Watchlist {name} changed, reloading");
+            let result = async {
+                let tickers = watchlist_tickers(&store, &name, &skip_stocks).await?;
+                state.set_tickers(&store, tickers).await
+            }
+            .await;
+            match result {
+                Ok(Some(_)) => {
+                    runs::record_run(&store, &state.stocks().await, &format!("watchlist {name}"))
+                        .await
+                }
+                Ok(None) => {}
+                Err(err) => warn!("Failed to reload watchlist {name}: {err:#}"),
+            }
+        }
+    });
+}
+
+async fn modified_times(files: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    let mut times = Vec::with_capacity(files.len());
+    for file in files {
+        let modified = fs::metadata(file).await.and_then(|m| m.modified()).ok();
+        times.push(modified);
+    }
+    times
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UploadQuery {
+    skip_lines: Option<usize>,
+    #[serde(default)]
+    skip_stocks: String,
+}
+
+/// POST /api/watchlist with a watchlist file as the body, in any layout
+/// [`util::parse_stock_content`] reads.
+///
+/// Replaces the served watchlist, the same as editing the input files.
+/// Returns the change, including tickers that matched several listings, or
+/// `null` when the tickers are the ones already served.
+pub async fn upload_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(state): Extension<Arc<SummaryState>>,
+    Query(query): Query<UploadQuery>,
+    body: String,
+) -> Result<Json<Option<SummaryUpdate>>, ApiError> {
+    let parsed = util::read_stocks_content(&body, query.skip_lines, &query.skip_stocks);
+    let tickers = parsed.tickers;
+    if tickers.is_empty() {
+        let rejected = parsed
+            .rejected
+            .iter()
+            .take(5)
+            .map(|reject| format!("line {}: {}", reject.line, reject.reason))
+            .join("; ");
+        return Err(ApiError::bad_request(format!(
+            "No tickers found in the uploaded watchlist ({rejected})"
+        )));
+    }
+    info!("Uploaded watchlist with {} tickers", tickers.len());
+    let update = state.set_tickers(&store, tickers).await?;
+    if update.is_some() {
+        runs::record_run(&store, &state.stocks().await, "upload").await;
+    }
+    Ok(Json(update))
+}
+
+/// POST /api/watchlist/load/{name}: serves the stored watchlist `name`,
+/// like starting stock_themes with `--watchlist`.
+pub async fn load_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Extension(state): Extension<Arc<SummaryState>>,
+    Path(name): Path<String>,
+    Query(query): Query<UploadQuery>,
+) -> Result<Json<Option<SummaryUpdate>>, ApiError> {
+    let tickers = watchlist_tickers(&store, &name, &query.skip_stocks).await?;
+    if tickers.is_empty() {
+        return Err(ApiError::bad_request(format!(
+            "Watchlist {name} has no tickers"
+        )));
+    }
+    info!("Loading watchlist {name} with {} tickers", tickers.len());
+    let update = state.set_tickers(&store, tickers).await?;
+    if update.is_some() {
+        runs::record_run(&store, &state.stocks().await, &format!("watchlist {name}")).await;
+    }
+    Ok(Json(update))
+}
+
+/// Tickers of the stored watchlist `name`, minus `skip_stocks` and the
+/// configured ignored stocks, for the `--watchlist` option of the binaries.
+pub async fn watchlist_tickers(
+    store: &Store,
+    name: &str,
+    skip_stocks: &str,
+) -> anyhow::Result<Vec<String>> {
+    let watchlist = store
+        .get_watchlist(name)
+        .await?
+        .ok_or_else(|| NotFoundError(format!("No watchlist named {name}")))?;
+    let skips = util::skip_set(skip_stocks);
+    let tickers = watchlist
+        .tickers()
+        .into_iter()
+        .filter(|ticker| !skips.contains(ticker))
+        .collect::<Vec<_>>();
+    info!("Watchlist {name} has {} tickers", tickers.len());
+    Ok(tickers)
+}
+
+/// Disambiguation report for tickers TradingView matched more than one
+/// listing for (or none exactly).
+fn report_ambiguous(ambiguous: &[Ambiguity]) {
+    if ambiguous.is_empty() {
+        return;
+    }
+    warn!(
+        "{} tickers resolved ambiguously; qualify them (TSX:SHOP or SHOP.TO) to pick another listing:",
+        ambiguous.len()
+    );
+    for ambiguity in ambiguous {
+        warn!(
+            "  {} → {} (also: {})",
+            ambiguity.ticker,
+            ambiguity.chosen,
+            ambiguity.alternatives.join(", ")
+        );
+    }
+}
+
+/// What [`fetch_stock_info`] found.
+pub struct StockInfo {
+    pub stocks: Vec<Stock>,
+    /// Tickers TradingView matched several listings for, and the one used.
+    pub ambiguous: Vec<Ambiguity>,
+}
+
+/// Stock info of `tickers`, from the store where known and from TradingView
+/// otherwise. Tickers TradingView doesn't know get an "Unknown" sector and
+/// industry.
+pub async fn fetch_stock_info(store: &Store, tickers: Vec<String>) -> anyhow::Result<StockInfo> {
+    let start = Instant::now();
+    let tickers = store.resolve_aliases(tickers).await?;
+    let mut cached_stocks = HashMap::new();
+    let mut missing_tickers = Vec::new();
+    for ticker in &tickers {
+        match store.get_stock(ticker).await? {
+            Some(stock) => {
+                cached_stocks.insert(ticker.clone(), stock);
+            }
+            None => missing_tickers.push(ticker.clone()),
+        }
+    }
+
+    let mut resolved_aliases = HashMap::new();
+    let mut ambiguous = Vec::new();
+    if !missing_tickers.is_empty() {
+        info!(
+            "Fetching {} stocks info from TradingView API",
+            missing_tickers.len(),
+        );
+        let stock_info_fetcher = ScreenerApi::default();
+        let fetched = stock_info_fetcher.fetch_stocks(&missing_tickers).await?;
+        report_ambiguous(&fetched.ambiguous);
+        ambiguous = fetched.ambiguous;
+        let mut fetched_stocks = fetched.stocks;
+        fetched_stocks
+            .retain(|_, stock| !(stock.sector.name.is_empty() || stock.industry.name.is_empty()));
+        if !fetched_stocks.is_empty() {
+            let stocks = fetched_stocks.values().cloned().collect::<Vec<_>>();
+            store.add_stocks(&stocks).await?;
+            cached_stocks.extend(fetched_stocks);
+        }
+        let aliases = fetched
+            .aliases
+            .into_iter()
+            .filter(|(_, listing)| cached_stocks.contains_key(listing))
+            .collect::<Vec<_>>();
+        if !aliases.is_empty() {
+            store.save_ticker_aliases(&aliases).await?;
+            for (alias, listing) in &aliases {
+                info!("{alias} resolved to the listing {listing}");
+            }
+            resolved_aliases.extend(aliases);
+        }
+    }
+
+    let missing_tickers = tickers
+        .into_iter()
+        .filter(|t| !cached_stocks.contains_key(t) && !resolved_aliases.contains_key(t))
+        .collect::<Vec<_>>();
+    if !missing_tickers.is_empty() {
+        warn!(
+            "Failed to fetch stock info for {} tickers: [{}]",
+            missing_tickers.len(),
+            missing_tickers.join(","),
+        );
+        for ticker in missing_tickers {
+            cached_stocks.insert(
+                ticker.clone(),
+                Stock {
+                    ticker,
+                    exchange: "".into(),
+                    sector: unknown_group(),
+                    industry: unknown_group(),
+                    last_update: Utc::now().date_naive(),
+                },
+            );
+        }
+    }
+    info!(
+        "Finished processing {} tickers in {:.2?}",
+        cached_stocks.len(),
+        start.elapsed(),
+    );
+
+    Ok(StockInfo {
+        stocks: cached_stocks.into_values().collect(),
+        ambiguous,
+    })
+}
+
+fn unknown_group() -> Group {
+    Group {
+        name: "Unknown".into(),
+        url: "".into(),
+    }
+}
@@ -0,0 +1,214 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+    response::IntoResponse,
+    routing,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::api_error::ApiError;
+use crate::store::Store;
+use crate::symbol;
+use crate::watchlist::formats::WatchlistFormat;
+use crate::watchlist::store::WatchlistItem;
+
+#[derive(Clone)]
+struct WatchlistState {
+    store: Arc<Store>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreateWatchlistRequest {
+    name: String,
+    #[serde(default)]
+    items: Vec<WatchlistItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateWatchlistRequest {
+    /// New name.
+    name: Option<String>,
+    /// Replaces the items when present.
+    items: Option<Vec<WatchlistItem>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImportQuery {
+    format: WatchlistFormat,
+    /// Add to the existing items instead of replacing them.
+    #[serde(default)]
+    append: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    format: WatchlistFormat,
+}
+
+pub fn router(store: Arc<Store>) -> Router {
+    Router::new()
+        .route(
+            "/api/watchlists",
+            routing::get(list_watchlists).post(create_watchlist),
+        )
+        .route(
+            "/api/watchlists/{name}",
+            routing::get(get_watchlist)
+                .put(update_watchlist)
+                .delete(delete_watchlist),
+        )
+        .route("/api/watchlists/{name}/import", routing::post(import))
+        .route("/api/watchlists/{name}/export", routing::get(export))
+        .with_state(WatchlistState { store })
+}
+
+async fn list_watchlists(
+    State(state): State<WatchlistState>,
+) -> Result<impl IntoResponse, ApiError> {
+    Ok(Json(state.store.list_watchlists().await?))
+}
+
+async fn get_watchlist(
+    State(state): State<WatchlistState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    match state.store.get_watchlist(&name).await? {
+        Some(watchlist) => Ok(Json(watchlist)),
+        None => Err(not_found(&name)),
+    }
+}
+
+async fn create_watchlist(
+    State(state): State<WatchlistState>,
+    Json(req): Json<CreateWatchlistRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = required_name(&req.name)?;
+    if state.store.watchlist_exists(name).await? {
+        return Err(ApiError::conflict(format!(
+            "Watchlist {name} already exists"
+        )));
+    }
+    let items = normalize_items(req.items);
+    state.store.save_watchlist(name, &items).await?;
+    info!("Created watchlist {name} with {} tickers", items.len());
+    Ok(Json(state.store.get_watchlist(name).await?))
+}
+
+async fn update_watchlist(
+    State(state): State<WatchlistState>,
+    Path(name): Path<String>,
+    Json(req): Json<UpdateWatchlistRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state.store.watchlist_exists(&name).await? {
+        return Err(not_found(&name));
+    }
+    let mut name = name;
+    if let Some(new_name) = &req.name {
+        let new_name = required_name(new_name)?;
+        if !new_name.eq_ignore_ascii_case(&name) && state.store.watchlist_exists(new_name).await? {
+            return Err(ApiError::conflict(format!(
+                "Watchlist {new_name} already exists"
+            )));
+        }
+        state.store.rename_watchlist(&name, new_name).await?;
+        name = new_name.to_string();
+    }
+    if let Some(items) = req.items {
+        state
+            .store
+            .save_watchlist(&name, &normalize_items(items))
+            .await?;
+    }
+    Ok(Json(state.store.get_watchlist(&name).await?))
+}
+
+async fn delete_watchlist(
+    State(state): State<WatchlistState>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    if !state.store.delete_watchlist(&name).await? {
+        return Err(not_found(&name));
+    }
+    info!("Deleted watchlist {name}");
+    Ok(Json(()))
+}
+
+/// POST /api/watchlists/{name}/import?format=tradingview with the file as the
+/// body. Creates the watchlist when it doesn't exist.
+async fn import(
+    State(state): State<WatchlistState>,
+    Path(name): Path<String>,
+    Query(query): Query<ImportQuery>,
+    body: String,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = required_name(&name)?;
+    let imported = query.format.parse(&body);
+    if imported.is_empty() {
+        return Err(ApiError::bad_request("No tickers found in the file"));
+    }
+
+    let mut items = match (query.append, state.store.get_watchlist(name).await?) {
+        (true, Some(watchlist)) => watchlist.items,
+        _ => Vec::new(),
+    };
+    items.extend(imported);
+    let items = normalize_items(items);
+    state.store.save_watchlist(name, &items).await?;
+    info!("Imported {} tickers into watchlist {name}", items.len());
+    Ok(Json(state.store.get_watchlist(name).await?))
+}
+
+/// GET /api/watchlists/{name}/export?format=tos, served as a download.
+async fn export(
+    State(state): State<WatchlistState>,
+    Path(name): Path<String>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Some(watchlist) = state.store.get_watchlist(&name).await? else {
+        return Err(not_found(&name));
+    };
+    let file_name = format!(
+        "{}.{}",
+        watchlist.summary.name.replace(['"', '/', '\\'], "_"),
+        query.format.extension()
+    );
+    Ok((
+        [
+            (CONTENT_TYPE, "text/plain; charset=utf-8".to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{file_name}\""),
+            ),
+        ],
+        query.format.render(&watchlist.items),
+    ))
+}
+
+fn required_name(name: &str) -> Result<&str, ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("Watchlist name is required"));
+    }
+    Ok(name)
+}
+
+fn not_found(name: &str) -> ApiError {
+    ApiError::not_found(format!("No watchlist named {name}"))
+}
+
+/// Canonicalizes tickers and drops blanks and repeats, keeping the first.
+fn normalize_items(items: Vec<WatchlistItem>) -> Vec<WatchlistItem> {
+    let mut seen = std::collections::HashSet::new();
+    items
+        .into_iter()
+        .map(|item| WatchlistItem {
+            ticker: symbol::canonical(&item.ticker),
+            exchange: item.exchange.trim().to_uppercase(),
+            section: item.section.trim().to_string(),
+        })
+        .filter(|item| !item.ticker.is_empty() && seen.insert(item.ticker.clone()))
+        .collect()
+}
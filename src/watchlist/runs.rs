@@ -0,0 +1,483 @@
+use askama::Template;
+use axum::Json;
+use axum::extract::{Extension, Query};
+use axum::response::Html;
+use chrono::{DateTime, Local, NaiveDate};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::Stock;
+use crate::api_error::{ApiError, NotFoundError};
+use crate::config::APP_CONFIG;
+use crate::html_error::HtmlError;
+use crate::store::Store;
+
+/// One recorded watchlist: the tickers a stock_themes run served.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WatchlistRun {
+    pub id: i64,
+    pub run_date: NaiveDate,
+    pub created_at: DateTime<Local>,
+    /// Where the tickers came from: input files or "upload".
+    pub source: String,
+    pub size: i64,
+}
+
+/// A ticker as it was grouped when the run was recorded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunStock {
+    pub ticker: String,
+    pub exchange: String,
+    pub sector: String,
+    pub industry: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeKind {
+    Sector,
+    Industry,
+    Tag,
+}
+
+/// A sector, industry or tag whose members changed between two runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThemeChange {
+    pub kind: ThemeKind,
+    pub name: String,
+    pub before: usize,
+    pub after: usize,
+    pub change: i64,
+    pub joined: Vec<String>,
+    pub left: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchlistDiff {
+    pub run: WatchlistRun,
+    /// The run compared against; `None` for the first run recorded.
+    pub base: Option<WatchlistRun>,
+    /// Tickers in `run` but not in `base`.
+    pub added: Vec<RunStock>,
+    /// Tickers in `base` but not in `run`.
+    pub removed: Vec<RunStock>,
+    /// Themes that gained or lost members, biggest gainers first.
+    pub themes: Vec<ThemeChange>,
+}
+
+impl Store {
+    /// Saves `stocks` with their current tags as a new run dated today.
+    pub async fn record_watchlist_run(
+        &self,
+        stocks: &[Stock],
+        source: &str,
+    ) -> anyhow::Result<i64> {
+        let tags = self
+            .list_stock_tags()
+            .await?
+            .into_iter()
+            .map(|stock| (stock.ticker, stock.tags))
+            .collect::<HashMap<_, _>>();
+        let now = Local::now();
+
+        let mut tx = self.pool.begin().await?;
+        let run_id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO watchlist_runs (run_date, created_at, source)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(now.date_naive())
+        .bind(now)
+        .bind(source)
+        .fetch_one(&mut *tx)
+        .await?;
+        for stock in stocks {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO watchlist_run_stocks (run_id, ticker, exchange, sector, industry)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(run_id)
+            .bind(&stock.ticker)
+            .bind(&stock.exchange)
+            .bind(&stock.sector.name)
+            .bind(&stock.industry.name)
+            .execute(&mut *tx)
+            .await?;
+            for tag in tags.get(&stock.ticker).into_iter().flatten() {
+                sqlx::query(
+                    "INSERT OR IGNORE INTO watchlist_run_tags (run_id, ticker, tag) VALUES ($1, $2, $3)",
+                )
+                .bind(run_id)
+                .bind(&stock.ticker)
+                .bind(&tag.name)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(run_id)
+    }
+
+    /// Id and tickers of the most recent run, if any.
+    pub async fn latest_watchlist_run(&self) -> sqlx::Result<Option<(i64, BTreeSet<String>)>> {
+        let Some(run_id) =
+            sqlx::query_scalar::<_, i64>("SELECT id FROM watchlist_runs ORDER BY id DESC LIMIT 1")
+                .fetch_optional(&self.pool)
+                .await?
+        else {
+            return Ok(None);
+        };
+        let tickers = sqlx::query_scalar::<_, String>(
+            "SELECT ticker FROM watchlist_run_stocks WHERE run_id = $1",
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(Some((run_id, tickers.into_iter().collect())))
+    }
+
+    /// Recorded runs, newest first.
+    pub async fn list_watchlist_runs(&self) -> sqlx::Result<Vec<WatchlistRun>> {
+        sqlx::query_as::<_, WatchlistRun>(
+            r#"
+            SELECT r.id, r.run_date, r.created_at, r.source, COUNT(s.ticker) AS size
+            FROM watchlist_runs r
+            LEFT JOIN watchlist_run_stocks s ON s.run_id = r.id
+            GROUP BY r.id
+            ORDER BY r.id DESC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_watchlist_run_stocks(&self, run_id: i64) -> sqlx::Result<Vec<RunStock>> {
+        let stocks = sqlx::query_as::<_, (String, String, String, String)>(
+            r#"
+            SELECT ticker, exchange, sector, industry
+            FROM watchlist_run_stocks
+            WHERE run_id = $1
+            ORDER BY ticker
+            "#,
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut tags = sqlx::query_as::<_, (String, String)>(
+            "SELECT ticker, tag FROM watchlist_run_tags WHERE run_id = $1 ORDER BY tag",
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .into_group_map();
+
+        Ok(stocks
+            .into_iter()
+            .map(|(ticker, exchange, sector, industry)| RunStock {
+                tags: tags.remove(&ticker).unwrap_or_default(),
+                ticker,
+                exchange,
+                sector,
+                industry,
+            })
+            .collect())
+    }
+}
+
+/// Records the served stocks as a run, logging rather than failing: a
+/// missed run shouldn't take the page down. Nothing is recorded when the
+/// tickers match the latest run, so restarting on the same input doesn't
+/// leave "vs previous run" comparing identical runs.
+pub async fn record_run(store: &Store, stocks: &[Stock], source: &str) {
+    match store.latest_watchlist_run().await {
+        Ok(Some((id, latest))) if same_tickers(&latest, stocks) => {
+            info!("Watchlist unchanged since run {id}, not recording a new run");
+            return;
+        }
+        Ok(_) => {}
+        Err(err) => warn!("Failed to load the latest watchlist run: {err:#}"),
+    }
+    match store.record_watchlist_run(stocks, source).await {
+        Ok(id) => info!("Recorded watchlist run {id} with {} stocks", stocks.len()),
+        Err(err) => warn!("Failed to record watchlist run: {err:#}"),
+    }
+}
+
+fn same_tickers(latest: &BTreeSet<String>, stocks: &[Stock]) -> bool {
+    latest.len() == stocks.len() && stocks.iter().all(|stock| latest.contains(&stock.ticker))
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DiffQuery {
+    /// Run to look at; the latest when missing.
+    pub run: Option<i64>,
+    /// Run to compare against.
+    pub against: Option<i64>,
+    /// Compare against the latest earlier run on or before this date.
+    pub date: Option<NaiveDate>,
+}
+
+/// Picks the run to show and the one to compare it with from `runs`
+/// (newest first). Without `against` or `date` that's the run before it.
+fn pick_runs<'a>(
+    runs: &'a [WatchlistRun],
+    query: &DiffQuery,
+) -> anyhow::Result<(&'a WatchlistRun, Option<&'a WatchlistRun>)> {
+    let find = |id: i64| {
+        runs.iter()
+            .find(|run| run.id == id)
+            .ok_or_else(|| NotFoundError(format!("No watchlist run with id {id}")))
+    };
+    let run = match query.run {
+        Some(id) => find(id)?,
+        None => runs
+            .first()
+            .ok_or_else(|| NotFoundError("No watchlist runs recorded yet".to_string()))?,
+    };
+    let base = match (query.against, query.date) {
+        (Some(id), _) => Some(find(id)?),
+        (None, Some(date)) => runs
+            .iter()
+            .find(|other| other.id < run.id && other.run_date <= date),
+        (None, None) => runs.iter().find(|other| other.id < run.id),
+    };
+    Ok((run, base))
+}
+
+/// Tickers that entered and dropped out between `base` and `current`, and
+/// how each sector, industry and tag's membership changed.
+pub fn diff_stocks(
+    base: &[RunStock],
+    current: &[RunStock],
+) -> (Vec<RunStock>, Vec<RunStock>, Vec<ThemeChange>) {
+    let tickers = |stocks: &[RunStock]| {
+        stocks
+            .iter()
+            .map(|stock| stock.ticker.clone())
+            .collect::<BTreeSet<_>>()
+    };
+    let (before, after) = (tickers(base), tickers(current));
+    let added = current
+        .iter()
+        .filter(|stock| !before.contains(&stock.ticker))
+        .cloned()
+        .collect();
+    let removed = base
+        .iter()
+        .filter(|stock| !after.contains(&stock.ticker))
+        .cloned()
+        .collect();
+
+    let before = themes(base);
+    let after = themes(current);
+    let empty = BTreeSet::new();
+    let mut changes = before
+        .keys()
+        .chain(after.keys())
+        .unique()
+        .filter_map(|key| {
+            let was = before.get(key).unwrap_or(&empty);
+            let now = after.get(key).unwrap_or(&empty);
+            let joined = now.difference(was).cloned().collect_vec();
+            let left = was.difference(now).cloned().collect_vec();
+            if joined.is_empty() && left.is_empty() {
+                return None;
+            }
+            Some(ThemeChange {
+                kind: key.0,
+                name: key.1.clone(),
+                before: was.len(),
+                after: now.len(),
+                change: now.len() as i64 - was.len() as i64,
+                joined,
+                left,
+            })
+        })
+        .collect_vec();
+    changes.sort_by(|a, b| {
+        b.change
+            .cmp(&a.change)
+            .then(b.joined.len().cmp(&a.joined.len()))
+            .then(a.kind.cmp(&b.kind))
+            .then(a.name.cmp(&b.name))
+    });
+
+    (added, removed, changes)
+}
+
+fn themes(stocks: &[RunStock]) -> BTreeMap<(ThemeKind, String), BTreeSet<String>> {
+    let mut themes = BTreeMap::<_, BTreeSet<_>>::new();
+    for stock in stocks {
+        let memberships = [
+            (ThemeKind::Sector, stock.sector.clone()),
+            (ThemeKind::Industry, stock.industry.clone()),
+        ]
+        .into_iter()
+        .chain(stock.tags.iter().map(|tag| (ThemeKind::Tag, tag.clone())));
+        for key in memberships {
+            themes.entry(key).or_default().insert(stock.ticker.clone());
+        }
+    }
+    themes
+}
+
+async fn watchlist_diff(store: &Store, query: &DiffQuery) -> anyhow::Result<WatchlistDiff> {
+    let runs = store.list_watchlist_runs().await?;
+    let (run, base) = pick_runs(&runs, query)?;
+    let current = store.get_watchlist_run_stocks(run.id).await?;
+    let previous = match base {
+        Some(base) => store.get_watchlist_run_stocks(base.id).await?,
+        None => Vec::new(),
+    };
+    let (added, removed, themes) = diff_stocks(&previous, &current);
+    Ok(WatchlistDiff {
+        run: run.clone(),
+        base: base.cloned(),
+        added,
+        removed,
+        themes,
+    })
+}
+
+/// GET /api/watchlist/runs
+pub async fn runs_handler(
+    Extension(store): Extension<Arc<Store>>,
+) -> Result<Json<Vec<WatchlistRun>>, ApiError> {
+    Ok(Json(store.list_watchlist_runs().await?))
+}
+
+/// GET /api/watchlist/diff?run=12&against=10 or ?date=2026-01-05
+pub async fn diff_handler(
+    Extension(store): Extension<Arc<Store>>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<WatchlistDiff>, ApiError> {
+    Ok(Json(watchlist_diff(&store, &query).await?))
+}
+
+#[derive(Template)]
+#[template(path = "watchlist_diff.html")]
+struct WatchlistDiffTemplate {
+    benchmark: String,
+    runs_json: String,
+    query_json: String,
+    diff_json: String,
+}
+
+/// GET /watchlist_diff.html, same parameters as `/api/watchlist/diff`.
+pub async fn diff_home(
+    Extension(store): Extension<Arc<Store>>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Html<String>, HtmlError> {
+    let runs = store.list_watchlist_runs().await?;
+    let diff = match runs.is_empty() {
+        true => None,
+        false => Some(watchlist_diff(&store, &query).await?),
+    };
+    let html = WatchlistDiffTemplate {
+        benchmark: APP_CONFIG.base_ticker.to_uppercase(),
+        runs_json: serde_json::to_string(&runs)?,
+        query_json: serde_json::to_string(&query)?,
+        diff_json: serde_json::to_string(&diff)?,
+    }
+    .render()?;
+    Ok(Html(html))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stock(ticker: &str, industry: &str, tags: &[&str]) -> RunStock {
+        RunStock {
+            ticker: ticker.into(),
+            exchange: "NASDAQ".into(),
+            sector: "Technology".into(),
+            industry: industry.into(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    fn run(id: i64, date: &str) -> WatchlistRun {
+        WatchlistRun {
+            id,
+            run_date: date.parse().unwrap(),
+            created_at: Local::now(),
+            source: String::new(),
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn diff_reports_entrants_dropouts_and_theme_changes() {
+        let base = [
+            stock("AMD", "Semiconductors", &["AI"]),
+            stock("MSFT", "Software", &["AI", "Cloud"]),
+        ];
+        let current = [
+            stock("MSFT", "Software", &["AI", "Cloud"]),
+            stock("NVDA", "Semiconductors", &["AI"]),
+            stock("SMCI", "Hardware", &["AI"]),
+        ];
+
+        let (added, removed, themes) = diff_stocks(&base, &current);
+
+        assert_eq!(
+            added.iter().map(|s| s.ticker.as_str()).collect_vec(),
+            ["NVDA", "SMCI"]
+        );
+        assert_eq!(
+            removed.iter().map(|s| s.ticker.as_str()).collect_vec(),
+            ["AMD"]
+        );
+        let summary = themes
+            .iter()
+            .map(|t| (t.kind, t.name.as_str(), t.before, t.after))
+            .collect_vec();
+        assert_eq!(
+            summary,
+            [
+                (ThemeKind::Sector, "Technology", 2, 3),
+                (ThemeKind::Tag, "AI", 2, 3),
+                (ThemeKind::Industry, "Hardware", 0, 1),
+                (ThemeKind::Industry, "Semiconductors", 1, 1),
+            ]
+        );
+        assert_eq!(themes[3].joined, ["NVDA"]);
+        assert_eq!(themes[3].left, ["AMD"]);
+    }
+
+    #[test]
+    fn picks_previous_run_or_latest_on_date() {
+        let runs = [
+            run(3, "2026-01-07"),
+            run(2, "2026-01-06"),
+            run(1, "2026-01-02"),
+        ];
+
+        let (current, base) = pick_runs(&runs, &DiffQuery::default()).unwrap();
+        assert_eq!((current.id, base.map(|b| b.id)), (3, Some(2)));
+
+        let query = DiffQuery {
+            date: "2026-01-05".parse().ok(),
+            ..Default::default()
+        };
+        let (_, base) = pick_runs(&runs, &query).unwrap();
+        assert_eq!(base.map(|b| b.id), Some(1));
+
+        let query = DiffQuery {
+            run: Some(1),
+            ..Default::default()
+        };
+        let (current, base) = pick_runs(&runs, &query).unwrap();
+        assert_eq!((current.id, base.map(|b| b.id)), (1, None));
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::store::Store;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WatchlistItem {
+    pub ticker: String,
+    #[serde(default)]
+    pub exchange: String,
+    /// TradingView section the ticker is listed under, if any.
+    #[serde(default)]
+    pub section: String,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct WatchlistSummary {
+    pub id: i64,
+    pub name: String,
+    pub size: i64,
+    pub created_at: DateTime<Local>,
+    pub updated_at: DateTime<Local>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Watchlist {
+    #[serde(flatten)]
+    pub summary: WatchlistSummary,
+    pub items: Vec<WatchlistItem>,
+}
+
+impl Watchlist {
+    pub fn tickers(&self) -> Vec<String> {
+        self.items.iter().map(|item| item.ticker.clone()).collect()
+    }
+}
+
+impl Store {
+    pub async fn list_watchlists(&self) -> sqlx::Result<Vec<WatchlistSummary>> {
+        sqlx::query_as::<_, WatchlistSummary>(
+            r#"
+            SELECT w.id, w.name, COUNT(i.ticker) AS size, w.created_at, w.updated_at
+            FROM watchlists w
+            LEFT JOIN watchlist_items i ON i.watchlist_id = w.id
+            GROUP BY w.id
+            ORDER BY lower(w.name)
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Looks a watchlist up by name, ignoring case.
+    pub async fn get_watchlist(&self, name: &str) -> sqlx::Result<Option<Watchlist>> {
+        let summary = sqlx::query_as::<_, WatchlistSummary>(
+            r#"
+            SELECT w.id, w.name, COUNT(i.ticker) AS size, w.created_at, w.updated_at
+            FROM watchlists w
+            LEFT JOIN watchlist_items i ON i.watchlist_id = w.id
+            WHERE w.name = $1
+            GROUP BY w.id
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(summary) = summary else {
+            return Ok(None);
+        };
+
+        let items = sqlx::query_as::<_, WatchlistItem>(
+            r#"
+            SELECT ticker, exchange, section
+            FROM watchlist_items
+            WHERE watchlist_id = $1
+            ORDER BY position
+            "#,
+        )
+        .bind(summary.id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(Some(Watchlist { summary, items }))
+    }
+
+    /// When the watchlist named `name` was last saved, `None` if there is none.
+    pub async fn watchlist_updated_at(&self, name: &str) -> sqlx::Result<Option<DateTime<Local>>> {
+        sqlx::query_scalar("SELECT updated_at FROM watchlists WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    pub async fn watchlist_exists(&self, name: &str) -> sqlx::Result<bool> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM watchlists WHERE name = $1")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// Creates the watchlist if needed and replaces its items with `items`,
+    /// keeping their order.
+    pub async fn save_watchlist(&self, name: &str, items: &[WatchlistItem]) -> sqlx::Result<()> {
+        let now = Local::now();
+        let mut tx = self.pool.begin().await?;
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO watchlists (name, created_at, updated_at)
+            VALUES ($1, $2, $2)
+            ON CONFLICT(name) DO UPDATE SET updated_at = excluded.updated_at
+            RETURNING id
+            "#,
+        )
+        .bind(name)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query("DELETE FROM watchlist_items WHERE watchlist_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        for (position, item) in items.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO watchlist_items (watchlist_id, position, ticker, exchange, section)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(id)
+            .bind(position as i64)
+            .bind(&item.ticker)
+            .bind(&item.exchange)
+            .bind(&item.section)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn rename_watchlist(&self, name: &str, new_name: &str) -> sqlx::Result<bool> {
+        let result =
+            sqlx::query("UPDATE watchlists SET name = $2, updated_at = $3 WHERE name = $1")
+                .bind(name)
+                .bind(new_name)
+                .bind(Local::now())
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_watchlist(&self, name: &str) -> sqlx::Result<bool> {
+        let result = sqlx::query("DELETE FROM watchlists WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -8,7 +8,7 @@ mod tests;
 pub use error::YfError;
 pub use types::{BarSize, Candle, CompanyProfile, Range, TickerInfo, TimeSpec};
 
-use crate::{Group, Stock, StockInfoFetcher, util::BROWSER_UA};
+use crate::{Group, Stock, StockInfoFetcher, symbol, util::BROWSER_UA};
 use anyhow::Context;
 use chrono::{Local, TimeZone, Utc};
 use de::{ChartResponse, QuoteSummaryResponse};
@@ -143,25 +143,16 @@ impl YFinance {
         Ok(())
     }
 
-    fn exchange_map(exchange: &str) -> anyhow::Result<&'static str> {
-        match exchange {
-            "NYSE" => Ok("NYSE"),
-            "NYSE American" => Ok("ARCA"),
-            _ if exchange.starts_with("Nasdaq") => Ok("NASDAQ"),
-            _ if exchange.starts_with("OTC") => Ok("OTC"),
-            x => anyhow::bail!("Unknown exchange: '{x}'"),
-        }
-    }
-
     // -------------------------------------------------------------------------
     // Public API
     // -------------------------------------------------------------------------
 
     pub async fn fetch_ticker_info(&self, symbol: &str) -> anyhow::Result<TickerInfo> {
         let crumb = self.crumb().await?;
+        let yahoo = symbol::yahoo(symbol);
 
         let url = format!(
-            "https://query1.finance.yahoo.com/v10/finance/quoteSummary/{symbol}\
+            "https://query1.finance.yahoo.com/v10/finance/quoteSummary/{yahoo}\
              ?modules=assetProfile,price&crumb={crumb}"
         );
 
@@ -196,9 +187,10 @@ impl YFinance {
 
     pub async fn fetch_company_profile(&self, symbol: &str) -> anyhow::Result<CompanyProfile> {
         let crumb = self.crumb().await?;
+        let yahoo = symbol::yahoo(symbol);
 
         let url = format!(
-            "https://query1.finance.yahoo.com/v10/finance/quoteSummary/{symbol}\
+            "https://query1.finance.yahoo.com/v10/finance/quoteSummary/{yahoo}\
              ?modules=assetProfile&crumb={crumb}"
         );
 
@@ -240,15 +232,16 @@ impl YFinance {
     ) -> anyhow::Result<Vec<Candle>> {
         // No crumb required for v8/finance/chart — it's an open endpoint.
         let pre_post = bar.include_pre_post();
+        let yahoo = symbol::yahoo(symbol);
 
         let url = match time {
             TimeSpec::Range(range) => format!(
-                "https://query1.finance.yahoo.com/v8/finance/chart/{symbol}\
+                "https://query1.finance.yahoo.com/v8/finance/chart/{yahoo}\
                  ?interval={bar}&range={range}&includePrePost={pre_post}&includeAdjustedClose=true",
                 range = range.as_str(),
             ),
             TimeSpec::Interval(start, end) => format!(
-                "https://query1.finance.yahoo.com/v8/finance/chart/{symbol}\
+                "https://query1.finance.yahoo.com/v8/finance/chart/{yahoo}\
                  ?interval={bar}&period1={}&period2={}&includePrePost={pre_post}&includeAdjustedClose=true",
                 start.timestamp(),
                 end.timestamp(),
@@ -346,7 +339,8 @@ impl StockInfoFetcher for YFinance {
         let exchange = ti
             .exchange
             .with_context(|| format!("Failed to get exchange {ticker}"))?;
-        let exchange = Self::exchange_map(&exchange)?;
+        let exchange = symbol::tradingview_exchange(&exchange)
+            .with_context(|| format!("Unknown exchange: '{exchange}'"))?;
         let sector = ti
             .sector
             .with_context(|| format!("Failed to get sector {ticker}"))?;
@@ -354,7 +348,7 @@ impl StockInfoFetcher for YFinance {
             .industry
             .with_context(|| format!("Failed to get industry {ticker}"))?;
         Ok(Stock {
-            ticker: ticker.to_owned(),
+            ticker: symbol::canonical(ticker),
             exchange: exchange.to_owned(),
             sector: Group {
                 name: sector,